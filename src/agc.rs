@@ -0,0 +1,289 @@
+//! Automatic gain control for content without gain information.
+//!
+//! Livestreams carry no gain information and cannot be measured in advance. This module
+//! measures their short-term loudness while they play, and slowly steers it towards the
+//! normalization target.
+//!
+//! Features:
+//! * Short-term loudness according to EBU R128 (K-weighted, 3 second window)
+//! * Slow gain changes that keep the dynamics within songs and speech
+//! * Gain bounded in both directions
+//! * Silence and quiet passages gated, so that pauses are not boosted
+//!
+//! The gain may overshoot on sudden transients; follow with a [`limiter`](crate::limiter)
+//! for safety.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::{agc, limiter};
+//!
+//! let source = agc::agc(source, -15.0);
+//! let limited = limiter::limit(source, -1.0, release, None);
+//! ```
+
+use std::{collections::VecDeque, time::Duration};
+
+use biquad::{Biquad, DirectForm1};
+use rodio::{ChannelCount, SampleRate, Source, source::SeekError};
+
+use crate::{
+    r128::{self, Meter},
+    util,
+};
+
+/// Largest boost or cut in dB.
+pub const MAX_GAIN_DB: f32 = 12.0;
+
+/// Steps of 100 ms in the short-term loudness window of 3 seconds.
+const WINDOW_STEPS: usize = 30;
+
+/// Loudness below which the gain is held, in LUFS: silence and pauses.
+const SILENCE_GATE: f64 = -50.0;
+
+/// Loudness this far below the target holds the gain, in LU: quiet intros and fades.
+const QUIET_GATE: f64 = -20.0;
+
+/// Time constant for reducing the gain.
+const ATTACK_TIME: Duration = Duration::from_secs(3);
+
+/// Time constant for increasing the gain, slower so that quiet passages keep sounding quiet.
+const RELEASE_TIME: Duration = Duration::from_secs(10);
+
+/// Creates automatic gain control for an audio source.
+///
+/// # Arguments
+///
+/// * `input` - Audio source to process
+/// * `target` - Target loudness in LUFS
+pub fn agc<I>(input: I, target: f32) -> Agc<I>
+where
+    I: Source,
+{
+    let channels = usize::from(input.channels().max(1));
+    let sample_rate = input.sample_rate().max(1);
+
+    let (shelf, high_pass) = Meter::k_weighting(f64::from(sample_rate));
+    let filters = (0..channels)
+        .map(|_| [DirectForm1::new(shelf), DirectForm1::new(high_pass)])
+        .collect();
+
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let frames_per_step = (f64::from(sample_rate) / 10.0).round().max(1.0) as usize;
+
+    Agc {
+        input,
+        target: f64::from(target),
+        filters,
+        squares: vec![0.0; channels],
+        channel: 0,
+        frames: 0,
+        frames_per_step,
+        window: VecDeque::with_capacity(WINDOW_STEPS),
+        gain_db: 0.0,
+        desired_db: 0.0,
+        attack: duration_to_coefficient(ATTACK_TIME),
+        release: duration_to_coefficient(RELEASE_TIME),
+        ratio: 1.0,
+        delta: 0.0,
+    }
+}
+
+/// Converts a time constant to a smoothing coefficient per step of 100 ms.
+fn duration_to_coefficient(time: Duration) -> f64 {
+    1.0 - (-0.1 / time.as_secs_f64()).exp()
+}
+
+/// Audio source with automatic gain control.
+#[derive(Clone, Debug)]
+pub struct Agc<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Target loudness in LUFS
+    target: f64,
+
+    /// K-weighting filters per channel
+    filters: Vec<[DirectForm1<f64>; 2]>,
+
+    /// Sum of squares per channel in the current step
+    squares: Vec<f64>,
+
+    /// Channel of the next sample
+    channel: usize,
+
+    /// Frames collected in the current step
+    frames: usize,
+
+    /// Frames per step of 100 ms
+    frames_per_step: usize,
+
+    /// Weighted mean squares of the steps in the window
+    window: VecDeque<f64>,
+
+    /// Current gain in dB
+    gain_db: f64,
+
+    /// Gain the current gain moves towards, in dB
+    desired_db: f64,
+
+    /// Smoothing coefficient per step when reducing the gain
+    attack: f64,
+
+    /// Smoothing coefficient per step when increasing the gain
+    release: f64,
+
+    /// Current gain as a ratio
+    ratio: f32,
+
+    /// Change of the ratio per frame, to move smoothly between steps
+    delta: f32,
+}
+
+impl<I> Agc<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Returns the current gain in dB.
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation)]
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db as f32
+    }
+
+    /// Updates the gain at the end of a step.
+    fn finish_step(&mut self) {
+        #[expect(clippy::cast_precision_loss)]
+        let frames = self.frames as f64;
+        let channels = self.squares.len();
+        let power = self
+            .squares
+            .iter()
+            .enumerate()
+            .map(|(channel, squares)| r128::channel_weight(channel, channels) * squares / frames)
+            .sum();
+
+        if self.window.len() == WINDOW_STEPS {
+            self.window.pop_front();
+        }
+        self.window.push_back(power);
+        self.squares.fill(0.0);
+        self.frames = 0;
+
+        #[expect(clippy::cast_precision_loss)]
+        let short_term = r128::loudness(self.window.iter().sum::<f64>() / self.window.len() as f64);
+
+        // Hold the gain through silence and quiet passages.
+        if short_term > SILENCE_GATE && short_term > self.target + QUIET_GATE {
+            let max_gain = f64::from(MAX_GAIN_DB);
+            self.desired_db = (self.target - short_term).clamp(-max_gain, max_gain);
+        }
+
+        let coefficient = if self.desired_db < self.gain_db {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain_db += (self.desired_db - self.gain_db) * coefficient;
+
+        #[expect(clippy::cast_possible_truncation)]
+        let gain_db = self.gain_db as f32;
+        #[expect(clippy::cast_precision_loss)]
+        let frames_per_step = self.frames_per_step as f32;
+        self.delta = (util::db_to_ratio(gain_db) - self.ratio) / frames_per_step;
+    }
+}
+
+impl<I> Iterator for Agc<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let output = sample * self.ratio;
+
+        if let Some([shelf, high_pass]) = self.filters.get_mut(self.channel) {
+            let weighted = high_pass.run(shelf.run(f64::from(sample)));
+            self.squares[self.channel] += weighted * weighted;
+        }
+
+        self.channel += 1;
+        if self.channel >= usize::from(self.input.channels()) {
+            self.channel = 0;
+            self.frames += 1;
+            self.ratio += self.delta;
+            if self.frames >= self.frames_per_step {
+                self.finish_step();
+            }
+        }
+
+        Some(output)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Agc<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    ///
+    /// Keeps the current gain, but starts a new loudness window.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        for [shelf, high_pass] in &mut self.filters {
+            shelf.reset_state();
+            high_pass.reset_state();
+        }
+        self.squares.fill(0.0);
+        self.window.clear();
+        self.channel = 0;
+        self.frames = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+//! HTTP/JSON API to control pleezer and query its status.
+//!
+//! For home automation, pleezer can serve a small API next to Deezer Connect.
+//! Commands are handled like those from a Deezer client, which stays in sync.
+//!
+//! # Authentication
+//!
+//! Every request must carry the token from the secrets file as a bearer token:
+//! ```text
+//! Authorization: Bearer <api_token>
+//! ```
+//!
+//! # Endpoints
+//!
+//! | Method | Path          | Body                 | Action                     |
+//! |--------|---------------|----------------------|----------------------------|
+//! | GET    | `/status`     |                      | Get the player status      |
+//! | POST   | `/play`       |                      | Start or resume playback   |
+//! | POST   | `/pause`      |                      | Pause playback             |
+//! | POST   | `/next`       |                      | Skip to the next track     |
+//! | POST   | `/previous`   |                      | Skip to the previous track |
+//! | POST   | `/seek`       | `{"position": 90.5}` | Seek to seconds in track   |
+//! | POST   | `/volume`     | `{"volume": 50}`     | Set volume in percent      |
+//! | POST   | `/repeat`     | `{"mode": "all"}`    | Set repeat: none, all, one |
+//! | POST   | `/shuffle`    | `{"enabled": true}`  | Enable or disable shuffle  |
+//! | POST   | `/equalizer`  | `{"enabled": true}`  | Switch the equalizer       |
+//! | POST   | `/crossfeed`  | `{"enabled": true}`  | Switch headphone crossfeed |
+//! | POST   | `/night_mode` | `{"enabled": true}`  | Switch night mode          |
+//!
+//! All endpoints respond with the [`Status`](crate::control::Status) of the player
+//! after handling the request:
+//! ```json
+//! {
+//!     "connected": true,
+//!     "controller": "00000000-0000-0000-0000-000000000000",
+//!     "playing": true,
+//!     "track": {
+//!         "id": "123456789",
+//!         "type": "song",
+//!         "title": "Song Title",
+//!         "artist": "Artist Name",
+//!         "album": "Album Title",
+//!         "cover_id": "cover_id",
+//!         "cover_url": "https://cdn-images.dzcdn.net/images/cover/cover_id/500x500.jpg",
+//!         "duration": 215.0,
+//!         "format": "FLAC 1.411M",
+//!         "decoder": "PCM 16 bit 44.1 kHz, Stereo"
+//!     },
+//!     "position": 42.3,
+//!     "progress": 19.7,
+//!     "volume": 80.0,
+//!     "repeat": "none",
+//!     "shuffle": false,
+//!     "queue_position": 3,
+//!     "queue_length": 12,
+//!     "equalizer": false,
+//!     "crossfeed": false,
+//!     "night_mode": false,
+//!     "normalization_limiter": null,
+//!     "output_limiter": {
+//!         "limited": 0.4,
+//!         "max_reduction": 1.2
+//!     }
+//! }
+//! ```
+//!
+//! Errors respond with an HTTP status code and a message:
+//! ```json
+//! { "error": "no next track" }
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::api::Server;
+//!
+//! let server = Server::bind("127.0.0.1:8080".parse()?, token, client.control())?;
+//! // Serves until dropped.
+//! ```
+
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    header,
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde::Deserialize;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::{
+    control::{Command, Handle},
+    error::{Error, Result},
+    protocol::connect::{Percentage, RepeatMode},
+};
+
+/// Maximum size of a request body in bytes.
+const BODY_SIZE_MAX: usize = 1024;
+
+/// Time to wait before accepting connections again, after accepting failed.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Body of a seek request.
+#[derive(Deserialize)]
+struct Seek {
+    /// Position in seconds.
+    position: f64,
+}
+
+/// Body of a volume request.
+#[derive(Deserialize)]
+struct Volume {
+    /// Volume in percent.
+    volume: f32,
+}
+
+/// Body of a repeat request.
+#[derive(Deserialize)]
+struct Repeat {
+    /// Repeat mode: none, all or one.
+    mode: String,
+}
+
+/// Body of a shuffle request.
+#[derive(Deserialize)]
+struct Shuffle {
+    /// Whether to shuffle.
+    enabled: bool,
+}
+
+/// Body of a request to switch audio processing on or off.
+#[derive(Deserialize)]
+struct Switch {
+    /// Whether to switch on.
+    enabled: bool,
+}
+
+/// HTTP server for the control API.
+///
+/// Stops accepting connections when dropped.
+#[derive(Debug)]
+pub struct Server {
+    /// Task accepting connections
+    task: JoinHandle<()>,
+}
+
+impl Server {
+    /// Binds to an address and starts serving in the background.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address and port to listen on
+    /// * `token` - Bearer token that requests must carry
+    /// * `control` - Handle to control the player with
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if the token is empty, or an error if the address
+    /// cannot be bound.
+    pub fn bind(address: SocketAddr, token: impl Into<String>, control: Handle) -> Result<Self> {
+        let token: Arc<str> = token.into().into();
+        if token.trim().is_empty() {
+            return Err(Error::invalid_argument("api token must not be empty"));
+        }
+
+        // Bind synchronously, so that errors surface at startup.
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        info!("serving control api on http://{address}");
+
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        error!("failed to accept api connection: {e}");
+                        tokio::time::sleep(ACCEPT_BACKOFF).await;
+                        continue;
+                    }
+                };
+
+                let token = token.clone();
+                let control = control.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| {
+                        let token = token.clone();
+                        let control = control.clone();
+                        async move { Ok::<_, Infallible>(handle(request, &token, &control).await) }
+                    });
+
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        debug!("api connection from {peer} failed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Handles a request and builds the response.
+async fn handle(
+    request: Request<Incoming>,
+    token: &str,
+    control: &Handle,
+) -> Response<Full<Bytes>> {
+    if !is_authorized(&request, token) {
+        let mut response = error_response(&Error::unauthenticated("invalid or missing api token"));
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            header::HeaderValue::from_static("Bearer"),
+        );
+        return response;
+    }
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    trace!("api request: {method} {path}");
+
+    let result = match (method, path.as_str()) {
+        (Method::GET, "/status") => control.status().await,
+        (Method::POST, path) => match command(path, request.into_body()).await {
+            Ok(command) => control.send(command).await,
+            Err(e) => Err(e),
+        },
+        (_, path) => Err(Error::not_found(format!("no endpoint {path}"))),
+    };
+
+    match result.and_then(|status| serde_json::to_vec(&status).map_err(Into::into)) {
+        Ok(body) => json_response(StatusCode::OK, body),
+        Err(e) => {
+            debug!("api request failed: {e}");
+            error_response(&e)
+        }
+    }
+}
+
+/// Returns whether the request carries the bearer token.
+///
+/// Compares in constant time, so that the token cannot be guessed from response times.
+fn is_authorized(request: &Request<Incoming>, token: &str) -> bool {
+    let Some(bearer) = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+
+    let (bearer, token) = (bearer.trim().as_bytes(), token.as_bytes());
+    bearer.len() == token.len()
+        && bearer
+            .iter()
+            .zip(token)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Parses the command of a POST request.
+async fn command(path: &str, body: Incoming) -> Result<Command> {
+    let command = match path {
+        "/play" => Command::Play,
+        "/pause" => Command::Pause,
+        "/next" => Command::Next,
+        "/previous" => Command::Previous,
+        "/seek" => {
+            let Seek { position } = parse(body).await?;
+            let position = Duration::try_from_secs_f64(position).map_err(|e| {
+                Error::invalid_argument(format!("invalid position {position}: {e}"))
+            })?;
+            Command::Seek(position)
+        }
+        "/volume" => {
+            let Volume { volume } = parse(body).await?;
+            if !(0.0..=100.0).contains(&volume) {
+                return Err(Error::invalid_argument(format!(
+                    "invalid volume {volume}: expected 0 to 100"
+                )));
+            }
+            Command::Volume(Percentage::from_percent(volume))
+        }
+        "/repeat" => {
+            let Repeat { mode } = parse(body).await?;
+            match mode.parse() {
+                Ok(RepeatMode::Unrecognized) | Err(_) => {
+                    return Err(Error::invalid_argument(format!(
+                        "invalid repeat mode {mode}: expected none, all or one"
+                    )));
+                }
+                Ok(repeat_mode) => Command::Repeat(repeat_mode),
+            }
+        }
+        "/shuffle" => {
+            let Shuffle { enabled } = parse(body).await?;
+            Command::Shuffle(enabled)
+        }
+        "/equalizer" => {
+            let Switch { enabled } = parse(body).await?;
+            Command::Equalizer(enabled)
+        }
+        "/crossfeed" => {
+            let Switch { enabled } = parse(body).await?;
+            Command::Crossfeed(enabled)
+        }
+        "/night_mode" => {
+            let Switch { enabled } = parse(body).await?;
+            Command::NightMode(enabled)
+        }
+        _ => return Err(Error::not_found(format!("no endpoint {path}"))),
+    };
+
+    Ok(command)
+}
+
+/// Reads and parses a JSON request body.
+async fn parse<T>(body: Incoming) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let body = Limited::new(body, BODY_SIZE_MAX)
+        .collect()
+        .await
+        .map_err(|e| Error::invalid_argument(format!("failed to read request body: {e}")))?
+        .to_bytes();
+
+    serde_json::from_slice(&body)
+        .map_err(|e| Error::invalid_argument(format!("invalid request body: {e}")))
+}
+
+/// Builds a JSON response.
+fn json_response(status: StatusCode, body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// Builds a JSON response for an error, with the HTTP status code of its kind.
+fn error_response(error: &Error) -> Response<Full<Bytes>> {
+    let body = serde_json::json!({ "error": error.error.to_string() });
+    json_response(error.kind.into(), body.to_string())
+}
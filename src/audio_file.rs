@@ -26,7 +26,10 @@
 //! }
 //! ```
 
-use std::io::{BufReader, Read, Seek};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek},
+};
 
 use stream_download::{StreamDownload, storage::StorageProvider};
 use symphonia::core::io::MediaSource;
@@ -98,11 +101,33 @@ impl AudioFile {
     where
         P: StorageProvider + Sync + 'static,
         P::Reader: Sync,
+    {
+        Self::try_from_reader(track, download)
+    }
+
+    /// Creates a new `AudioFile` from a track and a local copy of its download.
+    ///
+    /// Handles buffering and decryption like [`try_from_download`](Self::try_from_download),
+    /// for example to analyze a downloaded track independently of its playback.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Unimplemented` - Track uses unsupported encryption
+    /// * `Error::PermissionDenied` - Decryption key not available
+    /// * `Error::InvalidData` - Failed to create decryptor
+    pub fn try_from_file(track: &Track, file: File) -> Result<Self> {
+        Self::try_from_reader(track, file)
+    }
+
+    /// Wraps a reader in a buffer, and in a decryptor for encrypted tracks.
+    fn try_from_reader<R>(track: &Track, reader: R) -> Result<Self>
+    where
+        R: ReadSeek + 'static,
     {
         let byte_len = track.file_size();
         let is_seekable = byte_len.is_some();
 
-        let buffered = BufReader::with_capacity(BUFFER_LEN, download);
+        let buffered = BufReader::with_capacity(BUFFER_LEN, reader);
 
         let result = if track.is_encrypted() {
             let decryptor = Decrypt::new(track, buffered)?;
@@ -0,0 +1,241 @@
+//! Playback positions of podcast episodes.
+//!
+//! Long episodes are rarely heard in one go. This module remembers where each episode
+//! was left, so that it resumes from there when it is queued again. With a file, the
+//! positions also survive restarts.
+//!
+//! Episodes heard past [`PLAYED_THRESHOLD`] are marked as played, and start from the
+//! beginning when queued again.
+//!
+//! # File Format
+//!
+//! Bookmarks are stored as TOML, with positions in seconds:
+//!
+//! ```toml
+//! [episodes.123456789]
+//! position = 1234
+//! played = false
+//! updated = 1735239915
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::bookmarks::Bookmarks;
+//!
+//! let mut bookmarks = Bookmarks::load(Some("bookmarks.toml".into()))?;
+//! if let Some(bookmark) = bookmarks.update(id, position, Some(duration), false) {
+//!     bookmarks.save()?;
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, DurationSeconds, TimestampSeconds, serde_as};
+use tempfile::NamedTempFile;
+
+use crate::{
+    error::{Error, Result},
+    track::TrackId,
+};
+
+/// Fraction of an episode after which it counts as played.
+pub const PLAYED_THRESHOLD: f32 = 0.95;
+
+/// Smallest change in position that is stored, unless forced.
+///
+/// Keeps writes to the file and requests to Deezer infrequent while playing.
+pub const UPDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum number of bookmarks to keep; the least recently updated are dropped.
+pub const MAX_BOOKMARKS: usize = 1000;
+
+/// Playback position of an episode.
+#[serde_as]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Position to resume from, in whole seconds.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub position: Duration,
+
+    /// Whether the episode was heard past [`PLAYED_THRESHOLD`].
+    #[serde(default)]
+    pub played: bool,
+
+    /// When the bookmark was last updated.
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub updated: SystemTime,
+}
+
+/// Bookmarks of episodes, optionally backed by a file.
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Bookmarks {
+    /// File to store the bookmarks in. `None` keeps them for the session only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+
+    /// Bookmark per episode.
+    #[serde(default)]
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    episodes: HashMap<TrackId, Bookmark>,
+
+    /// Whether bookmarks changed since the last save.
+    #[serde(skip)]
+    modified: bool,
+}
+
+impl Bookmarks {
+    /// Creates empty bookmarks, to be stored in a file if given.
+    #[must_use]
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// Loads bookmarks from a file.
+    ///
+    /// Starts empty if the file does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be read or parsed.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+
+        let mut bookmarks: Self = match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| {
+                Error::invalid_argument(format!("invalid bookmarks in {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        info!(
+            "loaded {} episode bookmarks from {}",
+            bookmarks.episodes.len(),
+            path.display()
+        );
+        bookmarks.path = Some(path);
+        Ok(bookmarks)
+    }
+
+    /// Returns the bookmark of an episode, if any.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, id: TrackId) -> Option<&Bookmark> {
+        self.episodes.get(&id)
+    }
+
+    /// Returns the position to resume an episode from.
+    ///
+    /// None if the episode has no bookmark, was played, or was left at the start.
+    #[must_use]
+    pub fn resume_position(&self, id: TrackId) -> Option<Duration> {
+        self.get(id)
+            .filter(|bookmark| !bookmark.played && !bookmark.position.is_zero())
+            .map(|bookmark| bookmark.position)
+    }
+
+    /// Records the playback position of an episode.
+    ///
+    /// Positions past [`PLAYED_THRESHOLD`] of the duration mark the episode as played,
+    /// at its full duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the episode
+    /// * `position` - Current playback position
+    /// * `duration` - Duration of the episode, if known
+    /// * `force` - Whether to store changes smaller than [`UPDATE_INTERVAL`]
+    ///
+    /// # Returns
+    ///
+    /// The new bookmark if it was stored, None if nothing changed enough.
+    pub fn update(
+        &mut self,
+        id: TrackId,
+        position: Duration,
+        duration: Option<Duration>,
+        force: bool,
+    ) -> Option<Bookmark> {
+        let duration = duration.filter(|duration| !duration.is_zero());
+        let played = duration
+            .is_some_and(|duration| position.div_duration_f32(duration) >= PLAYED_THRESHOLD);
+        let position = match duration {
+            Some(duration) if played => duration,
+            _ => position,
+        };
+        let position = Duration::from_secs(position.as_secs());
+
+        let (last_position, last_played) =
+            self.get(id).map_or((Duration::ZERO, false), |bookmark| {
+                (bookmark.position, bookmark.played)
+            });
+        if played == last_played
+            && (position == last_position
+                || (!force && position.abs_diff(last_position) < UPDATE_INTERVAL))
+        {
+            return None;
+        }
+
+        let bookmark = Bookmark {
+            position,
+            played,
+            updated: SystemTime::now(),
+        };
+        self.episodes.insert(id, bookmark);
+        self.modified = true;
+
+        if self.episodes.len() > MAX_BOOKMARKS {
+            if let Some(oldest) = self
+                .episodes
+                .iter()
+                .min_by_key(|(_, bookmark)| bookmark.updated)
+                .map(|(id, _)| *id)
+            {
+                self.episodes.remove(&oldest);
+            }
+        }
+
+        Some(bookmark)
+    }
+
+    /// Writes the bookmarks to their file, if any and if they changed.
+    ///
+    /// Replaces the file at once, so that it stays intact if writing fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be written.
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = self.path.as_ref().filter(|_| self.modified) else {
+            return Ok(());
+        };
+
+        let contents = toml::to_string(self)
+            .map_err(|e| Error::internal(format!("failed to serialize bookmarks: {e}")))?;
+
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let mut file = NamedTempFile::new_in(directory)?;
+        file.write_all(contents.as_bytes())?;
+        file.persist(path).map_err(|e| e.error)?;
+
+        self.modified = false;
+        Ok(())
+    }
+}
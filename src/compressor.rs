@@ -0,0 +1,361 @@
+//! Dynamic range compression for night mode.
+//!
+//! Late at night, the loud parts of films, concerts and dynamic recordings wake others
+//! up, while the quiet parts are hard to follow at a low volume. Night mode compresses
+//! the loud parts and raises the whole a bit, so that everything can be heard at a lower
+//! volume.
+//!
+//! Features:
+//! * Wideband downward compression with a soft knee
+//! * Peak detection and smoothing shared with [`normalize`](crate::normalize)
+//! * Coupled gain reduction across channels, to preserve imaging
+//! * Switchable at runtime, fading in and out without clicks
+//! * Time window to switch night mode on and off automatically
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::sync::{Arc, atomic::AtomicBool};
+//! use pleezer::compressor::{self, Settings};
+//!
+//! let enabled = Arc::new(AtomicBool::new(true));
+//! let compressed = compressor::compress(source, Settings::night(), enabled);
+//! ```
+
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::NaiveTime;
+use rodio::{ChannelCount, SampleRate, Source, source::SeekError};
+
+use crate::{
+    error::{Error, Result},
+    normalize,
+    util::{self, ZERO_DB},
+};
+
+/// Time to fade compression in or out when switched.
+const FADE_TIME: Duration = Duration::from_millis(200);
+
+/// Compressor settings.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct Settings {
+    /// Level where compression begins, in dBFS.
+    pub threshold: f32,
+
+    /// Ratio of input to output level above the threshold.
+    pub ratio: f32,
+
+    /// Width of the soft knee in dB.
+    pub knee_width: f32,
+
+    /// Time to respond to level increases.
+    pub attack: Duration,
+
+    /// Time to recover after level decreases.
+    pub release: Duration,
+
+    /// Gain after compression in dB.
+    pub makeup: f32,
+}
+
+impl Settings {
+    /// Returns the night mode preset.
+    ///
+    /// Compresses 4:1 above -24 dBFS, so that peaks are reduced by up to 18 dB, and
+    /// raises the rest by 6 dB. Releases slowly, so that speech does not pump.
+    #[must_use]
+    pub fn night() -> Self {
+        Self {
+            threshold: -24.0,
+            ratio: 4.0,
+            knee_width: 6.0,
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(300),
+            makeup: 6.0,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::night()
+    }
+}
+
+/// Time of day window, that may cross midnight.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Window {
+    /// Start of the window, inclusive.
+    pub start: NaiveTime,
+
+    /// End of the window, exclusive.
+    pub end: NaiveTime,
+}
+
+impl Window {
+    /// Returns whether a time of day lies within the window.
+    #[must_use]
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl FromStr for Window {
+    type Err = Error;
+
+    /// Parses a window like `22:00-07:00`.
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|e| {
+                Error::invalid_argument(format!("invalid time {time} in window {s}: {e}"))
+            })
+        };
+
+        let (start, end) = s.split_once('-').ok_or_else(|| {
+            Error::invalid_argument(format!("invalid window {s}: expected HH:MM-HH:MM"))
+        })?;
+
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+/// Creates a compressor for an audio source.
+///
+/// # Arguments
+///
+/// * `input` - Audio source to process
+/// * `settings` - Threshold, ratio and timing of the compressor
+/// * `enabled` - Runtime switch, shared with the player
+pub fn compress<I>(input: I, settings: Settings, enabled: Arc<AtomicBool>) -> Compress<I>
+where
+    I: Source,
+{
+    let sample_rate = input.sample_rate();
+    let channels = usize::from(input.channels().max(1));
+
+    // Start fully switched, so that tracks do not fade in or out.
+    let mix = if enabled.load(Ordering::Relaxed) {
+        1.0
+    } else {
+        0.0
+    };
+
+    Compress {
+        input,
+        threshold: settings.threshold,
+        knee_width: settings.knee_width,
+        inv_knee_8: 1.0 / (8.0 * settings.knee_width),
+        slope: 1.0 - 1.0 / settings.ratio.max(1.0),
+        makeup: settings.makeup,
+        attack: normalize::duration_to_coefficient(settings.attack, sample_rate),
+        release: normalize::duration_to_coefficient(settings.release, sample_rate),
+        fade: 1.0 - normalize::duration_to_coefficient(FADE_TIME, sample_rate),
+        enabled,
+        mix,
+        integrators: vec![ZERO_DB; channels],
+        peaks: vec![ZERO_DB; channels],
+        channel: 0,
+        gain: util::UNITY_GAIN,
+    }
+}
+
+/// Audio source with dynamic range compression.
+#[derive(Clone, Debug)]
+pub struct Compress<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Level where compression begins (dB)
+    threshold: f32,
+
+    /// Width of the soft knee (dB)
+    knee_width: f32,
+
+    /// Inverse of 8 times the knee width
+    inv_knee_8: f32,
+
+    /// Fraction of the overshoot that is reduced: 1 - 1/ratio
+    slope: f32,
+
+    /// Gain after compression (dB)
+    makeup: f32,
+
+    /// Attack coefficient
+    attack: f32,
+
+    /// Release coefficient
+    release: f32,
+
+    /// Fade coefficient when switching
+    fade: f32,
+
+    /// Runtime switch shared with the player
+    enabled: Arc<AtomicBool>,
+
+    /// How much of the compression is applied, from 0.0 (off) to 1.0 (on)
+    mix: f32,
+
+    /// Integrator state per channel
+    integrators: Vec<f32>,
+
+    /// Smoothed gain reduction per channel (dB)
+    peaks: Vec<f32>,
+
+    /// Channel of the next sample
+    channel: usize,
+
+    /// Gain applied to the current frame
+    gain: f32,
+}
+
+impl<I> Compress<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Clears the detector states.
+    fn reset(&mut self) {
+        self.integrators.fill(ZERO_DB);
+        self.peaks.fill(ZERO_DB);
+        self.gain = util::UNITY_GAIN;
+    }
+}
+
+impl<I> Iterator for Compress<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+
+        let channel = self.channel;
+        self.channel += 1;
+        if self.channel >= self.peaks.len() {
+            self.channel = 0;
+        }
+
+        if channel == 0 {
+            // Fade on frame boundaries, to keep channels in step.
+            let target = if self.enabled.load(Ordering::Relaxed) {
+                1.0
+            } else {
+                0.0
+            };
+            self.mix += (target - self.mix) * self.fade;
+            if target < self.mix && self.mix < 1e-4 {
+                self.mix = 0.0;
+                self.reset();
+            }
+        }
+
+        // Switched off and faded out: pass samples unchanged.
+        if self.mix <= 0.0 {
+            return Some(sample);
+        }
+
+        let output = sample * self.gain;
+
+        let reduction_db = self.slope
+            * normalize::process_sample(sample, self.threshold, self.knee_width, self.inv_knee_8);
+        normalize::detect_peak(
+            reduction_db,
+            &mut self.integrators[channel],
+            &mut self.peaks[channel],
+            self.attack,
+            self.release,
+        );
+
+        // Couple the gain across channels, once all of the frame have been detected.
+        if self.channel == 0 {
+            let peak = self.peaks.iter().fold(ZERO_DB, |max, &peak| max.max(peak));
+            self.gain = util::db_to_ratio(self.mix * (self.makeup - peak));
+        }
+
+        Some(output)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Compress<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    /// Also resets the detector states when successful.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        self.channel = 0;
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@
 //! * Authentication methods (email/password or ARL)
 //! * Device identification and settings
 //! * Network configuration (interface binding)
-//! * Audio configuration (volume, normalization)
+//! * Audio configuration (volume, normalization, resampling)
 //! * Track decryption configuration
 //! * API client settings
 //!
@@ -36,7 +36,7 @@
 //! };
 //! ```
 
-use std::net::IpAddr;
+use std::{net::IpAddr, path::PathBuf, time::Duration};
 
 use regex_lite::Regex;
 use uuid::Uuid;
@@ -44,10 +44,13 @@ use veil::Redact;
 
 use crate::{
     arl::Arl,
+    compressor, convolve, crossfade, crossfeed,
     decrypt::{KEY_LENGTH, Key},
+    equalizer,
     error::{Error, Result},
-    http,
+    http, mixer, normalize,
     protocol::connect::{DeviceType, Percentage},
+    resample, volume,
 };
 
 /// Authentication methods for Deezer.
@@ -165,15 +168,61 @@ pub struct Config {
     /// By default this is `false`.
     pub normalization: bool,
 
+    /// Whether to normalize by track or by album.
+    pub normalization_mode: normalize::Mode,
+
+    /// Whether to normalize podcast episodes with automatic gain control, like livestreams.
+    pub normalize_episodes: bool,
+
     /// Whether to apply equal-loudness compensation.
     pub loudness: bool,
 
+    /// Whether to apply headphone crossfeed to stereo content.
+    pub crossfeed: bool,
+
+    /// Cut frequency and feed level of the headphone crossfeed.
+    pub crossfeed_preset: crossfeed::Preset,
+
+    /// Whether to start with night mode compression.
+    pub night_mode: bool,
+
+    /// Time of day to switch night mode on and off automatically.
+    pub night_mode_hours: Option<compressor::Window>,
+
     /// Initial volume level.
     ///
     /// Used when no volume is reported by Deezer client or when reported as maximum.
     /// None means no volume override.
     pub initial_volume: Option<Percentage>,
 
+    /// Shape of the mapping from volume settings to output amplitudes.
+    pub volume_curve: volume::Curve,
+
+    /// Dynamic range of the logarithmic volume curve in dB.
+    pub volume_range: f32,
+
+    /// Highest volume that may be set.
+    ///
+    /// Higher volumes are capped. None means no limit.
+    pub max_volume: Option<Percentage>,
+
+    /// Lowest volume that is not muted.
+    ///
+    /// Lower volumes are raised, but zero still mutes. None means no floor.
+    pub min_volume: Option<Percentage>,
+
+    /// Whether to keep the output at full scale and leave the volume to an external
+    /// amplifier.
+    pub fixed_volume: bool,
+
+    /// Command to run on volume changes with a fixed output.
+    ///
+    /// Receives the volume in the `VOLUME` environment variable.
+    pub volume_command: Option<String>,
+
+    /// ALSA mixer control to set on volume changes with a fixed output.
+    pub volume_mixer: Option<mixer::AlsaControl>,
+
     /// Dither bit depth based on DAC linearity (ENOB - Effective Number of Bits)
     ///
     /// This setting enables dithering to improve audio quality when reducing bit depth.
@@ -210,6 +259,46 @@ pub struct Config {
     /// The actual filter characteristics depend on the sample rate (44.1kHz or 48kHz).
     pub noise_shaping: u8,
 
+    /// Quality preset for sample rate conversion.
+    ///
+    /// Tracks are converted to the sample rate of the audio output, so that tracks
+    /// of different sample rates play without reopening the output. Higher presets
+    /// are more transparent, but use more CPU. Tracks that already match the output
+    /// sample rate are not converted.
+    pub resample_quality: resample::Quality,
+
+    /// Whether to play tracks bit-perfect when possible.
+    ///
+    /// Reopens the audio device at the native sample rate and bit depth of each
+    /// track. Volume control and dithering are bypassed at full volume, when
+    /// normalization and loudness compensation are disabled.
+    pub bit_perfect: bool,
+
+    /// Duration of the crossfade between consecutive tracks.
+    ///
+    /// Zero disables crossfading, so tracks play gaplessly. Tracks queued from the
+    /// same album are never crossfaded.
+    pub crossfade: Duration,
+
+    /// Shape of the crossfade between consecutive tracks.
+    pub crossfade_curve: crossfade::Curve,
+
+    /// Parametric equalizer settings.
+    ///
+    /// `None` disables the equalizer.
+    pub equalizer: Option<equalizer::Settings>,
+
+    /// Impulse responses for room correction, loaded from a WAV file.
+    ///
+    /// The path may contain `{rate}` to select a file per output sample rate. `None`
+    /// disables convolution.
+    pub convolution: Option<convolve::Impulses>,
+
+    /// File to store the playback positions of podcast episodes in.
+    ///
+    /// `None` keeps them for the session only.
+    pub bookmarks: Option<PathBuf>,
+
     /// Maximum amount of RAM in bytes that can be used for storing audio files.
     /// `None` means use temporary files instead of RAM.
     pub max_ram: Option<u64>,
@@ -0,0 +1,361 @@
+//! Local control of playback, next to Deezer Connect.
+//!
+//! Deezer clients control pleezer over the Connect websocket. Local interfaces, like
+//! the [`api`](crate::api) for home automation, control it through a [`Handle`]
+//! instead. Commands are applied by the [remote client](crate::remote::Client) in the
+//! same way as commands from Deezer, so that a connected Deezer client stays in sync.
+//!
+//! Every request is answered with the [`Status`] of the player after handling it.
+//! Interfaces that publish the status, like MPRIS, can also watch it for changes, or
+//! subscribe to [events](crate::events::Event) as they happen.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::control::Command;
+//!
+//! let control = client.control();
+//! let status = control.send(Command::Pause).await?;
+//! assert!(!status.playing);
+//! ```
+
+use std::{fmt, time::Duration};
+
+use serde::Serialize;
+use serde_with::{DisplayFromStr, DurationSecondsWithFrac, serde_as};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+
+use crate::{
+    error::{Error, Result},
+    events::Event,
+    limiter,
+    protocol::connect::{Percentage, RepeatMode},
+    track::{Track, TrackId, TrackType},
+};
+
+/// Commands to control playback.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Start or resume playback
+    Play,
+
+    /// Pause playback
+    Pause,
+
+    /// Skip to the next track in the queue
+    Next,
+
+    /// Skip to the previous track in the queue
+    Previous,
+
+    /// Seek to a position within the current track
+    Seek(Duration),
+
+    /// Set the volume
+    Volume(Percentage),
+
+    /// Set the repeat mode
+    Repeat(RepeatMode),
+
+    /// Enable or disable shuffle
+    Shuffle(bool),
+
+    /// Switch the equalizer on or off
+    Equalizer(bool),
+
+    /// Switch headphone crossfeed on or off
+    Crossfeed(bool),
+
+    /// Switch night mode on or off
+    NightMode(bool),
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Play => write!(f, "play"),
+            Self::Pause => write!(f, "pause"),
+            Self::Next => write!(f, "next"),
+            Self::Previous => write!(f, "previous"),
+            Self::Seek(position) => write!(f, "seek to {:.1}s", position.as_secs_f32()),
+            Self::Volume(volume) => write!(f, "volume to {volume}"),
+            Self::Repeat(repeat_mode) => write!(f, "repeat {repeat_mode}"),
+            Self::Shuffle(shuffle) => write!(f, "shuffle {}", if *shuffle { "on" } else { "off" }),
+            Self::Equalizer(enabled) => {
+                write!(f, "equalizer {}", if *enabled { "on" } else { "off" })
+            }
+            Self::Crossfeed(enabled) => {
+                write!(f, "crossfeed {}", if *enabled { "on" } else { "off" })
+            }
+            Self::NightMode(enabled) => {
+                write!(f, "night mode {}", if *enabled { "on" } else { "off" })
+            }
+        }
+    }
+}
+
+/// Metadata of the current track.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TrackStatus {
+    /// Deezer ID of the track.
+    #[serde_as(as = "DisplayFromStr")]
+    pub id: TrackId,
+
+    /// Type of content: song, episode, livestream or chapter.
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "type")]
+    pub typ: TrackType,
+
+    /// Title of the song, episode or chapter.
+    pub title: Option<String>,
+
+    /// Artist, podcast, station or author.
+    pub artist: String,
+
+    /// Album or audio book.
+    pub album: Option<String>,
+
+    /// Cover art identifier.
+    pub cover_id: String,
+
+    /// URL of the cover art.
+    pub cover_url: Option<String>,
+
+    /// Duration in seconds, unknown for livestreams.
+    #[serde_as(as = "Option<DurationSecondsWithFrac<f64>>")]
+    pub duration: Option<Duration>,
+
+    /// Encoded audio format, like "FLAC 1.411M".
+    pub format: String,
+
+    /// Decoded audio format, like "PCM 16 bit 44.1 kHz, Stereo".
+    pub decoder: String,
+}
+
+impl TrackStatus {
+    /// Size of the cover art in pixels, as Deezer uses by default.
+    pub const COVER_RESOLUTION: u16 = 500;
+}
+
+impl From<&Track> for TrackStatus {
+    fn from(track: &Track) -> Self {
+        Self {
+            id: track.id(),
+            typ: track.typ(),
+            title: track.title().map(ToString::to_string),
+            artist: track.artist().to_string(),
+            album: track.album_title().map(ToString::to_string),
+            cover_id: track.cover_id().to_string(),
+            cover_url: track.cover_url(TrackStatus::COVER_RESOLUTION),
+            duration: track.duration(),
+            format: track.format(),
+            decoder: track.decoder(),
+        }
+    }
+}
+
+/// Gain reduction by a limiter on the current track.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct LimiterStatus {
+    /// Time with gain reduction, in percent of the track played so far.
+    pub limited: f32,
+
+    /// Largest gain reduction in dB.
+    pub max_reduction: f32,
+}
+
+impl From<&limiter::Stats> for LimiterStatus {
+    fn from(stats: &limiter::Stats) -> Self {
+        Self {
+            limited: stats.limited_ratio() * 100.0,
+            max_reduction: stats.max_reduction_db(),
+        }
+    }
+}
+
+/// State of the player.
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Status {
+    /// Whether a Deezer client is connected.
+    pub connected: bool,
+
+    /// Device ID of the connected Deezer client, if any.
+    pub controller: Option<String>,
+
+    /// Whether playback is running.
+    pub playing: bool,
+
+    /// Current track, if any.
+    pub track: Option<TrackStatus>,
+
+    /// Playback position within the current track, in seconds.
+    #[serde_as(as = "Option<DurationSecondsWithFrac<f64>>")]
+    pub position: Option<Duration>,
+
+    /// Playback progress within the current track, in percent.
+    pub progress: Option<f32>,
+
+    /// Volume in percent.
+    pub volume: f32,
+
+    /// Repeat mode: none, all or one.
+    pub repeat: String,
+
+    /// Whether the queue is shuffled.
+    pub shuffle: bool,
+
+    /// Position of the current track in the queue, in playing order.
+    pub queue_position: Option<usize>,
+
+    /// Number of tracks in the queue.
+    pub queue_length: usize,
+
+    /// Whether the equalizer is configured and switched on.
+    pub equalizer: bool,
+
+    /// Whether headphone crossfeed is switched on.
+    pub crossfeed: bool,
+
+    /// Whether night mode is switched on.
+    pub night_mode: bool,
+
+    /// Gain reduction after normalization, if the current track is limited there.
+    pub normalization_limiter: Option<LimiterStatus>,
+
+    /// Gain reduction before the output, unless the current track plays bit-perfect.
+    pub output_limiter: Option<LimiterStatus>,
+}
+
+/// Event with the state of the player after it, as sent to hook scripts and watchers.
+///
+/// Serializes to a flat JSON object:
+/// ```json
+/// { "event": "volume_changed", "volume": 40.0, "status": { ... } }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Notification<'a> {
+    /// What happened, with its payload.
+    #[serde(flatten)]
+    pub event: Event,
+
+    /// State of the player after the event.
+    pub status: &'a Status,
+}
+
+/// Request from a local interface, answered with the status after handling it.
+#[derive(Debug)]
+pub struct Request {
+    /// Command to handle, or `None` to only get the status.
+    pub command: Option<Command>,
+
+    /// Channel for the status, or the error of the command.
+    pub reply: oneshot::Sender<Result<Status>>,
+}
+
+/// Handle for local interfaces to control the player.
+///
+/// Cheap to clone. Requests are handled by the remote client in order.
+#[derive(Clone, Debug)]
+pub struct Handle {
+    /// Channel to the remote client
+    tx: mpsc::Sender<Request>,
+
+    /// Latest status, as published by the remote client
+    status: watch::Receiver<Status>,
+
+    /// Channel of events, to subscribe to
+    events: broadcast::Sender<Event>,
+}
+
+impl Handle {
+    /// Maximum number of requests waiting to be handled.
+    const CAPACITY: usize = 16;
+
+    /// Maximum number of events kept for subscribers that fall behind.
+    const EVENTS_CAPACITY: usize = 64;
+
+    /// Maximum time to wait for a request to be handled.
+    ///
+    /// Requests wait while the remote client reconnects.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Creates a handle, the receiver of its requests, and the senders of the status
+    /// and events.
+    #[must_use]
+    pub fn channel() -> (
+        Self,
+        mpsc::Receiver<Request>,
+        watch::Sender<Status>,
+        broadcast::Sender<Event>,
+    ) {
+        let (tx, rx) = mpsc::channel(Self::CAPACITY);
+        let (status_tx, status) = watch::channel(Status::default());
+        let (events, _) = broadcast::channel(Self::EVENTS_CAPACITY);
+        (
+            Self {
+                tx,
+                status,
+                events: events.clone(),
+            },
+            rx,
+            status_tx,
+            events,
+        )
+    }
+
+    /// Returns a receiver of the status, that is notified when it changes.
+    ///
+    /// The status is published when the player state changes, and every few seconds
+    /// while connected to a Deezer client. The playback position may be outdated in
+    /// between: use [`status`](Self::status) for the current position.
+    #[must_use]
+    pub fn watch(&self) -> watch::Receiver<Status> {
+        self.status.clone()
+    }
+
+    /// Returns a receiver of the events from now on.
+    ///
+    /// Events are sent after the status is published, so that the status from
+    /// [`watch`](Self::watch) reflects them. Subscribers that fall behind miss events.
+    #[must_use]
+    pub fn events(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Returns the status of the player.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DeadlineExceeded` if the remote client does not respond in time, or
+    /// `Unavailable` if it has stopped.
+    pub async fn status(&self) -> Result<Status> {
+        self.request(None).await
+    }
+
+    /// Sends a command and returns the status of the player after it.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the command fails, the remote client does not respond in time,
+    /// or it has stopped.
+    pub async fn send(&self, command: Command) -> Result<Status> {
+        self.request(Some(command)).await
+    }
+
+    /// Sends a request and waits for the reply.
+    async fn request(&self, command: Option<Command>) -> Result<Status> {
+        let (reply, rx) = oneshot::channel();
+        tokio::time::timeout(Self::TIMEOUT, async {
+            self.tx
+                .send(Request { command, reply })
+                .await
+                .map_err(|_| Error::unavailable("player has stopped"))?;
+            rx.await
+                .map_err(|_| Error::unavailable("player has stopped"))?
+        })
+        .await
+        .map_err(|_| Error::deadline_exceeded("player is not responding"))?
+    }
+}
@@ -0,0 +1,731 @@
+//! FIR convolution with impulse responses, for room correction.
+//!
+//! Applies impulse responses as exported by tools like REW or DRC-FIR, so that room
+//! correction runs within pleezer instead of on a separate DSP.
+//!
+//! Features:
+//! * Uniformly partitioned FFT convolution, for long filters at low CPU usage
+//! * Mono or stereo impulse responses from WAV files
+//! * Impulse responses selected per output sample rate, or resampled
+//! * Latency reporting for accurate playback progress
+//!
+//! # Architecture
+//!
+//! The impulse response is split into partitions of one block each, which are
+//! transformed to the frequency domain once. During playback:
+//! 1. Input frames are collected into blocks per channel
+//! 2. Each block is transformed together with the previous block (overlap-save)
+//! 3. The spectra of the most recent blocks are multiplied with the partitions and summed
+//! 4. The inverse transform yields the next block of output
+//!
+//! Output is delayed by one block, plus the delay of the impulse response peak. When
+//! the input ends, the adapter flushes for as long as that latency, so no audio is
+//! lost. See [`Kernel::latency`].
+//!
+//! # Sample Rates
+//!
+//! When the path contains `{rate}`, it is replaced with the common sample rates in Hz,
+//! so that a filter designed for each rate is used:
+//! ```text
+//! room-{rate}.wav -> room-44100.wav, room-48000.wav, ...
+//! ```
+//! All files that exist are loaded at startup. When none matches the output sample
+//! rate, or the path has no placeholder, the closest impulse response is resampled to
+//! the output sample rate when the output opens.
+
+use std::{collections::HashMap, fs::File, io, path::Path, sync::Arc, time::Duration};
+
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex, num_complex::Complex};
+use rodio::{ChannelCount, Sample, SampleRate, Source, buffer::SamplesBuffer, source::SeekError};
+use symphonia::{
+    core::{
+        audio::SampleBuffer,
+        codecs::{CodecRegistry, DecoderOptions},
+        errors::Error as SymphoniaError,
+        formats::{FormatOptions, FormatReader},
+        io::MediaSourceStream,
+    },
+    default::{codecs::PcmDecoder, formats::WavReader},
+};
+
+use crate::{
+    error::{Error, Result},
+    resample,
+    util::ToF32,
+};
+
+/// Number of frames per partition.
+///
+/// Sets the processing latency: about 21 ms at 48 kHz.
+pub const BLOCK_SIZE: usize = 1024;
+
+/// Placeholder in impulse response paths for the output sample rate.
+pub const RATE_PLACEHOLDER: &str = "{rate}";
+
+/// Sample rates to look for when a path has a placeholder.
+const COMMON_SAMPLE_RATES: [SampleRate; 6] = [44_100, 48_000, 88_200, 96_000, 176_400, 192_000];
+
+/// Maximum length of an impulse response in seconds.
+///
+/// Room correction filters are typically well below one second.
+const MAX_IMPULSE_SECONDS: u32 = 10;
+
+/// Impulse response loaded from a file.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Impulse {
+    /// Sample rate in Hz.
+    pub sample_rate: SampleRate,
+
+    /// Filter taps for each channel.
+    pub channels: Vec<Vec<f32>>,
+}
+
+impl Impulse {
+    /// Loads an impulse response from a WAV file.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// * File cannot be read or decoded
+    /// * File has no audio, or more than two channels
+    /// * Impulse response is longer than 10 seconds
+    pub fn load(path: &str) -> Result<Self> {
+        let file = File::open(path)?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut reader = WavReader::try_new(stream, &FormatOptions::default())?;
+
+        let track = reader
+            .default_track()
+            .ok_or_else(|| Error::not_found(format!("{path} has no audio")))?;
+        let track_id = track.id;
+
+        let mut codecs = CodecRegistry::new();
+        codecs.register_all::<PcmDecoder>();
+        let mut decoder = codecs.make(&track.codec_params, &DecoderOptions::default())?;
+
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .filter(|&rate| rate > 0)
+            .ok_or_else(|| Error::invalid_argument(format!("{path} has no sample rate")))?;
+        let channels = track
+            .codec_params
+            .channels
+            .map_or(0, |channels| channels.count());
+        if !(1..=2).contains(&channels) {
+            return Err(Error::unimplemented(format!(
+                "{path} has {channels} channels, only mono and stereo are supported"
+            )));
+        }
+
+        let max_samples = usize::try_from(MAX_IMPULSE_SECONDS * sample_rate)
+            .unwrap_or(usize::MAX)
+            .saturating_mul(channels);
+        let mut samples = Vec::new();
+        loop {
+            let packet = match reader.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+
+            let decoded = decoder.decode(&packet)?;
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+            buffer.copy_interleaved_ref(decoded);
+            samples.extend_from_slice(buffer.samples());
+
+            if samples.len() > max_samples {
+                return Err(Error::invalid_argument(format!(
+                    "{path} is longer than {MAX_IMPULSE_SECONDS} seconds"
+                )));
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(Error::invalid_argument(format!("{path} has no audio")));
+        }
+
+        let channels = (0..channels)
+            .map(|channel| {
+                samples
+                    .iter()
+                    .skip(channel)
+                    .step_by(channels)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            sample_rate,
+            channels,
+        })
+    }
+
+    /// Returns the number of taps per channel.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    /// Returns whether the impulse response has no taps.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the position of the peak, which is the delay that the filter adds.
+    ///
+    /// Minimum-phase filters peak near the start, linear-phase filters in the middle.
+    #[must_use]
+    pub fn peak(&self) -> usize {
+        self.channels
+            .iter()
+            .flat_map(|taps| taps.iter().enumerate())
+            .fold((0, 0.0_f32), |(peak, max), (position, tap)| {
+                if tap.abs() > max {
+                    (position, tap.abs())
+                } else {
+                    (peak, max)
+                }
+            })
+            .0
+    }
+
+    /// Converts the impulse response to another sample rate.
+    ///
+    /// Taps are scaled by the ratio of sample rates, so the gain of the filter stays
+    /// the same.
+    #[must_use]
+    pub fn resample(&self, sample_rate: SampleRate, quality: resample::Quality) -> Self {
+        if sample_rate == self.sample_rate {
+            return self.clone();
+        }
+
+        let channels = self.channels.len();
+        let interleaved: Vec<Sample> = (0..self.len())
+            .flat_map(|position| self.channels.iter().map(move |taps| taps[position]))
+            .collect();
+
+        #[expect(clippy::cast_possible_truncation)]
+        let source = SamplesBuffer::new(channels as ChannelCount, self.sample_rate, interleaved);
+        let scale = self.sample_rate.to_f32_lossy() / sample_rate.to_f32_lossy();
+        let resampled: Vec<Sample> = resample::resample(source, sample_rate, quality)
+            .map(|sample| sample * scale)
+            .collect();
+
+        let channels = (0..channels)
+            .map(|channel| {
+                resampled
+                    .iter()
+                    .skip(channel)
+                    .step_by(channels)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            sample_rate,
+            channels,
+        }
+    }
+}
+
+/// Impulse response prepared for partitioned convolution.
+#[derive(Clone)]
+pub struct Kernel {
+    /// Sample rate in Hz.
+    sample_rate: SampleRate,
+
+    /// Spectra of the partitions for each channel, scaled for the inverse transform.
+    partitions: Vec<Vec<Vec<Complex<f32>>>>,
+
+    /// Forward transform of two blocks.
+    forward: Arc<dyn RealToComplex<f32>>,
+
+    /// Inverse transform of two blocks.
+    inverse: Arc<dyn ComplexToReal<f32>>,
+
+    /// Total delay in frames.
+    latency: usize,
+}
+
+impl Kernel {
+    /// Prepares an impulse response for convolution.
+    #[must_use]
+    pub fn new(impulse: &Impulse) -> Self {
+        let size = 2 * BLOCK_SIZE;
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(size);
+        let inverse = planner.plan_fft_inverse(size);
+
+        // Scale here for the unnormalized inverse transform.
+        #[expect(clippy::cast_precision_loss)]
+        let scale = 1.0 / size as f32;
+
+        let mut time = forward.make_input_vec();
+        let partitions = impulse
+            .channels
+            .iter()
+            .map(|taps| {
+                taps.chunks(BLOCK_SIZE)
+                    .map(|chunk| {
+                        time.fill(0.0);
+                        for (slot, tap) in time.iter_mut().zip(chunk) {
+                            *slot = tap * scale;
+                        }
+                        let mut spectrum = forward.make_output_vec();
+                        // Only fails on buffers of the wrong size.
+                        let _ = forward.process(&mut time, &mut spectrum);
+                        spectrum
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            sample_rate: impulse.sample_rate,
+            partitions,
+            forward,
+            inverse,
+            latency: BLOCK_SIZE + impulse.peak(),
+        }
+    }
+
+    /// Returns the sample rate in Hz.
+    #[must_use]
+    #[inline]
+    pub fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    /// Returns the delay of the output relative to the input.
+    ///
+    /// This is one block of processing, plus the delay of the impulse response peak.
+    #[must_use]
+    pub fn latency(&self) -> Duration {
+        let nanos = self.latency as u64 * 1_000_000_000 / u64::from(self.sample_rate.max(1));
+        Duration::from_nanos(nanos)
+    }
+
+    /// Returns the number of partitions per channel.
+    #[must_use]
+    #[inline]
+    fn len(&self) -> usize {
+        self.partitions.first().map_or(0, Vec::len)
+    }
+}
+
+/// Impulse responses for room correction, as loaded from a path.
+///
+/// Loaded when parsing the configuration, so that errors show at startup.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Impulses {
+    /// Path to the impulse response, possibly with [`RATE_PLACEHOLDER`].
+    path: String,
+
+    /// Impulse responses that were found, in order of sample rate.
+    impulses: Vec<Impulse>,
+}
+
+impl Impulses {
+    /// Loads the impulse responses of a path.
+    ///
+    /// With [`RATE_PLACEHOLDER`], loads the files of all common sample rates that exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// * No impulse response exists for the path
+    /// * An impulse response cannot be loaded
+    pub fn load(path: &str) -> Result<Self> {
+        let impulses = if path.contains(RATE_PLACEHOLDER) {
+            let mut impulses = Vec::new();
+            for rate in COMMON_SAMPLE_RATES {
+                let file = path.replace(RATE_PLACEHOLDER, &rate.to_string());
+                if Path::new(&file).exists() {
+                    impulses.push(Impulse::load(&file)?);
+                    info!("loaded impulse response {file}");
+                } else {
+                    trace!("impulse response {file} not found");
+                }
+            }
+            impulses
+        } else {
+            vec![Impulse::load(path)?]
+        };
+
+        if impulses.is_empty() {
+            return Err(Error::not_found(format!(
+                "no impulse response found for {path}"
+            )));
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            impulses,
+        })
+    }
+
+    /// Returns the path that the impulse responses were loaded from.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the impulse response that best matches a sample rate.
+    ///
+    /// Prefers the exact rate, then the closest rate.
+    fn closest(&self, sample_rate: SampleRate) -> &Impulse {
+        self.impulses
+            .iter()
+            .min_by_key(|impulse| impulse.sample_rate.abs_diff(sample_rate))
+            .expect("impulse responses are never empty")
+    }
+}
+
+/// Impulse responses for room correction, prepared per output sample rate.
+///
+/// Kernels are prepared on first use for each sample rate and kept for later tracks.
+#[derive(Clone)]
+pub struct Kernels {
+    /// Loaded impulse responses.
+    impulses: Impulses,
+
+    /// Quality to resample impulse responses with.
+    quality: resample::Quality,
+
+    /// Prepared kernels by output sample rate.
+    kernels: HashMap<SampleRate, Arc<Kernel>>,
+}
+
+impl Kernels {
+    /// Creates kernels for loaded impulse responses.
+    ///
+    /// Nothing is prepared until a kernel is requested.
+    #[must_use]
+    pub fn new(impulses: Impulses, quality: resample::Quality) -> Self {
+        Self {
+            impulses,
+            quality,
+            kernels: HashMap::new(),
+        }
+    }
+
+    /// Returns the kernel for an output sample rate, preparing it if needed.
+    pub fn get(&mut self, sample_rate: SampleRate) -> Arc<Kernel> {
+        if let Some(kernel) = self.kernels.get(&sample_rate) {
+            return Arc::clone(kernel);
+        }
+
+        let impulse = self.impulses.closest(sample_rate);
+        if impulse.sample_rate != sample_rate {
+            info!(
+                "resampling impulse response from {:.1} to {:.1} kHz",
+                impulse.sample_rate.to_f32_lossy() / 1000.0,
+                sample_rate.to_f32_lossy() / 1000.0
+            );
+        }
+
+        let kernel = Arc::new(Kernel::new(&impulse.resample(sample_rate, self.quality)));
+        debug!(
+            "convolution kernel: {} taps in {} partitions, {:.1} ms latency",
+            impulse.len(),
+            kernel.len(),
+            kernel.latency().as_secs_f32() * 1000.0
+        );
+
+        self.kernels.insert(sample_rate, Arc::clone(&kernel));
+        kernel
+    }
+}
+
+/// Convolution state of one channel.
+#[derive(Clone)]
+struct Channel {
+    /// Index of the impulse response channel to apply.
+    index: usize,
+
+    /// Previous and current input block.
+    input: Vec<f32>,
+
+    /// Output block being played.
+    output: Vec<f32>,
+
+    /// Spectra of the most recent input blocks, as a ring buffer.
+    history: Vec<Vec<Complex<f32>>>,
+
+    /// Position in the history of the most recent spectrum.
+    head: usize,
+}
+
+/// Creates a convolution for an audio source.
+///
+/// Mono impulse responses apply to all channels. Stereo impulse responses apply to
+/// the first two channels, and the right channel also to any further channels.
+///
+/// # Arguments
+///
+/// * `input` - Audio source to convolve, at the sample rate of the kernel
+/// * `kernel` - Prepared impulse response
+pub fn convolve<I>(input: I, kernel: Arc<Kernel>) -> Convolve<I>
+where
+    I: Source,
+{
+    if input.sample_rate() != kernel.sample_rate {
+        warn!(
+            "convolving {} Hz audio with a {} Hz impulse response",
+            input.sample_rate(),
+            kernel.sample_rate
+        );
+    }
+
+    let channels = (0..usize::from(input.channels()).max(1))
+        .map(|channel| Channel {
+            index: channel.min(kernel.partitions.len().saturating_sub(1)),
+            input: vec![0.0; 2 * BLOCK_SIZE],
+            output: vec![0.0; BLOCK_SIZE],
+            history: vec![kernel.forward.make_output_vec(); kernel.len().max(1)],
+            head: 0,
+        })
+        .collect();
+
+    Convolve {
+        time: kernel.forward.make_input_vec(),
+        accumulator: kernel.forward.make_output_vec(),
+        forward_scratch: kernel.forward.make_scratch_vec(),
+        inverse_scratch: kernel.inverse.make_scratch_vec(),
+        input,
+        kernel,
+        channels,
+        channel: 0,
+        frame: 0,
+        flush: None,
+    }
+}
+
+/// Audio source convolved with an impulse response.
+///
+/// Output is delayed by the latency of the kernel, and lasts that much longer than
+/// the input.
+#[derive(Clone)]
+pub struct Convolve<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Prepared impulse response
+    kernel: Arc<Kernel>,
+
+    /// State of each channel
+    channels: Vec<Channel>,
+
+    /// Channel of the next sample
+    channel: usize,
+
+    /// Frame of the next sample within the block
+    frame: usize,
+
+    /// Samples left to flush after the input ended
+    flush: Option<usize>,
+
+    /// Time-domain buffer for the transforms
+    time: Vec<f32>,
+
+    /// Sum of the partition products
+    accumulator: Vec<Complex<f32>>,
+
+    /// Scratch space for the forward transform
+    forward_scratch: Vec<Complex<f32>>,
+
+    /// Scratch space for the inverse transform
+    inverse_scratch: Vec<Complex<f32>>,
+}
+
+impl<I> Convolve<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Returns the delay of the output relative to the input.
+    #[must_use]
+    #[inline]
+    pub fn latency(&self) -> Duration {
+        self.kernel.latency()
+    }
+
+    /// Convolves the current input block of each channel into its next output block.
+    fn process(&mut self) {
+        for channel in &mut self.channels {
+            let partitions = channel.history.len();
+            let Some(kernel) = self.kernel.partitions.get(channel.index) else {
+                continue;
+            };
+
+            // Transforms only fail on buffers of the wrong size.
+            self.time.copy_from_slice(&channel.input);
+            let _ = self.kernel.forward.process_with_scratch(
+                &mut self.time,
+                &mut channel.history[channel.head],
+                &mut self.forward_scratch,
+            );
+
+            self.accumulator.fill(Complex::default());
+            for (offset, partition) in kernel.iter().enumerate() {
+                let spectrum = &channel.history[(channel.head + partitions - offset) % partitions];
+                for ((sum, x), h) in self.accumulator.iter_mut().zip(spectrum).zip(partition) {
+                    *sum += x * h;
+                }
+            }
+
+            // The inverse transform requires real values at DC and Nyquist.
+            if let Some(dc) = self.accumulator.first_mut() {
+                dc.im = 0.0;
+            }
+            if let Some(nyquist) = self.accumulator.last_mut() {
+                nyquist.im = 0.0;
+            }
+            let _ = self.kernel.inverse.process_with_scratch(
+                &mut self.accumulator,
+                &mut self.time,
+                &mut self.inverse_scratch,
+            );
+
+            // Overlap-save: the second half is free of circular wrap-around.
+            channel.output.copy_from_slice(&self.time[BLOCK_SIZE..]);
+            channel.input.copy_within(BLOCK_SIZE.., 0);
+            channel.head = (channel.head + 1) % partitions;
+        }
+    }
+
+    /// Clears all buffered audio.
+    fn reset(&mut self) {
+        for channel in &mut self.channels {
+            channel.input.fill(0.0);
+            channel.output.fill(0.0);
+            for spectrum in &mut channel.history {
+                spectrum.fill(Complex::default());
+            }
+            channel.head = 0;
+        }
+        self.channel = 0;
+        self.frame = 0;
+        self.flush = None;
+    }
+}
+
+impl<I> Iterator for Convolve<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = match self.flush {
+            None => match self.input.next() {
+                Some(sample) => sample,
+                None => {
+                    // Complete the frame, then play out the latency.
+                    let channels = self.channels.len();
+                    let remaining =
+                        (channels - self.channel) % channels + self.kernel.latency * channels;
+                    if remaining == 0 {
+                        return None;
+                    }
+                    self.flush = Some(remaining - 1);
+                    0.0
+                }
+            },
+            Some(0) => return None,
+            Some(remaining) => {
+                self.flush = Some(remaining - 1);
+                0.0
+            }
+        };
+
+        let channel = &mut self.channels[self.channel];
+        let output = channel.output[self.frame];
+        channel.input[BLOCK_SIZE + self.frame] = sample;
+
+        self.channel += 1;
+        if self.channel == self.channels.len() {
+            self.channel = 0;
+            self.frame += 1;
+            if self.frame == BLOCK_SIZE {
+                self.frame = 0;
+                self.process();
+            }
+        }
+
+        Some(output)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.flush {
+            None => (self.input.size_hint().0, None),
+            Some(remaining) => (remaining, Some(remaining)),
+        }
+    }
+}
+
+impl<I> Source for Convolve<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        match self.flush {
+            None => self.input.current_span_len(),
+            Some(remaining) => Some(remaining),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input
+            .total_duration()
+            .map(|duration| duration + self.latency())
+    }
+
+    /// Attempts to seek to the specified position.
+    /// Also clears buffered audio when successful, so output is delayed again.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        Ok(())
+    }
+}
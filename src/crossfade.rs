@@ -0,0 +1,516 @@
+//! Crossfading between consecutive tracks.
+//!
+//! This module overlaps the end of one track with the start of the next, instead of
+//! switching between them abruptly. The outgoing track fades out while the incoming
+//! track fades in, following a selectable curve.
+//!
+//! # Architecture
+//!
+//! Tracks play one after another from a queue of sources, so the overlap is mixed
+//! into the outgoing track:
+//! 1. Each track is wrapped in a [`Crossfade`] when it is loaded
+//! 2. When the next track is preloaded, it is attached to the [`Handle`] of the
+//!    outgoing track, which returns a [`Continuation`] to queue instead
+//! 3. At the start of the fade, the outgoing track takes the incoming track from its
+//!    handle and mixes it in
+//! 4. When the outgoing track ends, it hands the incoming track back, and the
+//!    continuation plays the rest of it
+//!
+//! When the incoming track is not attached by the start of the fade, or when the
+//! tracks differ in channel count or sample rate, the tracks play gaplessly instead.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use pleezer::crossfade::{Curve, crossfade};
+//!
+//! let (outgoing, handle) = crossfade(
+//!     current,
+//!     Duration::from_secs(5),
+//!     duration,
+//!     Duration::ZERO,
+//!     Curve::EqualPower,
+//! );
+//! queue.append(outgoing);
+//! queue.append(handle.attach(next));
+//! ```
+
+use std::{
+    f32::consts::FRAC_PI_2,
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use rodio::{ChannelCount, Sample, SampleRate, Source, source::SeekError};
+
+use crate::error::{Error, Result};
+
+/// Shape of the fade between two tracks.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Curve {
+    /// Gains change linearly.
+    ///
+    /// Keeps the amplitude constant, which suits closely related material. For unrelated
+    /// tracks, loudness dips by about 3 dB halfway through the fade.
+    Linear,
+
+    /// Gains follow a quarter sine and cosine.
+    ///
+    /// Keeps the power constant, so unrelated tracks keep their loudness throughout
+    /// the fade.
+    #[default]
+    EqualPower,
+}
+
+impl Curve {
+    /// Returns the gains of the outgoing and incoming track.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - Position in the fade, from 0.0 at the start to 1.0 at the end
+    #[must_use]
+    fn gains(self, progress: f32) -> (f32, f32) {
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => (1.0 - progress, progress),
+            Self::EqualPower => {
+                let angle = progress * FRAC_PI_2;
+                (angle.cos(), angle.sin())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => write!(f, "linear"),
+            Self::EqualPower => write!(f, "equal-power"),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "equal-power" => Ok(Self::EqualPower),
+            _ => Err(Error::invalid_argument(format!(
+                "invalid crossfade curve {s}"
+            ))),
+        }
+    }
+}
+
+/// Wraps a track so that it can crossfade into the next.
+///
+/// The fade starts `duration` before the end of the track. Without a total duration,
+/// the track never fades. Neither does a track that starts past the start of the fade.
+///
+/// # Arguments
+///
+/// * `input` - Processed source of the outgoing track, before volume and dither
+/// * `duration` - Duration of the fade
+/// * `total_duration` - Duration of the outgoing track, if known
+/// * `start` - Position that the input was seeked to before wrapping it
+/// * `curve` - Shape of the fade
+///
+/// # Returns
+///
+/// The wrapped source, and the handle to attach the next track to.
+pub fn crossfade<I>(
+    input: I,
+    duration: Duration,
+    total_duration: Option<Duration>,
+    start: Duration,
+    curve: Curve,
+) -> (Crossfade<I>, Arc<Handle>)
+where
+    I: Source,
+{
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+
+    let fade_len = samples(duration, sample_rate, channels);
+    let fade_start = total_duration.map(|total_duration| {
+        samples(total_duration, sample_rate, channels).saturating_sub(fade_len)
+    });
+
+    let handle = Arc::new(Handle::default());
+    let crossfade = Crossfade {
+        input,
+        handle: Arc::clone(&handle),
+        curve,
+        channels,
+        sample_rate,
+        position: samples(start, sample_rate, channels),
+        fade_start,
+        fade_len,
+        incoming: None,
+    };
+
+    (crossfade, handle)
+}
+
+/// Converts a duration to a number of interleaved samples, aligned to whole frames.
+fn samples(duration: Duration, sample_rate: SampleRate, channels: ChannelCount) -> u64 {
+    let frames = duration.as_nanos() * u128::from(sample_rate) / 1_000_000_000;
+    u64::try_from(frames * u128::from(channels)).unwrap_or(u64::MAX)
+}
+
+/// Converts a number of interleaved samples to a duration.
+fn duration(samples: u64, sample_rate: SampleRate, channels: ChannelCount) -> Duration {
+    let rate = u64::from(sample_rate) * u64::from(channels.max(1));
+    if rate == 0 {
+        return Duration::ZERO;
+    }
+
+    Duration::from_secs(samples / rate)
+        + Duration::from_nanos(samples % rate * 1_000_000_000 / rate)
+}
+
+/// Link between an outgoing track and the track that it fades into.
+///
+/// Shared between the player, the outgoing [`Crossfade`] and the incoming
+/// [`Continuation`].
+#[derive(Default)]
+pub struct Handle {
+    /// Incoming track, while it is not being mixed.
+    next: Mutex<Option<Box<dyn Source + Send>>>,
+
+    /// Whether the incoming track was detached, and must no longer be mixed.
+    detached: AtomicBool,
+
+    /// Duration of the incoming track that was mixed into the outgoing track, in
+    /// nanoseconds.
+    overlap: AtomicU64,
+}
+
+impl Handle {
+    /// Attaches the next track to fade into.
+    ///
+    /// # Returns
+    ///
+    /// A source that plays the next track after the fade. Queue it after the outgoing
+    /// track, instead of the next track itself.
+    pub fn attach<S>(self: &Arc<Self>, source: S) -> Continuation
+    where
+        S: Source + Send + 'static,
+    {
+        let continuation = Continuation {
+            handle: Arc::clone(self),
+            source: None,
+            channels: source.channels(),
+            sample_rate: source.sample_rate(),
+            total_duration: source.total_duration(),
+        };
+
+        *self.next.lock().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(source));
+        self.detached.store(false, Ordering::Relaxed);
+        self.overlap.store(0, Ordering::Relaxed);
+
+        continuation
+    }
+
+    /// Detaches the next track, for example when it was dropped from the queue.
+    ///
+    /// A fade that is in progress stops mixing in the next track.
+    pub fn detach(&self) {
+        self.detached.store(true, Ordering::Relaxed);
+        self.next
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+
+    /// Returns how much of the next track played during the fade.
+    ///
+    /// Valid once the outgoing track has ended. The next track continues from there.
+    #[must_use]
+    pub fn overlap(&self) -> Duration {
+        Duration::from_nanos(self.overlap.load(Ordering::Relaxed))
+    }
+
+    /// Takes the next track for mixing, if it matches the outgoing channel count and
+    /// sample rate.
+    fn take(
+        &self,
+        channels: ChannelCount,
+        sample_rate: SampleRate,
+    ) -> Option<Box<dyn Source + Send>> {
+        let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+        if next
+            .as_ref()
+            .is_some_and(|next| next.channels() == channels && next.sample_rate() == sample_rate)
+        {
+            next.take()
+        } else {
+            if next.is_some() {
+                debug!("not crossfading between different channel counts or sample rates");
+            }
+            None
+        }
+    }
+
+    /// Hands the next track back after mixing, unless it was detached or replaced.
+    fn put_back(&self, source: Box<dyn Source + Send>) {
+        if !self.detached.load(Ordering::Relaxed) {
+            self.next
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get_or_insert(source);
+        }
+    }
+}
+
+/// Audio source that fades out into the next track.
+///
+/// Plays the input unchanged until the fade starts. During the fade, mixes in the
+/// start of the next track, if one was attached to its [`Handle`] in time.
+pub struct Crossfade<I> {
+    /// The outgoing audio source
+    input: I,
+
+    /// Link to the incoming track
+    handle: Arc<Handle>,
+
+    /// Shape of the fade
+    curve: Curve,
+
+    /// Channel count of the input
+    channels: ChannelCount,
+
+    /// Sample rate of the input in Hz
+    sample_rate: SampleRate,
+
+    /// Sample position of the input, counted from the start of the track
+    position: u64,
+
+    /// Sample position where the fade starts, if the input duration is known
+    fade_start: Option<u64>,
+
+    /// Number of samples that the fade lasts
+    fade_len: u64,
+
+    /// Incoming track while it is being mixed
+    incoming: Option<Box<dyn Source + Send>>,
+}
+
+impl<I> Crossfade<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Returns the number of samples mixed from the incoming track so far.
+    fn mixed(&self) -> u64 {
+        self.fade_start
+            .map_or(0, |fade_start| self.position.saturating_sub(fade_start))
+    }
+
+    /// Hands the incoming track back to the handle, with its playback position.
+    fn finish(&mut self) {
+        if let Some(incoming) = self.incoming.take() {
+            let overlap = duration(self.mixed(), self.sample_rate, self.channels);
+            self.handle.overlap.store(
+                u64::try_from(overlap.as_nanos()).unwrap_or(u64::MAX),
+                Ordering::Relaxed,
+            );
+            self.handle.put_back(incoming);
+        }
+    }
+}
+
+impl<I> Iterator for Crossfade<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Only a fade that starts during playback mixes in the next track. After seeking
+        // past the start of the fade, the tracks play gaplessly instead.
+        if self.incoming.is_none() && self.fade_start == Some(self.position) {
+            self.incoming = self.handle.take(self.channels, self.sample_rate);
+        }
+
+        let Some(sample) = self.input.next() else {
+            self.finish();
+            return None;
+        };
+
+        if self.incoming.is_some() && self.handle.detached.load(Ordering::Relaxed) {
+            self.incoming = None;
+        }
+
+        let Some(incoming) = self.incoming.as_mut() else {
+            self.position = self.position.saturating_add(1);
+            return Some(sample);
+        };
+
+        #[expect(clippy::cast_precision_loss)]
+        let progress = self.mixed() as f32 / self.fade_len.max(1) as f32;
+        let (fade_out, fade_in) = self.curve.gains(progress);
+        self.position = self.position.saturating_add(1);
+
+        // The incoming track may be shorter than the fade.
+        let next = incoming.next().unwrap_or_default();
+        Some(sample * fade_out + next * fade_in)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Crossfade<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    ///
+    /// Cancels a fade in progress, and rewinds the incoming track so that it plays
+    /// from the start.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.position = samples(pos, self.sample_rate, self.channels);
+
+        if let Some(mut incoming) = self.incoming.take() {
+            if let Err(e) = incoming.try_seek(Duration::ZERO) {
+                warn!("failed to rewind next track after seeking: {e}");
+            }
+            self.handle.put_back(incoming);
+        }
+
+        Ok(())
+    }
+}
+
+/// Audio source that plays the rest of a track after it faded in.
+///
+/// Returned by [`Handle::attach`]. Plays nothing until the outgoing track has ended,
+/// and then continues the incoming track where the fade left off.
+pub struct Continuation {
+    /// Link to the outgoing track
+    handle: Arc<Handle>,
+
+    /// Incoming track, once it was handed back
+    source: Option<Box<dyn Source + Send>>,
+
+    /// Channel count of the incoming track
+    channels: ChannelCount,
+
+    /// Sample rate of the incoming track in Hz
+    sample_rate: SampleRate,
+
+    /// Total duration of the incoming track, if known
+    total_duration: Option<Duration>,
+}
+
+impl Continuation {
+    /// Returns the incoming track, taking it from the handle when first called.
+    fn source(&mut self) -> Option<&mut Box<dyn Source + Send>> {
+        if self.source.is_none() {
+            self.source = self
+                .handle
+                .next
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        }
+        self.source.as_mut()
+    }
+}
+
+impl Iterator for Continuation {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source()?.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.source.as_ref().map_or((0, None), Iterator::size_hint)
+    }
+}
+
+impl Source for Continuation {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.as_ref().and_then(Source::current_span_len)
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.source.as_ref().map_or(self.channels, Source::channels)
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.source
+            .as_ref()
+            .map_or(self.sample_rate, Source::sample_rate)
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        match self.source() {
+            Some(source) => source.try_seek(pos),
+            None => Err(SeekError::NotSupported {
+                underlying_source: std::any::type_name::<Self>(),
+            }),
+        }
+    }
+}
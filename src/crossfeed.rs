@@ -0,0 +1,358 @@
+//! Headphone crossfeed, after the Bauer stereophonic-to-binaural (BS2B) design.
+//!
+//! On speakers, each ear hears both speakers, with the far speaker slightly delayed and
+//! muffled by the head. On headphones, each ear hears only one channel, so hard-panned
+//! instruments sound unnaturally wide and tiring over time. Crossfeed mixes a low-passed
+//! and delayed part of each channel into the other, like the head would.
+//!
+//! Features:
+//! * Presets for the cut frequency and feed level
+//! * Direct signal high-boosted, so that the overall tonal balance is kept
+//! * Switchable at runtime without reloading tracks, with a short fade
+//! * Applies to stereo content only
+//!
+//! # Implementation
+//!
+//! Per channel, a first-order low-pass filter produces the crossfed signal, and a
+//! first-order high-boost filter shapes the direct signal. Their sum is scaled down by
+//! the bass gain, so that mono content keeps its level.
+
+use std::{
+    f64::consts::PI,
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use rodio::{ChannelCount, SampleRate, Source, source::SeekError};
+
+use crate::{
+    error::{Error, Result},
+    normalize,
+};
+
+/// Time to fade crossfeed in or out when switched.
+const FADE_TIME: Duration = Duration::from_millis(200);
+
+/// Crossfeed preset with a cut frequency and feed level.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Preset {
+    /// 700 Hz, 4.5 dB: closest to the virtual speaker placement of most recordings.
+    #[default]
+    Default,
+
+    /// 700 Hz, 6.0 dB: after the crossfeed circuit of Chu Moy.
+    ChuMoy,
+
+    /// 650 Hz, 9.5 dB: after the crossfeed circuit of Jan Meier, the strongest effect.
+    JanMeier,
+}
+
+impl Preset {
+    /// Returns the cut frequency of the low-pass filter in Hz.
+    #[must_use]
+    pub fn cut_frequency(self) -> f64 {
+        match self {
+            Self::Default | Self::ChuMoy => 700.0,
+            Self::JanMeier => 650.0,
+        }
+    }
+
+    /// Returns the feed level in dB, the difference in gain between low and high
+    /// frequencies of the crossfed signal.
+    #[must_use]
+    pub fn feed_level(self) -> f64 {
+        match self {
+            Self::Default => 4.5,
+            Self::ChuMoy => 6.0,
+            Self::JanMeier => 9.5,
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::ChuMoy => write!(f, "chu-moy"),
+            Self::JanMeier => write!(f, "jan-meier"),
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(Self::Default),
+            "chu-moy" | "cmoy" => Ok(Self::ChuMoy),
+            "jan-meier" | "jmeier" => Ok(Self::JanMeier),
+            _ => Err(Error::invalid_argument(format!(
+                "invalid crossfeed preset: {s}"
+            ))),
+        }
+    }
+}
+
+/// Filter coefficients for a preset at a sample rate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Coefficients {
+    /// Input gain of the low-pass filter
+    a0_lo: f32,
+    /// Feedback of the low-pass filter
+    b1_lo: f32,
+    /// Input gain of the high-boost filter
+    a0_hi: f32,
+    /// Previous input gain of the high-boost filter
+    a1_hi: f32,
+    /// Feedback of the high-boost filter
+    b1_hi: f32,
+    /// Output gain that compensates for the bass boost
+    gain: f32,
+}
+
+impl Coefficients {
+    /// Calculates the coefficients for a preset at a sample rate.
+    #[expect(clippy::cast_possible_truncation)]
+    fn new(preset: Preset, sample_rate: SampleRate) -> Self {
+        let level = preset.feed_level();
+        let sample_rate = f64::from(sample_rate.max(1));
+
+        let gain_lo_db = level * -5.0 / 6.0 - 3.0;
+        let gain_hi_db = level / 6.0 - 3.0;
+
+        let gain_lo = 10.0_f64.powf(gain_lo_db / 20.0);
+        let gain_hi = 1.0 - 10.0_f64.powf(gain_hi_db / 20.0);
+
+        let cut_lo = preset.cut_frequency();
+        let cut_hi = cut_lo * 2.0_f64.powf((gain_lo_db - 20.0 * gain_hi.log10()) / 12.0);
+
+        let x_lo = (-2.0 * PI * cut_lo / sample_rate).exp();
+        let x_hi = (-2.0 * PI * cut_hi / sample_rate).exp();
+
+        Self {
+            a0_lo: (gain_lo * (1.0 - x_lo)) as f32,
+            b1_lo: x_lo as f32,
+            a0_hi: (1.0 - gain_hi * (1.0 - x_hi)) as f32,
+            a1_hi: -x_hi as f32,
+            b1_hi: x_hi as f32,
+            gain: (1.0 / (1.0 - gain_hi + gain_lo)) as f32,
+        }
+    }
+}
+
+/// Creates a crossfeed for an audio source.
+///
+/// Sources that are not stereo pass through unchanged.
+///
+/// # Arguments
+///
+/// * `input` - Audio source to process
+/// * `preset` - Cut frequency and feed level
+/// * `enabled` - Runtime switch, shared with the player
+pub fn crossfeed<I>(input: I, preset: Preset, enabled: Arc<AtomicBool>) -> Crossfeed<I>
+where
+    I: Source,
+{
+    let sample_rate = input.sample_rate();
+    let coefficients = Coefficients::new(preset, sample_rate);
+
+    // Start fully switched, so that tracks do not fade in or out.
+    let mix = if enabled.load(Ordering::Relaxed) {
+        1.0
+    } else {
+        0.0
+    };
+
+    Crossfeed {
+        input,
+        coefficients,
+        fade: 1.0 - normalize::duration_to_coefficient(FADE_TIME, sample_rate),
+        enabled,
+        mix,
+        low: [0.0; 2],
+        high: [0.0; 2],
+        previous: [0.0; 2],
+        right: None,
+    }
+}
+
+/// Audio source with headphone crossfeed.
+#[derive(Clone, Debug)]
+pub struct Crossfeed<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Filter coefficients for the preset
+    coefficients: Coefficients,
+
+    /// Fade coefficient per frame when switched
+    fade: f32,
+
+    /// Runtime switch shared with the player
+    enabled: Arc<AtomicBool>,
+
+    /// Mix of the crossfed signal, from 0.0 (dry) to 1.0 (crossfed)
+    mix: f32,
+
+    /// Low-pass filter states
+    low: [f32; 2],
+
+    /// High-boost filter states
+    high: [f32; 2],
+
+    /// Previous input samples
+    previous: [f32; 2],
+
+    /// Right sample of the current frame, yet to be returned
+    right: Option<f32>,
+}
+
+impl<I> Crossfeed<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Clears the filter states.
+    fn reset(&mut self) {
+        self.low = [0.0; 2];
+        self.high = [0.0; 2];
+        self.previous = [0.0; 2];
+        self.right = None;
+    }
+
+    /// Processes a stereo frame.
+    #[inline]
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let c = &self.coefficients;
+        let [left, right] = frame;
+
+        self.low = [
+            c.a0_lo * left + c.b1_lo * self.low[0],
+            c.a0_lo * right + c.b1_lo * self.low[1],
+        ];
+        self.high = [
+            c.a0_hi * left + c.a1_hi * self.previous[0] + c.b1_hi * self.high[0],
+            c.a0_hi * right + c.a1_hi * self.previous[1] + c.b1_hi * self.high[1],
+        ];
+        self.previous = frame;
+
+        [
+            (self.high[0] + self.low[1]) * c.gain,
+            (self.high[1] + self.low[0]) * c.gain,
+        ]
+    }
+}
+
+impl<I> Iterator for Crossfeed<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        let left = self.input.next()?;
+        if self.input.channels() != 2 {
+            return Some(left);
+        }
+
+        // Fade on frame boundaries, to keep channels in step.
+        let target = if self.enabled.load(Ordering::Relaxed) {
+            1.0
+        } else {
+            0.0
+        };
+        self.mix += (target - self.mix) * self.fade;
+        if target < self.mix && self.mix < 1e-4 {
+            self.mix = 0.0;
+            self.reset();
+        }
+
+        let Some(right) = self.input.next() else {
+            return Some(left);
+        };
+
+        // Switched off and faded out: pass samples unchanged.
+        if self.mix <= 0.0 {
+            self.right = Some(right);
+            return Some(left);
+        }
+
+        let [crossfed_left, crossfed_right] = self.process([left, right]);
+        self.right = Some(right + (crossfed_right - right) * self.mix);
+        Some(left + (crossfed_left - left) * self.mix)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = usize::from(self.right.is_some());
+        let (lower, upper) = self.input.size_hint();
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I> Source for Crossfeed<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        let buffered = usize::from(self.right.is_some());
+        self.input
+            .current_span_len()
+            .map(|len| len.saturating_add(buffered))
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    /// Also resets the filter states when successful.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        Ok(())
+    }
+}
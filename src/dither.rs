@@ -82,6 +82,8 @@ use crate::{loudness::EqualLoudnessFilter, ringbuf::RingBuffer, volume::Volume};
 /// * `input` - The source audio stream
 /// * `volume` - Volume control with optional dithering parameters
 /// * `lufs_target` - Optional LUFS target for equal loudness compensation
+/// * `bit_perfect` - Whether the input reaches this stage unchanged from the decoder,
+///   so that it may pass through when the [`Volume`] is in bit-perfect bypass
 /// * `noise_shaping_profile` - Noise shaping aggressiveness level:
 ///   - 0: No shaping (plain TPDF dither) - safest, recommended for podcasts
 ///   - 1: Very mild shaping (~5 dB ultrasonic rise)
@@ -121,6 +123,7 @@ pub fn dithered_volume<I>(
     input: I,
     volume: Arc<Volume>,
     lufs_target: Option<f32>,
+    bit_perfect: bool,
     noise_shaping_profile: u8,
 ) -> Box<dyn Source<Item = I::Item> + Send>
 where
@@ -151,13 +154,14 @@ where
     }
 
     let equal_loudness =
-        lufs_target.map(|target| EqualLoudnessFilter::new(sample_rate, target, volume.volume()));
+        lufs_target.map(|target| EqualLoudnessFilter::new(sample_rate, target, volume.level()));
 
     match (sample_rate, noise_shaping_profile) {
         (_, 0) => Box::new(DitheredVolume::<I, 0> {
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &[],
@@ -166,6 +170,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_0,
@@ -174,6 +179,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_1,
@@ -182,6 +188,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_2,
@@ -190,6 +197,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_3,
@@ -198,6 +206,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_4,
@@ -206,6 +215,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_5,
@@ -214,6 +224,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_441_ATH_A_6,
@@ -222,6 +233,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_0,
@@ -230,6 +242,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_1,
@@ -238,6 +251,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_2,
@@ -246,6 +260,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_3,
@@ -254,6 +269,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_4,
@@ -262,6 +278,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_5,
@@ -270,6 +287,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_48_ATH_A_6,
@@ -278,6 +296,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_882_ATH_A_0,
@@ -286,6 +305,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_882_ATH_A_1,
@@ -294,6 +314,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_882_ATH_A_2,
@@ -302,6 +323,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_96_ATH_A_0,
@@ -310,6 +332,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_96_ATH_A_1,
@@ -318,6 +341,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_96_ATH_A_2,
@@ -326,6 +350,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_192_ATH_A_0,
@@ -334,6 +359,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_192_ATH_A_1,
@@ -342,6 +368,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_192_ATH_A_2,
@@ -350,6 +377,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_8_ATH_A_0,
@@ -358,6 +386,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_8_ATH_A_1,
@@ -366,6 +395,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_11_ATH_A_0,
@@ -374,6 +404,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_11_ATH_A_1,
@@ -382,6 +413,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_22_ATH_A_0,
@@ -390,6 +422,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &SHIBATA_22_ATH_A_1,
@@ -398,6 +431,7 @@ where
             input,
             volume,
             equal_loudness,
+            bit_perfect,
             rng: fastrand::Rng::new(),
             quantization_error_history: RingBuffer::new(),
             filter_coefficients: &[],
@@ -418,6 +452,10 @@ where
 ///    * Adds DC offset compensation
 /// 3. Applies volume scaling
 ///
+/// Samples pass through unchanged when the track was loaded bit-perfect, equal-loudness
+/// compensation is disabled and the [`Volume`] is in bit-perfect bypass. Samples that
+/// were processed before this stage are always dithered.
+///
 /// The type parameter N determines the noise shaping filter length,
 /// which varies by sample rate and chosen profile level. N=0 disables
 /// noise shaping for optimal performance when not needed.
@@ -440,6 +478,9 @@ pub struct DitheredVolume<I, const N: usize> {
 
     /// Optional equal loudness compensation filter
     equal_loudness: Option<EqualLoudnessFilter>,
+
+    /// Whether the input is unchanged from the decoder
+    bit_perfect: bool,
 }
 
 impl<I, const N: usize> DitheredVolume<I, N>
@@ -478,12 +519,17 @@ where
         /// provides additional linearization.
         const NOISE_SHAPING_DITHER_AMPLITUDE: f32 = 0.5;
 
+        // Bit-perfect: pass samples through when all processing would be a no-op
+        if self.bit_perfect && self.equal_loudness.is_none() && self.volume.is_bypassed() {
+            return self.input.next();
+        }
+
         self.input.next().map(|mut sample| {
             let volume = self.volume.volume();
 
             // Apply equal loudness compensation if enabled, without volume scaling
             if let Some(equal_loudness) = self.equal_loudness.as_mut() {
-                equal_loudness.update_volume(volume);
+                equal_loudness.update_volume(self.volume.level());
                 sample = equal_loudness.process(sample);
             }
 
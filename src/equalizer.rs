@@ -0,0 +1,633 @@
+//! Parametric equalizer using biquad filters.
+//!
+//! Lets users correct their speakers, headphones or room, for example with notches
+//! on room modes. Filters are configured per channel, and loaded from a TOML file or
+//! from Equalizer APO text, which is the format that AutoEQ publishes.
+//!
+//! Features:
+//! * Peaking, low/high shelf, low/high-pass and notch filters
+//! * Per-channel filters for left and right
+//! * Preamp with automatic headroom, so that boosts cannot clip
+//! * Switchable at runtime without reloading tracks, with a short fade
+//!
+//! # Headroom
+//!
+//! The combined frequency response of the filters is evaluated for each channel. When
+//! it rises above 0 dB, the preamp is lowered to at least minus the peak gain, so the
+//! equalized signal cannot clip before volume control and dithering. A preamp that
+//! already compensates the peak, like those of AutoEQ, is kept as it is.
+//!
+//! # TOML Format
+//!
+//! ```toml
+//! # Optional, in dB. Lowered when needed to prevent clipping.
+//! preamp = -2.0
+//!
+//! [[band]]
+//! type = "notch"      # peaking, low-shelf, high-shelf, low-pass, high-pass or notch
+//! frequency = 45.0    # in Hz
+//! q = 4.0             # optional, defaults to 0.707
+//! channel = "left"    # optional: all (default), left or right
+//!
+//! [[band]]
+//! type = "peaking"
+//! frequency = 120.0
+//! gain = -6.0         # in dB, for peaking and shelf filters
+//! q = 2.0
+//! ```
+//!
+//! # Equalizer APO Format
+//!
+//! ```text
+//! Preamp: -6.2 dB
+//! Filter 1: ON PK Fc 105 Hz Gain -3.4 dB Q 0.70
+//! Filter 2: ON LSC Fc 105 Hz Gain 5.5 dB Q 0.71
+//! Channel: R
+//! Filter 3: ON HP Fc 30 Hz
+//! ```
+//!
+//! Supported filter types are `PK`, `PEQ`, `LS`, `LSC`, `HS`, `HSC`, `LP`, `LPQ`,
+//! `HP`, `HPQ` and `NO`. A `Channel` line applies to the filters that follow it.
+
+use std::{
+    f64::consts::PI,
+    fs,
+    path::Path,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use biquad::{Biquad, Coefficients, DirectForm1, Q_BUTTERWORTH_F32, ToHertz, Type};
+use rodio::{ChannelCount, SampleRate, Source, source::SeekError};
+use serde::Deserialize;
+
+use crate::{
+    error::{Error, Result},
+    normalize, util,
+};
+
+/// Time to fade the equalizer in or out when switched.
+const FADE_TIME: Duration = Duration::from_millis(200);
+
+/// Type of an equalizer filter.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Filter {
+    /// Boosts or cuts a band around the frequency.
+    Peaking,
+    /// Boosts or cuts below the frequency.
+    LowShelf,
+    /// Boosts or cuts above the frequency.
+    HighShelf,
+    /// Removes content above the frequency.
+    LowPass,
+    /// Removes content below the frequency.
+    HighPass,
+    /// Removes a narrow band around the frequency.
+    Notch,
+}
+
+/// Channel that an equalizer band applies to.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// All channels.
+    #[default]
+    All,
+    /// The first channel.
+    Left,
+    /// The second channel.
+    Right,
+}
+
+impl Channel {
+    /// Returns whether this applies to the channel at the given index.
+    #[must_use]
+    fn contains(self, index: usize) -> bool {
+        match self {
+            Self::All => true,
+            Self::Left => index == 0,
+            Self::Right => index == 1,
+        }
+    }
+}
+
+/// A single equalizer filter.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Band {
+    /// Type of filter.
+    #[serde(rename = "type")]
+    pub filter: Filter,
+
+    /// Center, corner or cutoff frequency in Hz.
+    pub frequency: f32,
+
+    /// Gain in dB. Only used by peaking and shelf filters.
+    #[serde(default)]
+    pub gain: f32,
+
+    /// Quality factor. Higher values affect a narrower band.
+    #[serde(default = "Band::default_q")]
+    pub q: f32,
+
+    /// Channel that the filter applies to.
+    #[serde(default)]
+    pub channel: Channel,
+}
+
+impl Band {
+    /// Default quality factor, for a Butterworth response.
+    #[must_use]
+    fn default_q() -> f32 {
+        Q_BUTTERWORTH_F32
+    }
+
+    /// Returns the filter coefficients at the given sample rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the frequency is above the Nyquist frequency.
+    fn coefficients(&self, sample_rate: SampleRate) -> Result<Coefficients<f32>> {
+        let filter = match self.filter {
+            Filter::Peaking => Type::PeakingEQ(self.gain),
+            Filter::LowShelf => Type::LowShelf(self.gain),
+            Filter::HighShelf => Type::HighShelf(self.gain),
+            Filter::LowPass => Type::LowPass,
+            Filter::HighPass => Type::HighPass,
+            Filter::Notch => Type::Notch,
+        };
+
+        Coefficients::<f32>::from_params(filter, sample_rate.hz(), self.frequency.hz(), self.q)
+            .map_err(|e| {
+                Error::invalid_argument(format!(
+                    "{:?} filter at {} Hz not possible at {sample_rate} Hz: {e:?}",
+                    self.filter, self.frequency
+                ))
+            })
+    }
+
+    /// Checks that the parameters describe a valid filter.
+    fn validate(&self) -> Result<()> {
+        if !(self.frequency.is_finite() && self.frequency > 0.0) {
+            return Err(Error::invalid_argument(format!(
+                "invalid filter frequency {}",
+                self.frequency
+            )));
+        }
+        if !(self.q.is_finite() && self.q > 0.0) {
+            return Err(Error::invalid_argument(format!(
+                "invalid filter quality {}",
+                self.q
+            )));
+        }
+        if !self.gain.is_finite() {
+            return Err(Error::invalid_argument(format!(
+                "invalid filter gain {}",
+                self.gain
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Equalizer configuration.
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Preamp gain in dB.
+    ///
+    /// Lowered to minus the peak gain of the filters when it leaves less headroom than
+    /// that. `None` applies only the headroom that the filters need.
+    #[serde(default)]
+    pub preamp: Option<f32>,
+
+    /// Filters, applied in order.
+    #[serde(default, rename = "band")]
+    pub bands: Vec<Band>,
+}
+
+impl Settings {
+    /// Loads settings from a file.
+    ///
+    /// Files with a `.toml` extension are parsed as TOML, all others as Equalizer APO
+    /// text.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be read or parsed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+
+        let settings = if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+        {
+            Self::from_toml(&contents)?
+        } else {
+            contents.parse()?
+        };
+
+        info!(
+            "loaded {} equalizer filters from {}",
+            settings.bands.len(),
+            path.display()
+        );
+        Ok(settings)
+    }
+
+    /// Parses settings from TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the TOML is malformed or describes invalid filters.
+    pub fn from_toml(s: &str) -> Result<Self> {
+        let settings: Self = toml::from_str(s)
+            .map_err(|e| Error::invalid_argument(format!("invalid equalizer settings: {e}")))?;
+        for band in &settings.bands {
+            band.validate()?;
+        }
+        Ok(settings)
+    }
+
+    /// Returns the preamp gain in dB that prevents clipping at the given sample rate.
+    ///
+    /// This is the lower of the configured preamp and minus the peak gain of the
+    /// filters on the loudest channel.
+    #[must_use]
+    pub fn headroom(&self, sample_rate: SampleRate, channels: ChannelCount) -> f32 {
+        /// Number of frequencies to evaluate the response at.
+        const POINTS: usize = 512;
+
+        let nyquist = f64::from(sample_rate) / 2.0;
+        let high = nyquist.min(20_000.0);
+        let low = 20.0_f64.min(high);
+
+        // Log-spaced frequencies across the audible range, plus the band frequencies
+        // where peaking filters reach their extremes.
+        let mut frequencies: Vec<f64> = (0..POINTS)
+            .map(|i| {
+                #[expect(clippy::cast_precision_loss)]
+                let position = i as f64 / (POINTS - 1) as f64;
+                low * (high / low).powf(position)
+            })
+            .collect();
+        frequencies.extend(
+            self.bands
+                .iter()
+                .map(|band| f64::from(band.frequency))
+                .filter(|&frequency| frequency < nyquist),
+        );
+
+        let mut peak_db = 0.0_f64;
+        for index in 0..usize::from(channels.max(1)) {
+            let coefficients: Vec<_> = self
+                .bands
+                .iter()
+                .filter(|band| band.channel.contains(index))
+                .filter_map(|band| band.coefficients(sample_rate).ok())
+                .collect();
+
+            for &frequency in &frequencies {
+                let omega = 2.0 * PI * frequency / f64::from(sample_rate);
+                let gain_db: f64 = coefficients
+                    .iter()
+                    .map(|coefficients| 20.0 * magnitude(coefficients, omega).log10())
+                    .sum();
+                peak_db = peak_db.max(gain_db);
+            }
+        }
+
+        #[expect(clippy::cast_possible_truncation)]
+        let headroom = -peak_db as f32;
+        self.preamp.unwrap_or(0.0).min(headroom)
+    }
+}
+
+/// Parses Equalizer APO text, as published by AutoEQ.
+impl FromStr for Settings {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut settings = Self::default();
+        let mut channel = Channel::All;
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid =
+                |reason: &str| Error::invalid_argument(format!("line {}: {reason}", number + 1));
+
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(invalid("expected a colon"));
+            };
+            let key = key.trim().to_lowercase();
+            let mut tokens = value.split_whitespace();
+
+            if key == "preamp" {
+                let preamp = tokens
+                    .next()
+                    .and_then(|gain| gain.parse::<f32>().ok())
+                    .filter(|gain| gain.is_finite())
+                    .ok_or_else(|| invalid("invalid preamp"))?;
+                settings.preamp = Some(settings.preamp.unwrap_or(0.0) + preamp);
+            } else if key == "channel" {
+                let channels: Vec<_> = tokens.map(str::to_lowercase).collect();
+                channel = match channels.as_slice() {
+                    [c] if c == "l" || c == "1" => Channel::Left,
+                    [c] if c == "r" || c == "2" => Channel::Right,
+                    [c] if c == "all" => Channel::All,
+                    [l, r] if (l == "l" && r == "r") || (l == "1" && r == "2") => Channel::All,
+                    _ => return Err(invalid("unsupported channel selection")),
+                };
+            } else if key.starts_with("filter") {
+                match tokens.next().map(str::to_uppercase).as_deref() {
+                    Some("ON") => {}
+                    Some("OFF") => continue,
+                    _ => return Err(invalid("expected ON or OFF")),
+                }
+
+                let filter = match tokens.next().map(str::to_uppercase).as_deref() {
+                    Some("PK" | "PEQ") => Filter::Peaking,
+                    Some("LS" | "LSC") => Filter::LowShelf,
+                    Some("HS" | "HSC") => Filter::HighShelf,
+                    Some("LP" | "LPQ") => Filter::LowPass,
+                    Some("HP" | "HPQ") => Filter::HighPass,
+                    Some("NO") => Filter::Notch,
+                    _ => return Err(invalid("unsupported filter type")),
+                };
+
+                let mut band = Band {
+                    filter,
+                    frequency: 0.0,
+                    gain: 0.0,
+                    q: Band::default_q(),
+                    channel,
+                };
+
+                while let Some(token) = tokens.next() {
+                    let mut value = || {
+                        tokens
+                            .next()
+                            .and_then(|value| value.parse::<f32>().ok())
+                            .ok_or_else(|| invalid(&format!("invalid value for {token}")))
+                    };
+                    match token.to_lowercase().as_str() {
+                        "fc" => band.frequency = value()?,
+                        "gain" => band.gain = value()?,
+                        "q" => band.q = value()?,
+                        "hz" | "db" => {}
+                        // The frequency may be given without `Fc`.
+                        other => {
+                            band.frequency = other
+                                .parse()
+                                .map_err(|_| invalid(&format!("unexpected {token}")))?;
+                        }
+                    }
+                }
+
+                band.validate().map_err(|e| invalid(&e.to_string()))?;
+                settings.bands.push(band);
+            } else {
+                debug!("equalizer: ignoring line {}: {line}", number + 1);
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Returns the magnitude response of a biquad filter at an angular frequency.
+fn magnitude(coefficients: &Coefficients<f32>, omega: f64) -> f64 {
+    let (sin1, cos1) = omega.sin_cos();
+    let (sin2, cos2) = (2.0 * omega).sin_cos();
+
+    let b0 = f64::from(coefficients.b0);
+    let b1 = f64::from(coefficients.b1);
+    let b2 = f64::from(coefficients.b2);
+    let a1 = f64::from(coefficients.a1);
+    let a2 = f64::from(coefficients.a2);
+
+    let numerator = (b0 + b1 * cos1 + b2 * cos2).hypot(b1 * sin1 + b2 * sin2);
+    let denominator = (1.0 + a1 * cos1 + a2 * cos2).hypot(a1 * sin1 + a2 * sin2);
+
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        1.0
+    }
+}
+
+/// Creates an equalizer for an audio source.
+///
+/// Filters that are not possible at the sample rate of the source, like those above
+/// its Nyquist frequency, are skipped with a warning.
+///
+/// # Arguments
+///
+/// * `input` - Audio source to equalize
+/// * `settings` - Filters and preamp
+/// * `enabled` - Runtime switch, shared with the player
+pub fn equalize<I>(input: I, settings: &Settings, enabled: Arc<AtomicBool>) -> Equalize<I>
+where
+    I: Source,
+{
+    let sample_rate = input.sample_rate();
+    let channels = input.channels();
+
+    let filters = (0..usize::from(channels.max(1)))
+        .map(|index| {
+            settings
+                .bands
+                .iter()
+                .filter(|band| band.channel.contains(index))
+                .filter_map(|band| match band.coefficients(sample_rate) {
+                    Ok(coefficients) => Some(DirectForm1::<f32>::new(coefficients)),
+                    Err(e) => {
+                        warn!("skipping equalizer filter: {e}");
+                        None
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let preamp_db = settings.headroom(sample_rate, channels);
+    debug!("equalizer preamp: {preamp_db:.1} dB");
+
+    // Start fully switched, so that tracks do not fade in or out.
+    let mix = if enabled.load(Ordering::Relaxed) {
+        1.0
+    } else {
+        0.0
+    };
+
+    Equalize {
+        input,
+        filters,
+        preamp: util::db_to_ratio(preamp_db),
+        fade: 1.0 - normalize::duration_to_coefficient(FADE_TIME, sample_rate),
+        enabled,
+        mix,
+        channel: 0,
+    }
+}
+
+/// Audio source with a parametric equalizer.
+///
+/// Applies the preamp and then the filters of each channel in order. When switched,
+/// fades between the dry and equalized signal. When disabled, samples pass through
+/// unchanged.
+#[derive(Debug, Clone)]
+pub struct Equalize<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Filters for each channel
+    filters: Vec<Vec<DirectForm1<f32>>>,
+
+    /// Preamp gain as a linear ratio
+    preamp: f32,
+
+    /// Fade coefficient per frame when switched
+    fade: f32,
+
+    /// Runtime switch shared with the player
+    enabled: Arc<AtomicBool>,
+
+    /// Mix of the equalized signal, from 0.0 (dry) to 1.0 (equalized)
+    mix: f32,
+
+    /// Channel of the next sample
+    channel: usize,
+}
+
+impl<I> Equalize<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Clears the filter states, so that earlier audio does not ring into new audio.
+    fn reset(&mut self) {
+        for filter in self.filters.iter_mut().flatten() {
+            filter.reset_state();
+        }
+    }
+}
+
+impl<I> Iterator for Equalize<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let channel = self.channel;
+        self.channel = (channel + 1) % self.filters.len().max(1);
+
+        if channel == 0 {
+            // Fade on frame boundaries, to keep channels in step.
+            let target = if self.enabled.load(Ordering::Relaxed) {
+                1.0
+            } else {
+                0.0
+            };
+            self.mix += (target - self.mix) * self.fade;
+            if target < self.mix && self.mix < 1e-4 {
+                self.mix = 0.0;
+                self.reset();
+            }
+        }
+
+        // Switched off and faded out: pass samples unchanged.
+        if self.mix <= 0.0 {
+            return Some(sample);
+        }
+
+        let equalized = self.process(channel, sample);
+        Some(sample + (equalized - sample) * self.mix)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Equalize<I>
+where
+    I: Source,
+{
+    /// Runs a sample through the preamp and the filters of its channel.
+    #[inline]
+    fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        let mut output = sample * self.preamp;
+        if let Some(filters) = self.filters.get_mut(channel) {
+            for filter in filters {
+                output = filter.run(output);
+            }
+        }
+        output
+    }
+}
+
+impl<I> Source for Equalize<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    /// Also resets the filter states when successful.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        self.channel = 0;
+        Ok(())
+    }
+}
@@ -166,6 +166,29 @@ pub enum ErrorKind {
     DataLoss = 15,
 }
 
+/// Maps error kinds to their HTTP status codes.
+impl From<ErrorKind> for http::StatusCode {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::Cancelled => Self::from_u16(499).unwrap_or(Self::INTERNAL_SERVER_ERROR),
+            ErrorKind::InvalidArgument | ErrorKind::FailedPrecondition | ErrorKind::OutOfRange => {
+                Self::BAD_REQUEST
+            }
+            ErrorKind::DeadlineExceeded => Self::GATEWAY_TIMEOUT,
+            ErrorKind::NotFound => Self::NOT_FOUND,
+            ErrorKind::AlreadyExists | ErrorKind::Aborted => Self::CONFLICT,
+            ErrorKind::PermissionDenied => Self::FORBIDDEN,
+            ErrorKind::Unauthenticated => Self::UNAUTHORIZED,
+            ErrorKind::ResourceExhausted => Self::TOO_MANY_REQUESTS,
+            ErrorKind::Unimplemented => Self::NOT_IMPLEMENTED,
+            ErrorKind::Unavailable => Self::SERVICE_UNAVAILABLE,
+            ErrorKind::Unknown | ErrorKind::Internal | ErrorKind::DataLoss => {
+                Self::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
 impl Error {
     /// Creates a new error with specified kind and details.
     ///
@@ -908,3 +931,14 @@ impl From<std::num::TryFromIntError> for Error {
         Self::invalid_argument(e)
     }
 }
+
+/// D-Bus error conversion.
+///
+/// Maps errors connecting to or serving on a message bus to `Unavailable`.
+/// Occurs when the bus is not running, or the bus name is already taken.
+#[cfg(target_os = "linux")]
+impl From<zbus::Error> for Error {
+    fn from(e: zbus::Error) -> Self {
+        Self::unavailable(e)
+    }
+}
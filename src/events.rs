@@ -5,6 +5,7 @@
 //! * Monitor playback state changes
 //! * Track remote control connections
 //! * React to track changes
+//! * Notice errors and expiring credentials
 //!
 //! # Example
 //!
@@ -16,35 +17,58 @@
 //!         Event::Play => println!("Playback started"),
 //!         Event::TrackChanged => println!("New track playing"),
 //!         Event::Connected => println!("Remote control connected"),
+//!         Event::VolumeChanged { volume } => println!("Volume set to {volume}%"),
 //!         // ... handle other events ...
+//!         _ => {}
 //!     }
 //! }
 //! ```
 
+use std::{fmt, time::Duration};
+
+use serde::{Serialize, Serializer};
+use serde_with::{DisplayFromStr, DurationSecondsWithFrac, serde_as};
+
+use crate::{protocol::connect::RepeatMode, track::TrackId};
+
 /// Events that can be emitted by the Deezer Connect player or remote.
 ///
 /// These events represent significant state changes in playback
 /// or remote control status.
 ///
+/// Serializes to JSON with the same event names as hook scripts get,
+/// like `{"event": "track_changed"}`.
+///
 /// # Events
 ///
-/// Events fall into two categories:
+/// Events fall into three categories:
 ///
 /// Playback Events:
 /// * [`Play`](Self::Play) - Playback starts
 /// * [`Pause`](Self::Pause) - Playback pauses
 /// * [`TrackChanged`](Self::TrackChanged) - Current track changes
+/// * [`Seeked`](Self::Seeked) - Position within the track changes
+/// * [`VolumeChanged`](Self::VolumeChanged) - Volume changes
+/// * [`RepeatModeChanged`](Self::RepeatModeChanged) - Repeat mode changes
+/// * [`ShuffleChanged`](Self::ShuffleChanged) - Shuffle is enabled or disabled
+/// * [`QueueChanged`](Self::QueueChanged) - Queue is set or extended
+/// * [`Stopped`](Self::Stopped) - Audio output is closed
 ///
 /// Connection Events:
 /// * [`Connected`](Self::Connected) - Remote connects
 /// * [`Disconnected`](Self::Disconnected) - Remote disconnects
+/// * [`ArlExpiring`](Self::ArlExpiring) - ARL is about to expire
+///
+/// Error Events:
+/// * [`TrackUnavailable`](Self::TrackUnavailable) - Track cannot be played
+/// * [`PlaybackError`](Self::PlaybackError) - Playback fails
 ///
 /// # Example
 ///
 /// ```rust
 /// use pleezer::events::Event;
 ///
-/// // Events can be copied and compared
+/// // Events can be cloned and compared
 /// let event = Event::Play;
 /// assert_eq!(event, Event::Play);
 /// assert_ne!(event, Event::Pause);
@@ -56,18 +80,22 @@
 ///     _ => "Other event",
 /// };
 /// ```
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
 pub enum Event {
     /// Playback has started.
     ///
     /// Emitted when a track begins playing, either from a paused
     /// state or when starting a new track.
+    #[serde(rename = "playing")]
     Play,
 
     /// Playback has paused.
     ///
     /// Emitted when playback is suspended but can be resumed
     /// from the current position.
+    #[serde(rename = "paused")]
     Pause,
 
     /// Current track has changed.
@@ -87,4 +115,123 @@ pub enum Event {
     /// Emitted when a connected Deezer client ends its remote
     /// control session with this player.
     Disconnected,
+
+    /// Volume has changed.
+    ///
+    /// Emitted when a Deezer client or local interface sets a
+    /// different volume.
+    VolumeChanged {
+        /// Volume in percent
+        volume: f32,
+    },
+
+    /// Playback position has changed.
+    ///
+    /// Emitted when seeking within the current track.
+    Seeked {
+        /// Position within the track in seconds
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        position: Duration,
+    },
+
+    /// Repeat mode has changed.
+    RepeatModeChanged {
+        /// Repeat mode: none, all or one
+        #[serde(serialize_with = "lowercase")]
+        repeat_mode: RepeatMode,
+    },
+
+    /// Shuffle has been enabled or disabled.
+    ShuffleChanged {
+        /// Whether the queue is shuffled
+        shuffle: bool,
+    },
+
+    /// Queue has changed.
+    ///
+    /// Emitted when a Deezer client publishes a new queue, or when
+    /// Flow is extended with more recommendations.
+    QueueChanged {
+        /// Identifier of the queue
+        queue_id: String,
+
+        /// Number of tracks in the queue
+        tracks: usize,
+    },
+
+    /// Track cannot be played and will be skipped.
+    ///
+    /// Emitted once per track, when it fails to load or is not
+    /// available from Deezer.
+    TrackUnavailable {
+        /// Deezer ID of the track
+        #[serde_as(as = "DisplayFromStr")]
+        track_id: TrackId,
+
+        /// Why the track cannot be played
+        reason: String,
+    },
+
+    /// Playback has failed.
+    ///
+    /// Emitted when the audio output fails, or the player stops with
+    /// an error.
+    PlaybackError {
+        /// Description of the error
+        error: String,
+    },
+
+    /// Audio output has been closed.
+    ///
+    /// Emitted when playback stops for good, like when the remote
+    /// disconnects or pleezer shuts down.
+    Stopped,
+
+    /// ARL is about to expire.
+    ///
+    /// Emitted after logging in and renewing the session, while the
+    /// ARL expires within a week. Log in again to get a new ARL.
+    ArlExpiring {
+        /// Time until the ARL expires in seconds
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        expires_in: Duration,
+    },
+}
+
+impl Event {
+    /// Returns the name of the event, as used for hook scripts and in JSON.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Play => "playing",
+            Self::Pause => "paused",
+            Self::TrackChanged => "track_changed",
+            Self::Connected => "connected",
+            Self::Disconnected => "disconnected",
+            Self::VolumeChanged { .. } => "volume_changed",
+            Self::Seeked { .. } => "seeked",
+            Self::RepeatModeChanged { .. } => "repeat_mode_changed",
+            Self::ShuffleChanged { .. } => "shuffle_changed",
+            Self::QueueChanged { .. } => "queue_changed",
+            Self::TrackUnavailable { .. } => "track_unavailable",
+            Self::PlaybackError { .. } => "playback_error",
+            Self::Stopped => "stopped",
+            Self::ArlExpiring { .. } => "arl_expiring",
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Serializes a value by its lowercase display name, like `"all"`.
+fn lowercase<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: fmt::Display,
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string().to_lowercase())
 }
@@ -56,7 +56,10 @@
 //! let user_data = gateway.refresh().await?;
 //! ```
 
-use std::time::SystemTime;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
 
 use cookie_store::RawCookie;
 use futures_util::TryFutureExt;
@@ -81,8 +84,10 @@ use crate::{
         },
         gateway::{
             self, MediaUrl, Queue, Response, UserData,
+            episode_bookmark::{self, EpisodeBookmark},
             list_data::{
                 ListData,
+                chapters::{self, ChapterData},
                 episodes::{self, EpisodeData},
                 livestream::{self, LivestreamData},
                 songs::{self, SongData},
@@ -91,6 +96,7 @@ use crate::{
         },
     },
     tokens::UserToken,
+    track::{TrackId, TrackType},
 };
 
 /// Gateway client for Deezer API access.
@@ -112,9 +118,24 @@ pub struct Gateway {
 
     /// Client identifier for API requests.
     client_id: usize,
+
+    /// Metadata of songs, episodes and chapters resolved before, keyed by track type
+    /// and ID.
+    ///
+    /// Entries are dropped when their track tokens expire.
+    list_data: HashMap<(TrackType, TrackId), ListData>,
 }
 
 impl Gateway {
+    /// Maximum number of tracks to resolve in a single request.
+    ///
+    /// Keeps requests for large playlists and "Shuffle my music" queues within size
+    /// limits and network timeouts.
+    pub const LIST_DATA_PAGE_SIZE: usize = 100;
+
+    /// Minimum time that cached track tokens must remain valid to be reused.
+    const LIST_DATA_MIN_VALIDITY: Duration = Duration::from_secs(10 * 60);
+
     /// Cookie origin URL for Deezer services.
     const COOKIE_ORIGIN: &'static str = "https://deezer.com";
 
@@ -268,6 +289,7 @@ impl Gateway {
             client_id: config.client_id,
             http_client,
             user_data: None,
+            list_data: HashMap::new(),
         })
     }
 
@@ -513,12 +535,11 @@ impl Gateway {
 
     /// Converts a protocol buffer track list into a queue.
     ///
-    /// Fetches detailed track information for each track in the list.
-    /// Different track types support different formats:
-    /// * Songs: MP3 (CBR) or FLAC
-    /// * Episodes: MP3, AAC (ADTS), MP4, or WAV
-    /// * Livestreams: AAC (ADTS) or MP3
-    /// * Chapters: Not currently supported
+    /// Fetches detailed track information for each track in the list. Lists may mix
+    /// songs, episodes, livestreams and chapters: tracks are requested by type, in pages of
+    /// [`LIST_DATA_PAGE_SIZE`](Self::LIST_DATA_PAGE_SIZE) tracks, and returned in the
+    /// order of the list. For large lists, consider resolving pages on demand with
+    /// [`list_data`](Self::list_data) instead.
     ///
     /// # Arguments
     ///
@@ -528,52 +549,173 @@ impl Gateway {
     ///
     /// Returns an error if:
     /// * Track IDs are invalid
-    /// * Track type is unsupported (e.g., audiobooks)
+    /// * Track type is unknown, naming the first such track
     /// * Network request fails
     /// * Response parsing fails
     pub async fn list_to_queue(&mut self, list: &queue::List) -> Result<Queue> {
-        let ids = list
+        let tracks = list
             .tracks
             .iter()
-            .map(|track| track.id.parse().map_err(Error::from))
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-
-        if let Some(first) = list.tracks.first() {
-            let response: Response<ListData> = match first.typ.enum_value_or_default() {
-                queue::TrackType::TRACK_TYPE_SONG => {
-                    let songs = songs::Request { song_ids: ids };
-                    let request = serde_json::to_string(&songs)?;
-                    self.request::<SongData>(request, None)
-                        .map_ok(Into::into)
-                        .await?
+            .map(|track| Ok((TrackType::try_from(track)?, track.id.parse()?)))
+            .collect::<Result<Vec<(TrackType, TrackId)>>>()?;
+
+        // Request each type once, in pages.
+        let mut items = HashMap::new();
+        for typ in [
+            TrackType::Song,
+            TrackType::Episode,
+            TrackType::Livestream,
+            TrackType::Chapter,
+        ] {
+            let ids: Vec<_> = tracks
+                .iter()
+                .filter(|(track_typ, _)| *track_typ == typ)
+                .map(|(_, id)| *id)
+                .collect();
+            if !ids.is_empty() {
+                for item in self.list_data(typ, &ids).await? {
+                    items.insert((item.track_type(), item.id()), item);
                 }
-                queue::TrackType::TRACK_TYPE_EPISODE => {
-                    let episodes = episodes::Request { episode_ids: ids };
+            }
+        }
+
+        // Stitch the tracks back together in the order of the list.
+        Ok(tracks
+            .iter()
+            .filter_map(|key| items.get(key).cloned())
+            .collect())
+    }
+
+    /// Fetches detailed track information for tracks of a single type.
+    ///
+    /// Different track types support different formats:
+    /// * Songs: MP3 (CBR) or FLAC
+    /// * Episodes: MP3, AAC (ADTS), MP4, or WAV
+    /// * Livestreams: AAC (ADTS) or MP3
+    /// * Chapters: MP3 (CBR)
+    ///
+    /// Songs, episodes and chapters are served from the local cache while their track tokens
+    /// remain valid. Others are requested in pages of
+    /// [`LIST_DATA_PAGE_SIZE`](Self::LIST_DATA_PAGE_SIZE) tracks. Livestreams are
+    /// requested one at a time, and never cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `typ` - Type of all tracks
+    /// * `ids` - Identifiers of the tracks
+    ///
+    /// # Returns
+    ///
+    /// Track information in the order of `ids`. Tracks that the gateway did not return
+    /// are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * Network request fails
+    /// * Response parsing fails
+    pub async fn list_data(&mut self, typ: TrackType, ids: &[TrackId]) -> Result<Queue> {
+        if typ == TrackType::Livestream {
+            let mut queue = Queue::with_capacity(ids.len());
+            for id in ids {
+                let radio = livestream::Request {
+                    livestream_id: *id,
+                    supported_codecs: vec![Codec::ADTS, Codec::MP3],
+                };
+                let request = serde_json::to_string(&radio)?;
+                let response: Response<ListData> = self
+                    .request::<LivestreamData>(request, None)
+                    .map_ok(Into::into)
+                    .await?;
+                queue.extend(response.all().iter().cloned());
+            }
+            return Ok(queue);
+        }
+
+        // Drop entries with expiring track tokens, which also bounds the cache size.
+        let valid_until = SystemTime::now() + Self::LIST_DATA_MIN_VALIDITY;
+        self.list_data
+            .retain(|_, item| item.expiry().is_none_or(|expiry| expiry > valid_until));
+
+        let mut missing: Vec<TrackId> = ids
+            .iter()
+            .copied()
+            .filter(|id| !self.list_data.contains_key(&(typ, *id)))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+
+        for page in missing.chunks(Self::LIST_DATA_PAGE_SIZE) {
+            trace!("resolving {} {typ}s", page.len());
+            let response: Response<ListData> = match typ {
+                TrackType::Episode => {
+                    let episodes = episodes::Request {
+                        episode_ids: page.to_vec(),
+                    };
                     let request = serde_json::to_string(&episodes)?;
                     self.request::<EpisodeData>(request, None)
                         .map_ok(Into::into)
                         .await?
                 }
-                queue::TrackType::TRACK_TYPE_LIVE => {
-                    let radio = livestream::Request {
-                        livestream_id: first.id.parse()?,
-                        supported_codecs: vec![Codec::ADTS, Codec::MP3],
+                TrackType::Chapter => {
+                    let chapters = chapters::Request {
+                        chapter_ids: page.to_vec(),
                     };
-                    let request = serde_json::to_string(&radio)?;
-                    self.request::<LivestreamData>(request, None)
+                    let request = serde_json::to_string(&chapters)?;
+                    self.request::<ChapterData>(request, None)
                         .map_ok(Into::into)
                         .await?
                 }
-                queue::TrackType::TRACK_TYPE_CHAPTER => {
-                    return Err(Error::unimplemented(
-                        "audio books not implemented - report what you were trying to play to the developers",
-                    ));
+                TrackType::Song | TrackType::Livestream => {
+                    let songs = songs::Request {
+                        song_ids: page.to_vec(),
+                    };
+                    let request = serde_json::to_string(&songs)?;
+                    self.request::<SongData>(request, None)
+                        .map_ok(Into::into)
+                        .await?
                 }
             };
 
-            Ok(response.all().clone())
-        } else {
-            Ok(Queue::default())
+            for item in response.all() {
+                self.list_data.insert((typ, item.id()), item.clone());
+            }
+        }
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| self.list_data.get(&(typ, *id)).cloned())
+            .collect())
+    }
+
+    /// Stores the playback position of a podcast episode with Deezer.
+    ///
+    /// # Arguments
+    ///
+    /// * `episode_id` - ID of the episode
+    /// * `offset` - Playback position, or the duration of the episode to mark it as played
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * Network request fails
+    /// * Response parsing fails
+    /// * Gateway rejects the bookmark
+    pub async fn set_bookmark(&mut self, episode_id: TrackId, offset: Duration) -> Result<()> {
+        let request = episode_bookmark::Request {
+            episode_id,
+            offset: offset.as_secs(),
+        };
+        let body = serde_json::to_string(&request)?;
+        match self.request::<EpisodeBookmark>(body, None).await? {
+            Response::Paginated { error, .. } | Response::Unpaginated { error, .. }
+                if !error.is_empty() =>
+            {
+                Err(Error::unavailable(format!(
+                    "failed to bookmark episode {episode_id}: {error:?}"
+                )))
+            }
+            _ => Ok(()),
         }
     }
 
@@ -11,6 +11,7 @@
 //! * **Format Support**: Handles MP3 and FLAC formats based on subscription level
 //! * **Audio Processing**:
 //!   - Volume normalization with configurable target gain
+//!   - High-quality sample rate conversion
 //!   - High-quality dithering with psychoacoustic noise shaping
 //!   - Configurable for different DAC capabilities
 //!
@@ -22,18 +23,37 @@
 //!   - [`http`]: Manages HTTP connections and cookies
 //!   - [`gateway`]: Handles API authentication and requests
 //!   - [`remote`]: Implements Deezer Connect protocol
+//!   - [`control`]: Local control of playback, next to Deezer Connect
+//!   - [`api`]: HTTP/JSON control and status API
+//!   - [`mpris`]: MPRIS D-Bus interface for desktop integration (Linux only)
+//!   - [`mqtt`]: MQTT bridge with Home Assistant discovery
+//!   - [`socket`]: Line-delimited JSON control on a unix socket (Unix only)
 //!
 //! * **Audio Processing**
 //!   - [`audio_file`]: Unified interface for audio stream handling
 //!   - [`decrypt`]: Handles encrypted content
 //!   - [`decoder`]: Audio format decoding
+//!   - [`resample`]: Sample rate conversion to the output rate
 //!   - [`normalize`]: Audio leveling and dynamic range control
+//!   - [`agc`]: Automatic gain control for livestreams
+//!   - [`limiter`]: True-peak look-ahead limiting
+//!   - [`compressor`]: Dynamic range compression for night mode
+//!   - [`r128`]: Loudness measurement of tracks
+//!   - [`scan`]: Background loudness scans of tracks without gain information
+//!   - [`equalizer`]: Parametric equalizer from TOML or Equalizer APO files
+//!   - [`convolve`]: FIR convolution with impulse responses for room correction
+//!   - [`crossfeed`]: Headphone crossfeed for stereo content
 //!   - [`loudness`]: Equal-loudness compensation (ISO 226:2013)
 //!   - [`dither`]: High-quality dithering and noise shaping
-//!   - [`volume`]: Volume control with dithering integration
+//!   - [`volume`]: Volume control with curves and dithering integration
+//!   - [`mixer`]: External volume control for a fixed output
+//!   - [`crossfade`]: Crossfading between consecutive tracks
 //!   - [`player`]: Controls audio playback and queues
+//!   - [`output`]: Pluggable audio output through the `AudioSink` trait
+//!   - [`pipe`]: Raw PCM output to named pipes and files
 //!   - [`ringbuf`]: Ring buffer for audio processing
 //!   - [`track`]: Manages track metadata and downloads
+//!   - [`bookmarks`]: Playback positions of podcast episodes
 //!
 //! * **Authentication**
 //!   - [`arl`]: ARL token management
@@ -105,24 +125,45 @@
 #[macro_use]
 extern crate log;
 
+pub mod agc;
+pub mod api;
 pub mod arl;
 pub mod audio_file;
+pub mod bookmarks;
+pub mod compressor;
 pub mod config;
+pub mod control;
+pub mod convolve;
+pub mod crossfade;
+pub mod crossfeed;
 pub mod decoder;
 pub mod decrypt;
 pub mod dither;
+pub mod equalizer;
 pub mod error;
 pub mod events;
 pub mod gateway;
 pub mod http;
+pub mod limiter;
 pub mod loudness;
+pub mod mixer;
+#[cfg(target_os = "linux")]
+pub mod mpris;
+pub mod mqtt;
 pub mod normalize;
+pub mod output;
+pub mod pipe;
 pub mod player;
 pub mod protocol;
 pub mod proxy;
+pub mod r128;
 pub mod remote;
+pub mod resample;
 pub mod ringbuf;
+pub mod scan;
 pub mod signal;
+#[cfg(unix)]
+pub mod socket;
 pub mod tokens;
 pub mod track;
 pub mod util;
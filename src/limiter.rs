@@ -0,0 +1,556 @@
+//! True-peak look-ahead limiting.
+//!
+//! Reacting to sample peaks misses the peaks between samples, that appear when the
+//! signal is reconstructed by the DAC. Reacting after the fact lets the start of
+//! transients through. This limiter does neither:
+//!
+//! * Peaks are detected on a 4x oversampled signal, approximating the true peak of
+//!   ITU-R BS.1770
+//! * The signal is delayed by a short look-ahead, so gain reduction is complete when a
+//!   peak is played
+//! * Gain reduction is coupled across channels, to preserve imaging
+//! * Statistics of the gain reduction are shared with the player
+//!
+//! # Implementation
+//!
+//! For each frame the gain required to keep its true peak below the threshold is
+//! computed. The minimum over the look-ahead window is smoothed by a moving average of
+//! the same length, so that the gain ramps down smoothly and reaches the required gain
+//! exactly when the peak is played. Releasing is exponential.
+//!
+//! The output has the same length as the input: the look-ahead is read in advance, and
+//! drained when the input ends. Output frames line up with input frames, so unlike
+//! convolution, limiting does not delay the playback position. Below the threshold,
+//! samples pass unchanged.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::{sync::Arc, time::Duration};
+//! use pleezer::limiter::{self, Stats};
+//!
+//! let stats = Arc::new(Stats::default());
+//! let limited = limiter::limit(source, -1.0, Duration::from_millis(100), Some(stats.clone()));
+//! // ... after playback:
+//! println!("{stats}");
+//! ```
+
+use std::{
+    collections::VecDeque,
+    f32::consts::PI,
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use rodio::{ChannelCount, SampleRate, Source, source::SeekError};
+
+use crate::util::{self, ToF32, UNITY_GAIN};
+
+/// Time that the limiter looks ahead.
+pub const LOOKAHEAD: Duration = Duration::from_millis(5);
+
+/// Oversampling factor of the true-peak detector.
+const OVERSAMPLING: usize = 4;
+
+/// Taps per phase of the interpolation filter.
+const TAPS: usize = 16;
+
+/// Frames after which the statistics are published.
+const STATS_INTERVAL: u64 = 4096;
+
+/// Gain reduction statistics of a limiter.
+///
+/// Updated while the limiter runs, so that it may be read from another thread.
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Frames processed
+    frames: AtomicU64,
+
+    /// Frames with gain reduction
+    limited: AtomicU64,
+
+    /// Largest gain reduction in dB, as bits of an `f32`
+    max_reduction: AtomicU32,
+}
+
+impl Stats {
+    /// Returns the number of frames processed.
+    #[must_use]
+    pub fn frames(&self) -> u64 {
+        self.frames.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of frames with gain reduction.
+    #[must_use]
+    pub fn limited(&self) -> u64 {
+        self.limited.load(Ordering::Relaxed)
+    }
+
+    /// Returns the fraction of frames with gain reduction, from 0.0 to 1.0.
+    #[must_use]
+    pub fn limited_ratio(&self) -> f32 {
+        match self.frames() {
+            0 => 0.0,
+            frames => self.limited().to_f32_lossy() / frames.to_f32_lossy(),
+        }
+    }
+
+    /// Returns the largest gain reduction in dB, as a positive number.
+    #[must_use]
+    pub fn max_reduction_db(&self) -> f32 {
+        f32::from_bits(self.max_reduction.load(Ordering::Relaxed))
+    }
+
+    /// Returns whether any gain reduction was applied.
+    #[must_use]
+    pub fn is_limited(&self) -> bool {
+        self.limited() > 0
+    }
+
+    /// Adds the statistics of a block of frames.
+    fn record(&self, frames: u64, limited: u64, max_reduction_db: f32) {
+        self.frames.fetch_add(frames, Ordering::Relaxed);
+        self.limited.fetch_add(limited, Ordering::Relaxed);
+        if max_reduction_db > self.max_reduction_db() {
+            self.max_reduction
+                .store(max_reduction_db.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at most {:.1} dB, {:.1}% of the time",
+            self.max_reduction_db(),
+            self.limited_ratio() * 100.0
+        )
+    }
+}
+
+/// Creates a true-peak look-ahead limiter for an audio source.
+///
+/// # Arguments
+///
+/// * `input` - Audio source to process
+/// * `threshold` - Highest true peak in dBTP
+/// * `release` - Time constant for recovering from gain reduction
+/// * `stats` - Where to report gain reduction statistics, if anywhere
+pub fn limit<I>(
+    input: I,
+    threshold: f32,
+    release: Duration,
+    stats: Option<Arc<Stats>>,
+) -> Limiter<I>
+where
+    I: Source,
+{
+    let channels = usize::from(input.channels().max(1));
+    let sample_rate = input.sample_rate().max(1);
+
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let window = (LOOKAHEAD.as_secs_f32() * sample_rate.to_f32_lossy()).ceil() as usize + 1;
+
+    // The interpolated peaks lag behind by half the filter length.
+    let delay = window - 1 + TAPS / 2;
+
+    Limiter {
+        input,
+        threshold: util::db_to_ratio(threshold),
+        release: 1.0 - f32::exp(-1.0 / (release.as_secs_f32() * sample_rate.to_f32_lossy())),
+        channels,
+        phases: interpolation_filter(),
+        history: vec![VecDeque::from(vec![0.0; TAPS]); channels],
+        delay: VecDeque::with_capacity((delay + 1) * channels),
+        delay_frames: delay,
+        minima: VecDeque::with_capacity(window),
+        averages: VecDeque::with_capacity(window),
+        average_sum: 0.0,
+        window,
+        gain: UNITY_GAIN,
+        frame: 0,
+        output: Vec::with_capacity(channels),
+        position: 0,
+        ended: false,
+        stats,
+        block: Block::default(),
+    }
+}
+
+/// Returns the coefficients of the phases between two samples, of a windowed sinc
+/// interpolation filter.
+fn interpolation_filter() -> [[f32; TAPS]; OVERSAMPLING - 1] {
+    let mut phases = [[0.0; TAPS]; OVERSAMPLING - 1];
+    let half = TAPS.to_f32_lossy() / 2.0;
+    for (phase, coefficients) in phases.iter_mut().enumerate() {
+        let offset = (phase + 1).to_f32_lossy() / OVERSAMPLING.to_f32_lossy();
+        for (tap, coefficient) in coefficients.iter_mut().enumerate() {
+            // Distance from the interpolated point, between the middle two taps.
+            let t = tap.to_f32_lossy() - (half - 1.0) - offset;
+            let sinc = if t.abs() < f32::EPSILON {
+                1.0
+            } else {
+                (PI * t).sin() / (PI * t)
+            };
+            let window = 0.5 * (1.0 + (PI * t / half).cos());
+            *coefficient = sinc * window;
+        }
+
+        // Normalize for unity gain at DC.
+        let sum: f32 = coefficients.iter().sum();
+        for coefficient in coefficients.iter_mut() {
+            *coefficient /= sum;
+        }
+    }
+    phases
+}
+
+/// Statistics collected since they were last published.
+#[derive(Clone, Copy, Debug, Default)]
+struct Block {
+    /// Frames processed
+    frames: u64,
+
+    /// Frames with gain reduction
+    limited: u64,
+
+    /// Lowest gain applied
+    min_gain: f32,
+}
+
+impl Block {
+    /// Adds the statistics to those shared.
+    fn publish(self, stats: &Stats) {
+        if self.frames == 0 {
+            return;
+        }
+
+        let max_reduction_db = if self.min_gain > 0.0 {
+            -util::ratio_to_db(self.min_gain)
+        } else {
+            0.0
+        };
+        stats.record(self.frames, self.limited, max_reduction_db);
+    }
+}
+
+/// Audio source with true-peak look-ahead limiting.
+#[derive(Debug)]
+pub struct Limiter<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Highest true peak as a ratio
+    threshold: f32,
+
+    /// Smoothing coefficient when releasing
+    release: f32,
+
+    /// Number of channels
+    channels: usize,
+
+    /// Interpolation filter per phase
+    phases: [[f32; TAPS]; OVERSAMPLING - 1],
+
+    /// Recent input samples per channel, for interpolation
+    history: Vec<VecDeque<f32>>,
+
+    /// Delayed input samples, interleaved
+    delay: VecDeque<f32>,
+
+    /// Number of frames that the output lags behind the input
+    delay_frames: usize,
+
+    /// Required gains and their frame numbers, ascending from the front, for the
+    /// minimum over the look-ahead window
+    minima: VecDeque<(usize, f32)>,
+
+    /// Minimum gains of the last frames, to average
+    averages: VecDeque<f32>,
+
+    /// Sum of `averages`
+    average_sum: f32,
+
+    /// Length of the look-ahead window in frames
+    window: usize,
+
+    /// Gain applied to the current output frame
+    gain: f32,
+
+    /// Number of the next input frame
+    frame: usize,
+
+    /// Current output frame
+    output: Vec<f32>,
+
+    /// Position in the current output frame
+    position: usize,
+
+    /// Whether the input ended
+    ended: bool,
+
+    /// Where to publish statistics
+    stats: Option<Arc<Stats>>,
+
+    /// Statistics not yet published
+    block: Block,
+}
+
+impl<I> Limiter<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Reads a frame into the delay, and returns the gain it requires.
+    ///
+    /// Returns `None` when the input ended.
+    fn read_frame(&mut self) -> Option<f32> {
+        let mut peak: f32 = 0.0;
+        for channel in 0..self.channels {
+            let Some(sample) = self.input.next() else {
+                // Drop a partial frame.
+                for _ in 0..channel {
+                    self.delay.pop_back();
+                }
+                return None;
+            };
+            self.delay.push_back(sample);
+
+            let history = &mut self.history[channel];
+            history.pop_front();
+            history.push_back(sample);
+
+            // The middle sample, and the points between it and the next.
+            peak = peak.max(history[TAPS / 2 - 1].abs());
+            for phase in &self.phases {
+                let interpolated: f32 = phase.iter().zip(history.iter()).map(|(c, x)| c * x).sum();
+                peak = peak.max(interpolated.abs());
+            }
+        }
+
+        Some(if peak > self.threshold {
+            self.threshold / peak
+        } else {
+            UNITY_GAIN
+        })
+    }
+
+    /// Advances the gain by one frame.
+    fn update_gain(&mut self, required: f32) {
+        let frame = self.frame;
+        self.frame = self.frame.wrapping_add(1);
+
+        // Minimum over the look-ahead window.
+        while self
+            .minima
+            .back()
+            .is_some_and(|&(_, gain)| gain >= required)
+        {
+            self.minima.pop_back();
+        }
+        self.minima.push_back((frame, required));
+        while self
+            .minima
+            .front()
+            .is_some_and(|&(start, _)| start + self.window <= frame)
+        {
+            self.minima.pop_front();
+        }
+        let minimum = self.minima.front().map_or(UNITY_GAIN, |&(_, gain)| gain);
+
+        // Moving average, so the gain ramps down over the look-ahead window.
+        if self.averages.len() == self.window {
+            if let Some(oldest) = self.averages.pop_front() {
+                self.average_sum -= oldest;
+            }
+        }
+        self.averages.push_back(minimum);
+        self.average_sum += minimum;
+        let smoothed = (self.average_sum / self.averages.len().to_f32_lossy()).min(UNITY_GAIN);
+
+        if smoothed < self.gain {
+            self.gain = smoothed;
+        } else {
+            self.gain += (smoothed - self.gain) * self.release;
+        }
+    }
+
+    /// Fills the output frame from the delay, and returns whether there is one.
+    fn next_frame(&mut self) -> bool {
+        // Fill the look-ahead.
+        while !self.ended && self.delay.len() < self.delay_frames * self.channels {
+            match self.read_frame() {
+                Some(required) => self.update_gain(required),
+                None => self.ended = true,
+            }
+        }
+
+        if self.ended {
+            if self.delay.len() < self.channels {
+                self.publish();
+                return false;
+            }
+            self.update_gain(UNITY_GAIN);
+        } else {
+            match self.read_frame() {
+                Some(required) => self.update_gain(required),
+                None => {
+                    self.ended = true;
+                    return self.next_frame();
+                }
+            }
+        }
+
+        // Keep below the threshold despite rounding.
+        let gain = if self.gain < UNITY_GAIN {
+            self.gain * (1.0 - f32::EPSILON)
+        } else {
+            UNITY_GAIN
+        };
+
+        self.output.clear();
+        for _ in 0..self.channels {
+            if let Some(sample) = self.delay.pop_front() {
+                // Pass samples unchanged below the threshold.
+                self.output.push(if gain < UNITY_GAIN {
+                    sample * gain
+                } else {
+                    sample
+                });
+            }
+        }
+        self.position = 0;
+
+        self.block.frames += 1;
+        if gain < UNITY_GAIN {
+            self.block.limited += 1;
+            if self.block.min_gain <= 0.0 || gain < self.block.min_gain {
+                self.block.min_gain = gain;
+            }
+        }
+        if self.block.frames >= STATS_INTERVAL {
+            self.publish();
+        }
+
+        true
+    }
+
+    /// Publishes the statistics collected since the last time.
+    fn publish(&mut self) {
+        let block = std::mem::take(&mut self.block);
+        if let Some(stats) = self.stats.as_ref() {
+            block.publish(stats);
+        }
+    }
+
+    /// Clears the look-ahead and gain, for example after seeking.
+    fn reset(&mut self) {
+        self.publish();
+        for history in &mut self.history {
+            history.iter_mut().for_each(|sample| *sample = 0.0);
+        }
+        self.delay.clear();
+        self.minima.clear();
+        self.averages.clear();
+        self.average_sum = 0.0;
+        self.gain = UNITY_GAIN;
+        self.output.clear();
+        self.position = 0;
+        self.ended = false;
+    }
+}
+
+impl<I> Iterator for Limiter<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.output.len() && !self.next_frame() {
+            return None;
+        }
+
+        let sample = self.output[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.delay.len() + self.output.len() - self.position;
+        let (lower, upper) = self.input.size_hint();
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I> Source for Limiter<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        let buffered = self.delay.len() + self.output.len() - self.position;
+        self.input
+            .current_span_len()
+            .map(|len| len.saturating_add(buffered))
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    /// Also clears the look-ahead when successful.
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        Ok(())
+    }
+}
+
+impl<I> Drop for Limiter<I> {
+    /// Publishes the last statistics, for example when skipping to the next track.
+    fn drop(&mut self) {
+        if let Some(stats) = self.stats.as_ref() {
+            self.block.publish(stats);
+        }
+    }
+}
@@ -19,7 +19,7 @@
 //! * 12.5 kHz - High shelf (Q=0.707)
 //!
 //! The filter gains are dynamically adjusted based on:
-//! * Current listening level (volume)
+//! * Current listening level (output amplitude, after the volume curve)
 //! * Target LUFS level
 //! * Equal-loudness contour shapes
 //! * Reference playback level (83 dB SPL)
@@ -29,6 +29,8 @@ use std::f32::consts::SQRT_2;
 use biquad::{Biquad, Coefficients, DirectForm1, Q_BUTTERWORTH_F32, ToHertz, Type};
 use rodio::SampleRate;
 
+use crate::util::ratio_to_db;
+
 /// ISO 226:2013 standard frequencies in Hz
 const FREQUENCIES: &[f32] = &[
     20.0, 25.0, 31.5, 40.0, 50.0, 63.0, 80.0, 100.0, 125.0, 160.0, 200.0, 250.0, 315.0, 400.0,
@@ -184,11 +186,17 @@ impl EqualLoudnessFilter {
     /// Maps volume and LUFS target to corresponding phon level
     ///
     /// Converts the current listening level to phons for equal-loudness curve selection.
+    /// The volume is the output amplitude after the volume curve, so the listening level
+    /// drops by the actual attenuation in dB, whichever curve is configured.
     /// Results are clamped to the valid range (0-100 phons) defined in ISO 226:2013.
     fn calculate_phon(volume: f32, lufs_target: f32) -> f32 {
+        if volume <= 0.0 {
+            return 0.0;
+        }
+
         // Map volume to phon level for equal-loudness curve selection
         let listening_level = REFERENCE_SPL + lufs_target;
-        (listening_level * volume).clamp(0.0, 100.0)
+        (listening_level + ratio_to_db(volume)).clamp(0.0, 100.0)
     }
 
     /// Updates filter coefficients when volume changes
@@ -199,13 +207,14 @@ impl EqualLoudnessFilter {
         if 2.0 * (volume - self.volume).abs() > f32::EPSILON * (volume.abs() + self.volume.abs()) {
             let phon = Self::calculate_phon(volume, self.lufs_target);
 
+            // Set the volume first: it bounds the boost of the new filters
+            self.volume = volume;
+
             // Create and update to new filters
             for band in 0..NUM_BANDS {
                 let new_coeffs = self.calculate_coefficients_for_phon(band, phon);
                 self.filters[band].update_coefficients(new_coeffs);
             }
-
-            self.volume = volume;
         }
     }
 
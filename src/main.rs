@@ -44,24 +44,39 @@
 //! * Maximum backoff of 10 seconds
 //! * Random jitter between attempts
 
-use std::{env, fs, path::Path, process, time::Duration};
+use std::{
+    env, fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
 
 use clap::{Parser, ValueHint, command};
 use exponential_backoff::Backoff;
 use log::{LevelFilter, debug, error, info, trace, warn};
 
 use pleezer::{
+    api,
     arl::Arl,
+    compressor,
     config::{Config, Credentials},
-    decrypt,
+    convolve, crossfade, crossfeed, decrypt, equalizer,
     error::{Error, ErrorKind, Result},
+    mixer, mqtt, normalize,
     player::Player,
     protocol::connect::{DeviceType, Percentage},
-    remote,
+    remote, resample,
     signal::{self, ShutdownSignal},
     uuid::Uuid,
+    volume,
 };
 
+#[cfg(target_os = "linux")]
+use pleezer::mpris;
+#[cfg(unix)]
+use pleezer::socket;
+
 /// Build profile indicator for logging.
 ///
 /// Shows "debug" when built without optimizations.
@@ -108,6 +123,7 @@ const MAX_BACKOFF: Duration = Duration::from_secs(10);
 ///   - Volume normalization
 ///   - Dithering configuration
 ///   - Noise shaping profiles
+///   - Resampling quality
 /// * Connection behavior (interruptions, binding)
 /// * Debug features (logging, eavesdropping)
 ///
@@ -139,7 +155,8 @@ struct Args {
     /// Select the audio output device
     ///
     /// Format: [<host>][|<device>][|<sample rate>][|<sample format>]
-    /// Use "?" to list available stereo 44.1/48 kHz output devices.
+    /// Use "?" to list available stereo output devices.
+    /// Use "pipe:<path>" or "file:<path>" for raw PCM output ("file:-" for stdout).
     /// If omitted, uses the system default output device.
     #[arg(short, long, default_value = None, env = "PLEEZER_DEVICE")]
     device: Option<String>,
@@ -150,6 +167,28 @@ struct Args {
     #[arg(long, default_value_t = false, env = "PLEEZER_NORMALIZE_VOLUME")]
     normalize_volume: bool,
 
+    /// Set volume normalization mode
+    ///
+    /// Values: track, album, auto
+    /// Album mode keeps the loudness differences between tracks of an album.
+    /// Auto mode uses album mode when playing consecutive tracks of an album.
+    #[arg(
+        long,
+        value_name = "MODE",
+        default_value_t = normalize::Mode::default(),
+        env = "PLEEZER_NORMALIZATION_MODE",
+        verbatim_doc_comment
+    )]
+    normalization_mode: normalize::Mode,
+
+    /// Normalize podcast episodes in real time
+    ///
+    /// Applies automatic gain control to episodes, like to livestreams, instead of a
+    /// fixed gain. Evens out shows that change level throughout.
+    /// Requires --normalize-volume.
+    #[arg(long, default_value_t = false, env = "PLEEZER_NORMALIZE_EPISODES")]
+    normalize_episodes: bool,
+
     /// Enable loudness compensation (ISO 226:2013)
     ///
     /// Applies frequency-dependent gain to match human hearing sensitivity.
@@ -157,6 +196,36 @@ struct Args {
     #[arg(long, default_value_t = false, env = "PLEEZER_LOUDNESS")]
     loudness: bool,
 
+    /// Enable headphone crossfeed
+    ///
+    /// Mixes part of each stereo channel into the other, like speakers in a room.
+    /// Reduces listening fatigue with hard-panned recordings on headphones.
+    #[arg(long, default_value_t = false, env = "PLEEZER_CROSSFEED")]
+    crossfeed: bool,
+
+    /// Set crossfeed preset
+    ///
+    /// Values: default (700 Hz, 4.5 dB), chu-moy (700 Hz, 6 dB), jan-meier (650 Hz, 9.5 dB)
+    #[arg(
+        long,
+        value_name = "PRESET",
+        default_value_t = crossfeed::Preset::default(),
+        env = "PLEEZER_CROSSFEED_PRESET"
+    )]
+    crossfeed_preset: crossfeed::Preset,
+
+    /// Enable night mode
+    ///
+    /// Compresses loud passages and raises quiet ones, for listening at low volume.
+    #[arg(long, default_value_t = false, env = "PLEEZER_NIGHT_MODE")]
+    night_mode: bool,
+
+    /// Switch night mode on and off at these hours
+    ///
+    /// Format: HH:MM-HH:MM in local time, for example 22:00-07:00.
+    #[arg(long, value_name = "HOURS", env = "PLEEZER_NIGHT_MODE_HOURS")]
+    night_mode_hours: Option<compressor::Window>,
+
     /// Set initial volume level (0-100)
     ///
     /// Applied when no volume is reported by Deezer client or when reported as maximum.
@@ -169,6 +238,84 @@ struct Args {
     )]
     initial_volume: Option<u8>,
 
+    /// Set volume curve
+    ///
+    /// Values: linear, cubic, log
+    #[arg(
+        long,
+        value_name = "CURVE",
+        default_value_t = volume::Curve::default(),
+        env = "PLEEZER_VOLUME_CURVE"
+    )]
+    volume_curve: volume::Curve,
+
+    /// Set dynamic range of the logarithmic volume curve in dB
+    ///
+    /// Lower values make quiet settings louder, for amplifiers with little gain.
+    #[arg(
+        long,
+        value_name = "DB",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u8).range(10..=120),
+        env = "PLEEZER_VOLUME_RANGE"
+    )]
+    volume_range: u8,
+
+    /// Cap volume at this level (0-100)
+    ///
+    /// Higher volumes set by Deezer clients are limited, to protect speakers and ears.
+    #[arg(
+        long,
+        value_name = "PERCENTAGE",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        env = "PLEEZER_MAX_VOLUME"
+    )]
+    max_volume: Option<u8>,
+
+    /// Raise volume to at least this level (0-100)
+    ///
+    /// Lower volumes are raised to this level, but zero still mutes.
+    /// Useful for amplifiers that are inaudible at low levels.
+    #[arg(
+        long,
+        value_name = "PERCENTAGE",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        env = "PLEEZER_MIN_VOLUME"
+    )]
+    min_volume: Option<u8>,
+
+    /// Keep the output at full volume and leave the volume to an external amplifier
+    ///
+    /// Volume changes from Deezer clients are still reported back, and forwarded
+    /// with --volume-command or --volume-mixer.
+    #[arg(long, default_value_t = false, env = "PLEEZER_FIXED_VOLUME")]
+    fixed_volume: bool,
+
+    /// Script to execute on volume changes with a fixed output
+    ///
+    /// Receives the volume (0-100) in the VOLUME environment variable.
+    /// Requires --fixed-volume.
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        value_hint = ValueHint::ExecutablePath,
+        requires = "fixed_volume",
+        env = "PLEEZER_VOLUME_COMMAND"
+    )]
+    volume_command: Option<String>,
+
+    /// Set ALSA mixer control on volume changes with a fixed output (Linux only)
+    ///
+    /// Format: [DEVICE|]CONTROL, for example PCM or "hw:0|Master".
+    /// Requires --fixed-volume.
+    #[arg(
+        long,
+        value_name = "CONTROL",
+        requires = "fixed_volume",
+        env = "PLEEZER_VOLUME_MIXER"
+    )]
+    volume_mixer: Option<mixer::AlsaControl>,
+
     /// Set dither bit depth based on DAC linearity (ENOB)
     ///
     /// Set to effective number of bits from DAC measurements, or 0 to disable dithering.
@@ -202,6 +349,70 @@ struct Args {
     )]
     noise_shaping: u8,
 
+    /// Set sample rate conversion quality
+    ///
+    /// Tracks are converted to the sample rate of the output device.
+    /// Values: fast, medium, high, best
+    #[arg(
+        long,
+        value_name = "QUALITY",
+        default_value_t = resample::Quality::default(),
+        env = "PLEEZER_RESAMPLE_QUALITY"
+    )]
+    resample_quality: resample::Quality,
+
+    /// Play tracks bit-perfect when possible
+    ///
+    /// Switches the output device to the sample rate and bit depth of each track.
+    /// Processing is bypassed at full volume without normalization or loudness.
+    #[arg(long, default_value_t = false, env = "PLEEZER_BIT_PERFECT")]
+    bit_perfect: bool,
+
+    /// Crossfade between tracks for this many seconds (0-12)
+    ///
+    /// Tracks from the same album still play gaplessly. Set to 0 to disable.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u8).range(0..=12),
+        default_value_t = 0,
+        env = "PLEEZER_CROSSFADE"
+    )]
+    crossfade: u8,
+
+    /// Set crossfade curve
+    ///
+    /// Values: linear, equal-power
+    #[arg(
+        long,
+        value_name = "CURVE",
+        default_value_t = crossfade::Curve::default(),
+        env = "PLEEZER_CROSSFADE_CURVE"
+    )]
+    crossfade_curve: crossfade::Curve,
+
+    /// Parametric equalizer file
+    ///
+    /// TOML, or Equalizer APO text as published by AutoEQ.
+    /// The preamp is lowered automatically so boosts cannot clip.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, env = "PLEEZER_EQUALIZER")]
+    equalizer: Option<String>,
+
+    /// Impulse response WAV file for room correction
+    ///
+    /// Mono or stereo, as exported from REW or DRC-FIR. Use {rate} in the
+    /// file name to select a file per output sample rate, for example
+    /// "room-{rate}.wav". Otherwise the file is resampled as needed.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, env = "PLEEZER_CONVOLUTION", verbatim_doc_comment)]
+    convolution: Option<String>,
+
+    /// File to store podcast episode positions in
+    ///
+    /// Episodes resume where they were left, also after a restart.
+    /// Without a file, positions are kept until pleezer exits.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, env = "PLEEZER_BOOKMARKS")]
+    bookmarks: Option<PathBuf>,
+
     /// Maximum RAM (in MB) to use for storing audio files in memory
     ///
     /// If not specified or if a track exceeds this limit, temporary files will be used.
@@ -232,6 +443,59 @@ struct Args {
     #[arg(long, value_hint = ValueHint::ExecutablePath, env = "PLEEZER_HOOK")]
     hook: Option<String>,
 
+    /// Serve the HTTP control API on this address and port
+    ///
+    /// For example "127.0.0.1:8080". Requests must carry the `api_token`
+    /// from the secrets file as a bearer token.
+    #[arg(long, value_name = "ADDRESS", env = "PLEEZER_API")]
+    api: Option<SocketAddr>,
+
+    /// Register as MPRIS media player on this D-Bus bus
+    ///
+    /// Use "session" for desktop integration and media keys, or "system" when
+    /// running as a service (requires a bus policy allowing the name).
+    #[cfg(target_os = "linux")]
+    #[arg(long, value_name = "BUS", env = "PLEEZER_MPRIS")]
+    mpris: Option<mpris::Bus>,
+
+    /// Publish status to and take commands from this MQTT broker
+    ///
+    /// For example "mqtt://localhost:1883". Log in with `mqtt_username` and
+    /// `mqtt_password` from the secrets file, if set.
+    #[arg(long, value_name = "URL", env = "PLEEZER_MQTT")]
+    mqtt: Option<url::Url>,
+
+    /// Base topic for MQTT state and commands
+    ///
+    /// Defaults to "pleezer/<device id>".
+    #[arg(
+        long,
+        value_name = "TOPIC",
+        requires = "mqtt",
+        env = "PLEEZER_MQTT_TOPIC"
+    )]
+    mqtt_topic: Option<String>,
+
+    /// Prefix for Home Assistant MQTT discovery
+    ///
+    /// Set to an empty string to disable discovery.
+    #[arg(long, value_name = "PREFIX", default_value = mqtt::DEFAULT_DISCOVERY_PREFIX, env = "PLEEZER_MQTT_DISCOVERY")]
+    mqtt_discovery: String,
+
+    /// Serve the line-delimited JSON control protocol on this unix socket
+    ///
+    /// Also the socket that `pleezer ctl` connects to.
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, global = true, env = "PLEEZER_SOCKET")]
+    socket: Option<PathBuf>,
+
+    /// Permissions of the control socket, in octal
+    ///
+    /// Only the owner can connect by default. Use 660 to let the group connect.
+    #[cfg(unix)]
+    #[arg(long, value_name = "MODE", default_value = "600", value_parser = parse_mode, env = "PLEEZER_SOCKET_MODE")]
+    socket_mode: u32,
+
     /// Suppress all output except warnings and errors
     #[arg(short, long, default_value_t = false, group = ARGS_GROUP_LOGGING, env = "PLEEZER_QUIET")]
     quiet: bool,
@@ -254,6 +518,100 @@ struct Args {
         env = "PLEEZER_EAVESDROP"
     )]
     eavesdrop: bool,
+
+    #[cfg(unix)]
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands, next to running the player.
+#[cfg(unix)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, clap::Subcommand)]
+enum Command {
+    /// Control a running pleezer through its control socket
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+}
+
+/// Commands of `pleezer ctl`.
+#[cfg(unix)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, clap::Subcommand)]
+enum CtlCommand {
+    /// Print the player status
+    Status,
+
+    /// Start or resume playback
+    Play,
+
+    /// Pause playback
+    Pause,
+
+    /// Skip to the next track
+    Next,
+
+    /// Skip to the previous track
+    Previous,
+
+    /// Seek to a position in seconds
+    Seek { position: f64 },
+
+    /// Set the volume in percent
+    Volume { volume: f32 },
+
+    /// Set the repeat mode: none, all or one
+    Repeat { mode: String },
+
+    /// Enable or disable shuffle
+    Shuffle {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+
+    /// Switch the equalizer on or off
+    Equalizer {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+
+    /// Switch headphone crossfeed on or off
+    Crossfeed {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+
+    /// Switch night mode on or off
+    NightMode {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+
+    /// Print events as they happen, until interrupted
+    Watch,
+}
+
+#[cfg(unix)]
+impl From<&CtlCommand> for socket::Request {
+    fn from(command: &CtlCommand) -> Self {
+        match command {
+            CtlCommand::Status => Self::Status,
+            CtlCommand::Play => Self::Play,
+            CtlCommand::Pause => Self::Pause,
+            CtlCommand::Next => Self::Next,
+            CtlCommand::Previous => Self::Previous,
+            CtlCommand::Seek { position } => Self::Seek {
+                position: *position,
+            },
+            CtlCommand::Volume { volume } => Self::Volume { volume: *volume },
+            CtlCommand::Repeat { mode } => Self::Repeat { mode: mode.clone() },
+            CtlCommand::Shuffle { enabled } => Self::Shuffle { enabled: *enabled },
+            CtlCommand::Equalizer { enabled } => Self::Equalizer { enabled: *enabled },
+            CtlCommand::Crossfeed { enabled } => Self::Crossfeed { enabled: *enabled },
+            CtlCommand::NightMode { enabled } => Self::NightMode { enabled: *enabled },
+            CtlCommand::Watch => Self::Watch,
+        }
+    }
 }
 
 /// Initialize logging system.
@@ -359,6 +717,46 @@ fn parse_secrets(secrets: impl AsRef<Path>) -> Result<toml::Value> {
     })
 }
 
+/// Parse file permissions in octal, like "660".
+#[cfg(unix)]
+fn parse_mode(mode: &str) -> Result<u32> {
+    u32::from_str_radix(mode, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .ok_or_else(|| {
+            Error::invalid_argument(format!("invalid mode {mode}: expected octal like 660"))
+        })
+}
+
+/// Send a command to a running pleezer over its control socket.
+///
+/// Prints the status after the command as JSON, or for `watch`, every event as a
+/// line of JSON until the connection closes.
+///
+/// # Errors
+///
+/// Returns error if no socket is configured, it cannot be connected to, or the
+/// command fails.
+#[cfg(unix)]
+async fn ctl(path: Option<&Path>, command: &CtlCommand) -> Result<()> {
+    let path = path.ok_or_else(|| {
+        Error::invalid_argument("no control socket: set --socket or PLEEZER_SOCKET")
+    })?;
+    let mut client = socket::Client::connect(path).await?;
+
+    let request = socket::Request::from(command);
+    let reply = client.request(&request).await?;
+    if request == socket::Request::Watch {
+        while let Some(line) = client.next().await? {
+            println!("{line}");
+        }
+    } else {
+        println!("{}", serde_json::to_string_pretty(&reply)?);
+    }
+
+    Ok(())
+}
+
 /// Main application loop.
 ///
 /// Handles the core application lifecycle:
@@ -402,12 +800,10 @@ async fn run(args: Args) -> Result<ShutdownSignal> {
         // List available devices and exit.
         let devices = Player::enumerate_devices();
         if devices.is_empty() {
-            return Err(Error::not_found(
-                "no stereo 44.1/48 kHz output devices found",
-            ));
+            return Err(Error::not_found("no stereo output devices found"));
         }
 
-        info!("available stereo 44.1/48 kHz output devices:");
+        info!("available stereo output devices:");
         for device in devices {
             info!("- {device}");
         }
@@ -418,7 +814,7 @@ async fn run(args: Args) -> Result<ShutdownSignal> {
         info!("using proxy: {proxy}");
     }
 
-    let config = {
+    let (config, api_token, mqtt_credentials) = {
         // Get the credentials from the secrets file.
         info!("parsing secrets from {}", args.secrets);
         let secrets = parse_secrets(args.secrets)?;
@@ -454,6 +850,18 @@ async fn run(args: Args) -> Result<ShutdownSignal> {
             None => None,
         };
 
+        let api_token = secrets
+            .get("api_token")
+            .and_then(|value| value.as_str())
+            .map(ToString::to_string);
+
+        let mqtt_credentials = ["mqtt_username", "mqtt_password"].map(|key| {
+            secrets
+                .get(key)
+                .and_then(|value| value.as_str())
+                .map(ToString::to_string)
+        });
+
         let app_name = env!("CARGO_PKG_NAME").to_owned();
         let app_version = env!("CARGO_PKG_VERSION").to_owned();
         let app_lang = "en".to_owned();
@@ -511,7 +919,7 @@ async fn run(args: Args) -> Result<ShutdownSignal> {
         let client_id = fastrand::usize(100_000_000..=999_999_999);
         trace!("client id: {client_id}");
 
-        Config {
+        let config = Config {
             app_name: app_name.clone(),
             app_version,
             app_lang,
@@ -526,13 +934,41 @@ async fn run(args: Args) -> Result<ShutdownSignal> {
             interruptions: !args.no_interruptions,
 
             normalization: args.normalize_volume,
+            normalization_mode: args.normalization_mode,
+            normalize_episodes: args.normalize_episodes,
             loudness: args.loudness,
+            crossfeed: args.crossfeed,
+            crossfeed_preset: args.crossfeed_preset,
+            night_mode: args.night_mode,
+            night_mode_hours: args.night_mode_hours,
             initial_volume: args
                 .initial_volume
                 .map(|volume| Percentage::from_percent(volume as f32)),
+            volume_curve: args.volume_curve,
+            volume_range: f32::from(args.volume_range),
+            max_volume: args
+                .max_volume
+                .map(|volume| Percentage::from_percent(f32::from(volume))),
+            min_volume: args
+                .min_volume
+                .map(|volume| Percentage::from_percent(f32::from(volume))),
+            fixed_volume: args.fixed_volume,
+            volume_command: args.volume_command,
+            volume_mixer: args.volume_mixer,
 
             dither_bits: args.dither_bits,
             noise_shaping: args.noise_shaping,
+            resample_quality: args.resample_quality,
+            bit_perfect: args.bit_perfect,
+            crossfade: Duration::from_secs(args.crossfade.into()),
+            crossfade_curve: args.crossfade_curve,
+            equalizer: args.equalizer.map(equalizer::Settings::load).transpose()?,
+            convolution: args
+                .convolution
+                .as_deref()
+                .map(convolve::Impulses::load)
+                .transpose()?,
+            bookmarks: args.bookmarks,
 
             // Convert MB to bytes
             max_ram: args.max_ram.map(|mb| mb * 1024 * 1024),
@@ -546,11 +982,62 @@ async fn run(args: Args) -> Result<ShutdownSignal> {
 
             eavesdrop: args.eavesdrop,
             bind_address: args.bind.parse()?,
-        }
+        };
+
+        (config, api_token, mqtt_credentials)
     };
 
     let player = Player::new(&config, args.device.as_deref().unwrap_or_default()).await?;
     let mut client = remote::Client::new(&config, player)?;
+
+    // Serve the control API until shutdown or restart.
+    let _api = match args.api {
+        Some(address) => {
+            let token = api_token
+                .ok_or_else(|| Error::unauthenticated("api_token not found in secrets file"))?;
+            Some(api::Server::bind(address, token, client.control())?)
+        }
+        None => None,
+    };
+
+    // Likewise, stay registered on D-Bus until shutdown or restart.
+    #[cfg(target_os = "linux")]
+    let _mpris = match args.mpris {
+        Some(bus) => {
+            Some(mpris::Mpris::new(bus, config.device_name.clone(), client.control()).await?)
+        }
+        None => None,
+    };
+
+    let _mqtt = match args.mqtt {
+        Some(broker) => {
+            let [username, password] = mqtt_credentials;
+            let options = mqtt::Options {
+                broker,
+                username,
+                password,
+                topic: args
+                    .mqtt_topic
+                    .unwrap_or_else(|| format!("pleezer/{}", config.device_id)),
+                discovery_prefix: Some(args.mqtt_discovery).filter(|prefix| !prefix.is_empty()),
+                device_id: config.device_id.to_string(),
+                device_name: config.device_name.clone(),
+            };
+            Some(mqtt::Mqtt::connect(options, client.control())?)
+        }
+        None => None,
+    };
+
+    #[cfg(unix)]
+    let _socket = match args.socket.as_ref() {
+        Some(path) => Some(socket::Server::bind(
+            path,
+            args.socket_mode,
+            client.control(),
+        )?),
+        None => None,
+    };
+
     let mut signals = signal::Handler::new()?;
 
     // Main application loop. This restarts the new remote client when it gets disconnected for
@@ -642,6 +1129,15 @@ async fn main() {
     let args = Args::parse();
     init_logger(&args);
 
+    #[cfg(unix)]
+    if let Some(Command::Ctl { command }) = &args.command {
+        if let Err(e) = ctl(args.socket.as_deref(), command).await {
+            error!("{e}");
+            process::exit(1);
+        }
+        process::exit(0);
+    }
+
     // Dump command line arguments before we do anything more.
     // This aids in debugging of whatever comes next.
     debug!("Command {:#?}", args);
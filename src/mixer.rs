@@ -0,0 +1,239 @@
+//! External volume control for a fixed output.
+//!
+//! When pleezer drives a preamp or amplifier with its own volume control, attenuating
+//! in software throws away resolution. With a fixed output, the player stays at full
+//! scale and forwards volume changes from Deezer clients to:
+//! * A command, that receives the volume in the `VOLUME` environment variable
+//! * An ALSA mixer control, selected by name (Linux only)
+//!
+//! Changes are forwarded in the background and in order. When changes arrive faster
+//! than they can be applied, only the last one is forwarded.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::mixer::Mixer;
+//!
+//! let control = "hw:0|Master".parse()?;
+//! if let Some(mixer) = Mixer::new(None, Some(control)) {
+//!     // 50% setting, 30 dB attenuation
+//!     mixer.set_volume(0.5, 0.031_6);
+//! }
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use tokio::{process::Command, sync::watch};
+
+use crate::error::{Error, Result};
+
+/// ALSA device of a mixer control, if none is given.
+pub const DEFAULT_DEVICE: &str = "default";
+
+/// Mixer control of an ALSA device.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AlsaControl {
+    /// ALSA device, for example `default` or `hw:0`.
+    pub device: String,
+
+    /// Name of the simple mixer control, for example `Master` or `PCM`.
+    pub control: String,
+}
+
+impl AlsaControl {
+    /// Sets the mixer control to an amplitude.
+    ///
+    /// Controls with dB information are set to the attenuation of the amplitude, from
+    /// their maximum. Other controls map the volume setting linearly onto their steps.
+    /// Controls with a switch are switched off at zero volume.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unavailable` if the device cannot be opened or has no such control.
+    #[cfg(target_os = "linux")]
+    fn set(&self, volume: f32, amplitude: f32) -> Result<()> {
+        use alsa::{
+            Round,
+            mixer::{MilliBel, SelemId},
+        };
+
+        let mixer = alsa::Mixer::new(&self.device, false).map_err(|e| {
+            Error::unavailable(format!("failed to open mixer of {}: {e}", self.device))
+        })?;
+        let selem = mixer
+            .find_selem(&SelemId::new(&self.control, 0))
+            .filter(alsa::mixer::Selem::has_playback_volume)
+            .ok_or_else(|| Error::unavailable(format!("no playback volume control {self}")))?;
+
+        let (min_db, max_db) = selem.get_playback_db_range();
+        let result = if max_db > min_db {
+            let db = if amplitude > 0.0 {
+                (max_db.to_db() + crate::util::ratio_to_db(amplitude)).max(min_db.to_db())
+            } else {
+                min_db.to_db()
+            };
+            selem.set_playback_db_all(MilliBel::from_db(db), Round::Floor)
+        } else {
+            let (min, max) = selem.get_playback_volume_range();
+            #[expect(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+            let steps = (f64::from(volume) * (max - min) as f64).round() as i64;
+            selem.set_playback_volume_all(min + steps)
+        };
+        result.map_err(|e| Error::unavailable(format!("failed to set {self}: {e}")))?;
+
+        if selem.has_playback_switch() {
+            selem
+                .set_playback_switch_all(i32::from(volume > 0.0))
+                .map_err(|e| Error::unavailable(format!("failed to switch {self}: {e}")))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the mixer control to an amplitude.
+    ///
+    /// # Errors
+    ///
+    /// Always returns `Unavailable`: ALSA is only available on Linux.
+    #[cfg(not(target_os = "linux"))]
+    fn set(&self, _volume: f32, _amplitude: f32) -> Result<()> {
+        Err(Error::unavailable(format!(
+            "cannot set {self}: ALSA mixers are only supported on Linux"
+        )))
+    }
+}
+
+impl fmt::Display for AlsaControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.device, self.control)
+    }
+}
+
+impl FromStr for AlsaControl {
+    type Err = Error;
+
+    /// Parses a control like `PCM` or `hw:0|Master`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (device, control) = s.split_once('|').unwrap_or((DEFAULT_DEVICE, s));
+        let (device, control) = (device.trim(), control.trim());
+        if device.is_empty() || control.is_empty() {
+            return Err(Error::invalid_argument(format!(
+                "invalid mixer control {s}: expected [DEVICE|]CONTROL"
+            )));
+        }
+
+        Ok(Self {
+            device: device.to_string(),
+            control: control.to_string(),
+        })
+    }
+}
+
+/// Volume as forwarded to the external volume control.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Setting {
+    /// Volume setting after the volume limits (0.0 to 1.0)
+    volume: f32,
+
+    /// Amplitude after the volume curve (0.0 to 1.0)
+    amplitude: f32,
+}
+
+/// Forwards volume changes to an external volume control.
+///
+/// Stops forwarding when dropped.
+#[derive(Debug)]
+pub struct Mixer {
+    /// Latest volume, picked up by the background task
+    tx: watch::Sender<Option<Setting>>,
+}
+
+impl Mixer {
+    /// Creates a mixer and starts forwarding in the background.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Command to run on every volume change
+    /// * `control` - ALSA mixer control to set on every volume change
+    ///
+    /// # Returns
+    ///
+    /// None if neither a command nor a mixer control is given.
+    #[must_use]
+    pub fn new(command: Option<String>, control: Option<AlsaControl>) -> Option<Self> {
+        if command.is_none() && control.is_none() {
+            return None;
+        }
+
+        let (tx, mut rx) = watch::channel(None);
+        tokio::spawn(async move {
+            while rx.changed().await.is_ok() {
+                let Some(setting) = *rx.borrow_and_update() else {
+                    continue;
+                };
+
+                if let Some(command) = command.as_ref() {
+                    Self::run(command, setting).await;
+                }
+
+                if let Some(control) = control.clone() {
+                    match tokio::task::spawn_blocking(move || {
+                        control.set(setting.volume, setting.amplitude)
+                    })
+                    .await
+                    {
+                        Ok(Ok(())) => trace!("mixer set to {:.0}%", setting.volume * 100.0),
+                        Ok(Err(e)) => error!("{e}"),
+                        Err(e) => error!("failed to set mixer: {e}"),
+                    }
+                }
+            }
+        });
+
+        Some(Self { tx })
+    }
+
+    /// Forwards a volume change.
+    ///
+    /// Repeats of the last volume are skipped, as Deezer clients send the volume with
+    /// every status update.
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - Volume setting after the volume limits (0.0 to 1.0)
+    /// * `amplitude` - Amplitude after the volume curve (0.0 to 1.0)
+    pub fn set_volume(&self, volume: f32, amplitude: f32) {
+        let setting = Setting { volume, amplitude };
+        self.tx.send_if_modified(|current| {
+            let modified = *current != Some(setting);
+            *current = Some(setting);
+            modified
+        });
+    }
+
+    /// Runs the volume command and waits for it to exit.
+    async fn run(command: &str, setting: Setting) {
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let percent = (setting.volume * 100.0).round() as u8;
+
+        match Command::new(command)
+            .env("VOLUME", percent.to_string())
+            .spawn()
+        {
+            Ok(mut child) => match child.wait().await {
+                Ok(status) => {
+                    if !status.success() {
+                        error!(
+                            "volume command exited with error {}",
+                            status.code().unwrap_or(-1)
+                        );
+                    }
+                }
+                Err(e) => error!("failed to wait for volume command: {e}"),
+            },
+            Err(e) => error!("failed to spawn volume command: {e}"),
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! MPRIS D-Bus interface for desktop integration (Linux only).
+//!
+//! Registers pleezer as `org.mpris.MediaPlayer2.pleezer`, so that desktop environments,
+//! media keys and tools like `playerctl` can show and control what is playing. Runs on
+//! the session bus for desktops, or the system bus for services.
+//!
+//! Implements:
+//! * `org.mpris.MediaPlayer2`: identity of the player
+//! * `org.mpris.MediaPlayer2.Player`: metadata, playback status, volume, position, loop
+//!   and shuffle status, and playback control
+//!
+//! Commands are applied through [`control`](crate::control), like commands from a
+//! Deezer client, which stays in sync.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::mpris::{Bus, Mpris};
+//!
+//! let mpris = Mpris::new(Bus::Session, "pleezer", client.control()).await?;
+//! // Registered until dropped.
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::watch, task::JoinHandle};
+use zbus::{
+    connection, fdo, interface,
+    object_server::SignalEmitter,
+    zvariant::{ObjectPath, OwnedValue, Value},
+};
+
+use crate::{
+    control::{Command, Handle, Status, TrackStatus},
+    error::{Error, Result},
+    protocol::connect::{Percentage, RepeatMode},
+};
+
+/// Well-known name of the player on the bus.
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.pleezer";
+
+/// Object path of the MPRIS interfaces.
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// Track ID for when there is no current track.
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+/// Difference from the expected position, beyond which the position was changed.
+const SEEK_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// Message bus to register on.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Bus {
+    /// Bus of the logged in user, for desktops
+    #[default]
+    Session,
+
+    /// Bus of the system, for services
+    System,
+}
+
+impl fmt::Display for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Session => write!(f, "session"),
+            Self::System => write!(f, "system"),
+        }
+    }
+}
+
+impl FromStr for Bus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "session" => Ok(Self::Session),
+            "system" => Ok(Self::System),
+            _ => Err(Error::invalid_argument(format!(
+                "invalid bus {s}: expected session or system"
+            ))),
+        }
+    }
+}
+
+/// The `org.mpris.MediaPlayer2` interface.
+struct Root {
+    /// Name of the player, as shown to users
+    identity: String,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    /// Does nothing: there is no window to raise.
+    fn raise(&self) {}
+
+    /// Does nothing: pleezer cannot be quit over D-Bus.
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The `org.mpris.MediaPlayer2.Player` interface.
+struct MediaPlayer {
+    /// Handle to control the player with
+    control: Handle,
+
+    /// Latest status of the player
+    status: watch::Receiver<Status>,
+}
+
+impl MediaPlayer {
+    /// Sends a command, and converts errors for D-Bus.
+    async fn send(&self, command: Command) -> fdo::Result<Status> {
+        self.control
+            .send(command)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl MediaPlayer {
+    async fn next(&self) -> fdo::Result<()> {
+        self.send(Command::Next).await.map(drop)
+    }
+
+    async fn previous(&self) -> fdo::Result<()> {
+        self.send(Command::Previous).await.map(drop)
+    }
+
+    async fn pause(&self) -> fdo::Result<()> {
+        self.send(Command::Pause).await.map(drop)
+    }
+
+    async fn play_pause(&self) -> fdo::Result<()> {
+        let command = if self.status.borrow().playing {
+            Command::Pause
+        } else {
+            Command::Play
+        };
+        self.send(command).await.map(drop)
+    }
+
+    /// Pauses: Deezer Connect has no notion of stopping.
+    async fn stop(&self) -> fdo::Result<()> {
+        self.send(Command::Pause).await.map(drop)
+    }
+
+    async fn play(&self) -> fdo::Result<()> {
+        self.send(Command::Play).await.map(drop)
+    }
+
+    /// Seeks relative to the current position, in microseconds.
+    ///
+    /// Seeking beyond the end skips to the next track.
+    async fn seek(&self, offset: i64) -> fdo::Result<()> {
+        let status = self
+            .control
+            .status()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let Some(duration) = status.track.as_ref().and_then(|track| track.duration) else {
+            return Ok(());
+        };
+
+        let position = status.position.unwrap_or_default();
+        let delta = Duration::from_micros(offset.unsigned_abs());
+        let target = if offset.is_negative() {
+            position.saturating_sub(delta)
+        } else {
+            position.saturating_add(delta)
+        };
+
+        if target > duration {
+            self.send(Command::Next).await.map(drop)
+        } else {
+            self.send(Command::Seek(target)).await.map(drop)
+        }
+    }
+
+    /// Seeks to an absolute position in microseconds, if the track is still current.
+    async fn set_position(&self, track_id: ObjectPath<'_>, position: i64) -> fdo::Result<()> {
+        let target = {
+            let status = self.status.borrow();
+            let Some(track) = status.track.as_ref() else {
+                return Ok(());
+            };
+            if track_path(track) != track_id {
+                return Ok(());
+            }
+
+            let Ok(position) = u64::try_from(position) else {
+                return Ok(());
+            };
+            let position = Duration::from_micros(position);
+            if track.duration.is_none_or(|duration| position > duration) {
+                return Ok(());
+            }
+
+            position
+        };
+
+        self.send(Command::Seek(target)).await.map(drop)
+    }
+
+    async fn open_uri(&self, _uri: &str) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported(
+            "opening URIs is not supported".to_string(),
+        ))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> String {
+        let status = self.status.borrow();
+        match (status.track.is_some(), status.playing) {
+            (false, _) => "Stopped",
+            (true, true) => "Playing",
+            (true, false) => "Paused",
+        }
+        .to_string()
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> String {
+        match self.status.borrow().repeat.parse() {
+            Ok(RepeatMode::One) => "Track",
+            Ok(RepeatMode::All) => "Playlist",
+            _ => "None",
+        }
+        .to_string()
+    }
+
+    #[zbus(property)]
+    async fn set_loop_status(&mut self, value: String) -> fdo::Result<()> {
+        let repeat_mode = match value.as_str() {
+            "None" => RepeatMode::None,
+            "Track" => RepeatMode::One,
+            "Playlist" => RepeatMode::All,
+            _ => {
+                return Err(fdo::Error::InvalidArgs(format!(
+                    "invalid loop status {value}"
+                )));
+            }
+        };
+        self.send(Command::Repeat(repeat_mode)).await.map(drop)
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn shuffle(&self) -> bool {
+        self.status.borrow().shuffle
+    }
+
+    #[zbus(property)]
+    async fn set_shuffle(&mut self, value: bool) -> fdo::Result<()> {
+        self.send(Command::Shuffle(value)).await.map(drop)
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        metadata(self.status.borrow().track.as_ref())
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        f64::from(self.status.borrow().volume) / 100.0
+    }
+
+    #[zbus(property)]
+    async fn set_volume(&mut self, value: f64) -> fdo::Result<()> {
+        #[expect(clippy::cast_possible_truncation)]
+        let volume = Percentage::from_ratio(value.clamp(0.0, 1.0) as f32);
+        self.send(Command::Volume(volume)).await.map(drop)
+    }
+
+    /// Returns the current position in microseconds.
+    ///
+    /// Asks the player, as the published status is only updated every few seconds.
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn position(&self) -> i64 {
+        let position = match self.control.status().await {
+            Ok(status) => status.position,
+            Err(_) => self.status.borrow().position,
+        };
+        position.map_or(0, micros)
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        let status = self.status.borrow();
+        status.track.is_some()
+            && (status.repeat == "all"
+                || status
+                    .queue_position
+                    .is_some_and(|position| position + 1 < status.queue_length))
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        let status = self.status.borrow();
+        status.track.is_some()
+            && (status.repeat == "all"
+                || status.queue_position.is_some_and(|position| position > 0))
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        self.status.borrow().track.is_some()
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        self.status.borrow().track.is_some()
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        self.status
+            .borrow()
+            .track
+            .as_ref()
+            .is_some_and(|track| track.duration.is_some())
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+/// Registration of pleezer on a message bus.
+///
+/// Unregisters when dropped.
+#[derive(Debug)]
+pub struct Mpris {
+    /// Task publishing changes of the status
+    task: JoinHandle<()>,
+}
+
+impl Mpris {
+    /// Registers on a message bus and starts publishing changes in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `bus` - Message bus to register on
+    /// * `identity` - Name of the player, as shown to users
+    /// * `control` - Handle to control the player with
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unavailable` if the bus cannot be connected to, or the name is taken.
+    pub async fn new(bus: Bus, identity: impl Into<String>, control: Handle) -> Result<Self> {
+        let builder = match bus {
+            Bus::Session => connection::Builder::session()?,
+            Bus::System => connection::Builder::system()?,
+        };
+
+        let status = control.watch();
+        let connection = builder
+            .name(BUS_NAME)?
+            .serve_at(
+                OBJECT_PATH,
+                Root {
+                    identity: identity.into(),
+                },
+            )?
+            .serve_at(
+                OBJECT_PATH,
+                MediaPlayer {
+                    control,
+                    status: status.clone(),
+                },
+            )?
+            .build()
+            .await?;
+        info!("registered {BUS_NAME} on the {bus} bus");
+
+        // The task owns the connection, so that the name is released when it is aborted.
+        let task = tokio::spawn(async move {
+            if let Err(e) = publish(&connection, status).await {
+                error!("failed to publish mpris status: {e}");
+            }
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for Mpris {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Signals changes of the status to MPRIS clients, until the status channel closes.
+///
+/// Position changes are only signalled when they jump, as clients extrapolate the
+/// position while playing.
+async fn publish(connection: &zbus::Connection, mut status: watch::Receiver<Status>) -> Result<()> {
+    let player = connection
+        .object_server()
+        .interface::<_, MediaPlayer>(OBJECT_PATH)
+        .await?;
+
+    let mut last = status.borrow_and_update().clone();
+    let mut last_update = Instant::now();
+
+    while status.changed().await.is_ok() {
+        let current = status.borrow_and_update().clone();
+        let emitter = player.signal_emitter();
+        let interface = player.get().await;
+
+        let track_changed = current.track != last.track;
+        if track_changed || current.playing != last.playing {
+            interface.playback_status_changed(emitter).await?;
+        }
+        if track_changed {
+            interface.metadata_changed(emitter).await?;
+            interface.can_play_changed(emitter).await?;
+            interface.can_pause_changed(emitter).await?;
+            interface.can_seek_changed(emitter).await?;
+        }
+        if current.volume != last.volume {
+            interface.volume_changed(emitter).await?;
+        }
+        if current.repeat != last.repeat {
+            interface.loop_status_changed(emitter).await?;
+        }
+        if current.shuffle != last.shuffle {
+            interface.shuffle_changed(emitter).await?;
+        }
+        if track_changed
+            || current.repeat != last.repeat
+            || current.queue_position != last.queue_position
+            || current.queue_length != last.queue_length
+        {
+            interface.can_go_next_changed(emitter).await?;
+            interface.can_go_previous_changed(emitter).await?;
+        }
+
+        // Signal seeks within the same track, when the position is not where it would
+        // have been by playing on.
+        let same_track = current.track.as_ref().map(|track| track.id)
+            == last.track.as_ref().map(|track| track.id);
+        if let (true, Some(position), Some(last_position)) =
+            (same_track, current.position, last.position)
+        {
+            let expected = if last.playing {
+                last_position.saturating_add(last_update.elapsed())
+            } else {
+                last_position
+            };
+            if position.abs_diff(expected) > SEEK_TOLERANCE {
+                MediaPlayer::seeked(emitter, micros(position)).await?;
+            }
+        }
+
+        last = current;
+        last_update = Instant::now();
+    }
+
+    Ok(())
+}
+
+/// Returns the MPRIS metadata of a track.
+fn metadata(track: Option<&TrackStatus>) -> HashMap<String, OwnedValue> {
+    let mut metadata = HashMap::new();
+    let mut insert = |key: &str, value: Value<'_>| {
+        if let Ok(value) = OwnedValue::try_from(value) {
+            metadata.insert(key.to_string(), value);
+        }
+    };
+
+    let Some(track) = track else {
+        insert(
+            "mpris:trackid",
+            ObjectPath::from_static_str_unchecked(NO_TRACK).into(),
+        );
+        return metadata;
+    };
+
+    insert("mpris:trackid", track_path(track).into());
+    if let Some(duration) = track.duration {
+        insert("mpris:length", micros(duration).into());
+    }
+    if let Some(title) = track.title.as_deref() {
+        insert("xesam:title", title.into());
+    }
+    insert("xesam:artist", vec![track.artist.as_str()].into());
+    if let Some(album) = track.album.as_deref() {
+        insert("xesam:album", album.into());
+    }
+    if let Some(cover_url) = track.cover_url.as_deref() {
+        insert("mpris:artUrl", cover_url.into());
+    }
+
+    metadata
+}
+
+/// Returns the MPRIS track ID of a track.
+///
+/// IDs of uploaded tracks are negative, so the sign is spelled out to make a valid
+/// object path.
+fn track_path(track: &TrackStatus) -> ObjectPath<'static> {
+    let id = track.id.to_string().replace('-', "_");
+    ObjectPath::try_from(format!("/org/pleezer/{}/{id}", track.typ))
+        .unwrap_or_else(|_| ObjectPath::from_static_str_unchecked(NO_TRACK))
+}
+
+/// Converts a duration to MPRIS microseconds.
+fn micros(duration: Duration) -> i64 {
+    duration.as_micros().try_into().unwrap_or(i64::MAX)
+}
@@ -0,0 +1,496 @@
+//! MQTT bridge for home automation.
+//!
+//! Publishes the player status to retained topics on an MQTT broker, and handles
+//! commands from command topics. Commands are applied through
+//! [`control`](crate::control), like commands from a Deezer client, which stays in
+//! sync.
+//!
+//! # Topics
+//!
+//! State topics, below the base topic (default `pleezer/<device id>`):
+//!
+//! | Topic          | Payload                                    |
+//! |----------------|--------------------------------------------|
+//! | `availability` | `online` or `offline`                      |
+//! | `state`        | `playing`, `paused` or `idle`              |
+//! | `title`        | Title of the current track                 |
+//! | `artist`       | Artist, podcast, station or author         |
+//! | `album`        | Album or audio book                        |
+//! | `type`         | `song`, `episode`, `livestream`, `chapter` |
+//! | `cover_url`    | URL of the cover art                       |
+//! | `duration`     | Duration in seconds                        |
+//! | `position`     | Playback position in seconds               |
+//! | `volume`       | Volume from 0.0 to 1.0                     |
+//! | `repeat`       | `none`, `all` or `one`                     |
+//! | `shuffle`      | `true` or `false`                          |
+//! | `equalizer`    | `true` or `false`                          |
+//! | `crossfeed`    | `true` or `false`                          |
+//! | `night_mode`   | `true` or `false`                          |
+//! | `controller`   | Device ID of the connected Deezer client   |
+//! | `format`       | Encoded audio format, like `FLAC 1.411M`   |
+//! | `decoder`      | Decoded audio format                       |
+//! | `status`       | [`Status`] as JSON, like the HTTP API      |
+//!
+//! Topics without a value, like the title when nothing plays, are empty.
+//!
+//! Command topics, below `<base topic>/command`:
+//!
+//! | Topic        | Payload                |
+//! |--------------|------------------------|
+//! | `play`       |                        |
+//! | `pause`      |                        |
+//! | `playpause`  |                        |
+//! | `next`       |                        |
+//! | `previous`   |                        |
+//! | `seek`       | Position in seconds    |
+//! | `volume`     | Volume from 0.0 to 1.0 |
+//! | `repeat`     | `none`, `all` or `one` |
+//! | `shuffle`    | `true` or `false`      |
+//! | `equalizer`  | `true` or `false`      |
+//! | `crossfeed`  | `true` or `false`      |
+//! | `night_mode` | `true` or `false`      |
+//!
+//! Retained commands are ignored, as they would be handled again on every reconnect.
+//!
+//! # Home Assistant
+//!
+//! Unless disabled, a discovery config is published, so that pleezer shows up as a
+//! `media_player` entity through the "MQTT Media Player" integration.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::mqtt::{Mqtt, Options};
+//!
+//! let mqtt = Mqtt::connect(options, client.control())?;
+//! // Publishes until dropped.
+//! ```
+
+use std::{collections::HashMap, time::Duration};
+
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, Publish, QoS};
+use tokio::task::JoinHandle;
+use url::Url;
+use veil::Redact;
+
+use crate::{
+    control::{Command, Handle, Status},
+    error::{Error, Result},
+    protocol::connect::{Percentage, RepeatMode},
+};
+
+/// Default port of MQTT brokers.
+pub const DEFAULT_PORT: u16 = 1883;
+
+/// Default prefix of Home Assistant discovery topics.
+pub const DEFAULT_DISCOVERY_PREFIX: &str = "homeassistant";
+
+/// Maximum number of requests waiting to be sent to the broker.
+const CAPACITY: usize = 64;
+
+/// Interval of keep-alive pings to the broker.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// Time to wait before reconnecting, after the connection failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Options to connect to the broker with.
+#[derive(Clone, Redact)]
+pub struct Options {
+    /// Address of the broker, like `mqtt://localhost:1883`
+    pub broker: Url,
+
+    /// Username to log in with, if any
+    pub username: Option<String>,
+
+    /// Password to log in with, if any
+    #[redact]
+    pub password: Option<String>,
+
+    /// Topic to publish state and receive commands below
+    pub topic: String,
+
+    /// Prefix of Home Assistant discovery topics, or `None` to disable discovery
+    pub discovery_prefix: Option<String>,
+
+    /// Unique identifier of this player, like its device ID
+    pub device_id: String,
+
+    /// Name of this player, as shown to users
+    pub device_name: String,
+}
+
+/// Bridge between the player and an MQTT broker.
+///
+/// Disconnects when dropped. Reconnects to the broker when the connection fails,
+/// republishing the state after reconnecting.
+#[derive(Debug)]
+pub struct Mqtt {
+    /// Task running the connection to the broker
+    task: JoinHandle<()>,
+}
+
+impl Mqtt {
+    /// Starts connecting to the broker in the background.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Broker to connect to and topics to use
+    /// * `control` - Handle to control the player with
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if the broker address is not an `mqtt://` URL with
+    /// a host, or the topic is empty.
+    pub fn connect(options: Options, control: Handle) -> Result<Self> {
+        if options.broker.scheme() != "mqtt" {
+            return Err(Error::invalid_argument(format!(
+                "invalid mqtt broker {}: expected mqtt://host:port",
+                options.broker
+            )));
+        }
+        let host = options
+            .broker
+            .host_str()
+            .ok_or_else(|| Error::invalid_argument("mqtt broker has no host"))?;
+        let port = options.broker.port().unwrap_or(DEFAULT_PORT);
+
+        let topic = options.topic.trim_end_matches('/').to_string();
+        if topic.is_empty() {
+            return Err(Error::invalid_argument("mqtt topic must not be empty"));
+        }
+
+        let mut mqtt_options =
+            MqttOptions::new(format!("pleezer-{}", options.device_id), host, port);
+        mqtt_options.set_keep_alive(KEEP_ALIVE);
+        mqtt_options.set_last_will(LastWill::new(
+            format!("{topic}/availability"),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some(username) = options.username.as_ref() {
+            mqtt_options.set_credentials(username, options.password.as_deref().unwrap_or_default());
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(mqtt_options, CAPACITY);
+        let mut bridge = Bridge {
+            client,
+            control,
+            topic,
+            discovery_prefix: options.discovery_prefix,
+            device_id: options.device_id,
+            device_name: options.device_name,
+            connected: false,
+            published: HashMap::new(),
+        };
+        info!("publishing to mqtt broker {host}:{port}");
+
+        let task = tokio::spawn(async move {
+            let mut status = bridge.control.watch();
+            loop {
+                tokio::select! {
+                    event = eventloop.poll() => match event {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            info!("connected to mqtt broker");
+                            bridge.on_connect(&status.borrow_and_update());
+                        }
+                        Ok(Event::Incoming(Packet::Publish(publish))) => {
+                            bridge.handle_publish(&publish);
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            if bridge.connected {
+                                error!("mqtt connection failed: {e}");
+                            } else {
+                                debug!("mqtt connection failed: {e}");
+                            }
+                            bridge.connected = false;
+                            tokio::time::sleep(RECONNECT_DELAY).await;
+                        }
+                    },
+
+                    Ok(()) = status.changed() => {
+                        bridge.publish_status(&status.borrow_and_update());
+                    }
+                }
+            }
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for Mqtt {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// State of the bridge, owned by its task.
+struct Bridge {
+    /// Client to send requests to the broker with
+    client: AsyncClient,
+
+    /// Handle to control the player with
+    control: Handle,
+
+    /// Topic to publish state and receive commands below
+    topic: String,
+
+    /// Prefix of Home Assistant discovery topics, if enabled
+    discovery_prefix: Option<String>,
+
+    /// Unique identifier of this player
+    device_id: String,
+
+    /// Name of this player
+    device_name: String,
+
+    /// Whether the broker acknowledged the connection
+    connected: bool,
+
+    /// Payloads last published per state topic, to only publish changes
+    published: HashMap<&'static str, String>,
+}
+
+impl Bridge {
+    /// Subscribes to commands and publishes the state, after (re)connecting.
+    fn on_connect(&mut self, status: &Status) {
+        self.connected = true;
+        self.published.clear();
+
+        if let Err(e) = self
+            .client
+            .try_subscribe(format!("{}/command/+", self.topic), QoS::AtLeastOnce)
+        {
+            error!("failed to subscribe to mqtt commands: {e}");
+        }
+
+        if let Some(prefix) = self.discovery_prefix.as_deref() {
+            let topic = format!("{prefix}/media_player/{}/config", self.device_id);
+            self.publish(topic, self.discovery().to_string());
+        }
+
+        self.publish(format!("{}/availability", self.topic), "online");
+        self.publish_status(status);
+    }
+
+    /// Publishes the state topics that changed.
+    fn publish_status(&mut self, status: &Status) {
+        if !self.connected {
+            return;
+        }
+
+        let track = status.track.as_ref();
+        let state = match (track.is_some(), status.playing) {
+            (false, _) => "idle",
+            (true, true) => "playing",
+            (true, false) => "paused",
+        };
+
+        let mut payloads = vec![
+            ("state", state.to_string()),
+            (
+                "title",
+                track.and_then(|t| t.title.clone()).unwrap_or_default(),
+            ),
+            (
+                "artist",
+                track.map(|t| t.artist.clone()).unwrap_or_default(),
+            ),
+            (
+                "album",
+                track.and_then(|t| t.album.clone()).unwrap_or_default(),
+            ),
+            ("type", track.map(|t| t.typ.to_string()).unwrap_or_default()),
+            (
+                "cover_url",
+                track.and_then(|t| t.cover_url.clone()).unwrap_or_default(),
+            ),
+            (
+                "duration",
+                track
+                    .and_then(|t| t.duration)
+                    .map(|duration| duration.as_secs().to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "position",
+                status
+                    .position
+                    .map(|position| position.as_secs().to_string())
+                    .unwrap_or_default(),
+            ),
+            ("volume", (status.volume / 100.0).to_string()),
+            ("repeat", status.repeat.clone()),
+            ("shuffle", status.shuffle.to_string()),
+            ("equalizer", status.equalizer.to_string()),
+            ("crossfeed", status.crossfeed.to_string()),
+            ("night_mode", status.night_mode.to_string()),
+            ("controller", status.controller.clone().unwrap_or_default()),
+            (
+                "format",
+                track.map(|t| t.format.clone()).unwrap_or_default(),
+            ),
+            (
+                "decoder",
+                track.map(|t| t.decoder.clone()).unwrap_or_default(),
+            ),
+        ];
+
+        match serde_json::to_string(status) {
+            Ok(json) => payloads.push(("status", json)),
+            Err(e) => error!("failed to serialize status: {e}"),
+        }
+
+        for (name, payload) in payloads {
+            if self.published.get(name) != Some(&payload) {
+                self.publish(format!("{}/{name}", self.topic), payload.clone());
+                self.published.insert(name, payload);
+            }
+        }
+    }
+
+    /// Publishes a retained message.
+    ///
+    /// Does not wait, so that the connection keeps being served: messages are
+    /// dropped when too many are waiting to be sent.
+    fn publish(&self, topic: String, payload: impl Into<Vec<u8>>) {
+        if let Err(e) = self
+            .client
+            .try_publish(topic, QoS::AtLeastOnce, true, payload)
+        {
+            warn!("failed to publish to mqtt: {e}");
+        }
+    }
+
+    /// Handles a message on a command topic.
+    ///
+    /// Sends the command in the background, so that the connection keeps being served
+    /// while the player handles it.
+    fn handle_publish(&self, publish: &Publish) {
+        let Some(name) = publish
+            .topic
+            .strip_prefix(&self.topic)
+            .and_then(|topic| topic.strip_prefix("/command/"))
+        else {
+            return;
+        };
+
+        if publish.retain {
+            warn!("ignoring retained mqtt command {name}");
+            return;
+        }
+
+        let payload = String::from_utf8_lossy(&publish.payload);
+        let playing = self.control.watch().borrow().playing;
+        let command = match parse_command(name, payload.trim(), playing) {
+            Ok(command) => command,
+            Err(e) => {
+                warn!("ignoring mqtt command {name}: {e}");
+                return;
+            }
+        };
+
+        let control = self.control.clone();
+        tokio::spawn(async move {
+            if let Err(e) = control.send(command).await {
+                warn!("mqtt command {command} failed: {e}");
+            }
+        });
+    }
+
+    /// Returns the Home Assistant discovery config for a `media_player` entity.
+    fn discovery(&self) -> serde_json::Value {
+        let topic = &self.topic;
+        serde_json::json!({
+            "name": self.device_name,
+            "unique_id": format!("pleezer_{}", self.device_id),
+            "device": {
+                "identifiers": [format!("pleezer_{}", self.device_id)],
+                "name": self.device_name,
+                "manufacturer": "pleezer",
+                "model": "Deezer Connect receiver",
+                "sw_version": env!("CARGO_PKG_VERSION"),
+            },
+            "availability_topic": format!("{topic}/availability"),
+            "state_state_topic": format!("{topic}/state"),
+            "state_title_topic": format!("{topic}/title"),
+            "state_artist_topic": format!("{topic}/artist"),
+            "state_album_topic": format!("{topic}/album"),
+            "state_duration_topic": format!("{topic}/duration"),
+            "state_position_topic": format!("{topic}/position"),
+            "state_volume_topic": format!("{topic}/volume"),
+            "command_volume_topic": format!("{topic}/command/volume"),
+            "command_play_topic": format!("{topic}/command/play"),
+            "command_play_payload": "",
+            "command_pause_topic": format!("{topic}/command/pause"),
+            "command_pause_payload": "",
+            "command_playpause_topic": format!("{topic}/command/playpause"),
+            "command_playpause_payload": "",
+            "command_next_topic": format!("{topic}/command/next"),
+            "command_next_payload": "",
+            "command_previous_topic": format!("{topic}/command/previous"),
+            "command_previous_payload": "",
+        })
+    }
+}
+
+/// Parses the command of a command topic.
+///
+/// Toggles between playing and pausing for `playpause`, depending on whether the
+/// player is playing.
+fn parse_command(name: &str, payload: &str, playing: bool) -> Result<Command> {
+    let command = match name {
+        "play" => Command::Play,
+        "pause" => Command::Pause,
+        "playpause" if playing => Command::Pause,
+        "playpause" => Command::Play,
+        "next" => Command::Next,
+        "previous" => Command::Previous,
+        "seek" => {
+            let position: f64 = payload
+                .parse()
+                .map_err(|e| Error::invalid_argument(format!("invalid position {payload}: {e}")))?;
+            let position = Duration::try_from_secs_f64(position)
+                .map_err(|e| Error::invalid_argument(format!("invalid position {payload}: {e}")))?;
+            Command::Seek(position)
+        }
+        "volume" => {
+            let volume: f32 = payload
+                .parse()
+                .map_err(|e| Error::invalid_argument(format!("invalid volume {payload}: {e}")))?;
+            if !(0.0..=1.0).contains(&volume) {
+                return Err(Error::invalid_argument(format!(
+                    "invalid volume {payload}: expected 0.0 to 1.0"
+                )));
+            }
+            Command::Volume(Percentage::from_ratio(volume))
+        }
+        "repeat" => match payload.parse() {
+            Ok(RepeatMode::Unrecognized) | Err(_) => {
+                return Err(Error::invalid_argument(format!(
+                    "invalid repeat mode {payload}: expected none, all or one"
+                )));
+            }
+            Ok(repeat_mode) => Command::Repeat(repeat_mode),
+        },
+        "shuffle" => Command::Shuffle(parse_switch(name, payload)?),
+        "equalizer" => Command::Equalizer(parse_switch(name, payload)?),
+        "crossfeed" => Command::Crossfeed(parse_switch(name, payload)?),
+        "night_mode" => Command::NightMode(parse_switch(name, payload)?),
+        _ => return Err(Error::not_found(format!("no command {name}"))),
+    };
+
+    Ok(command)
+}
+
+/// Parses the payload of a command that switches something on or off.
+fn parse_switch(name: &str, payload: &str) -> Result<bool> {
+    payload.parse().map_err(|_| {
+        Error::invalid_argument(format!("invalid {name} {payload}: expected true or false"))
+    })
+}
@@ -24,6 +24,16 @@
 //! 5. Maximum peak detection across channels (specialized per channel count)
 //! 6. Gain reduction application (coupled across channels)
 //!
+//! The player limits with the true-peak [`limiter`](crate::limiter) instead, which
+//! also catches the peaks between samples. This limiter remains for uses where the
+//! look-ahead delay is not wanted.
+//!
+//! # Normalization Modes
+//!
+//! The gain applied before limiting is chosen by the player per [`Mode`]: from the
+//! loudness of each track, or from the combined loudness of all tracks of an album, so
+//! that quiet and loud songs keep their intended contrast.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -41,11 +51,57 @@
 //! );
 //! ```
 
-use std::time::Duration;
+use std::{fmt, str::FromStr, time::Duration};
 
 use rodio::{ChannelCount, Sample, SampleRate, Source, source::SeekError};
 
-use crate::util::{self, ToF32, ZERO_DB};
+use crate::{
+    error::{Error, Result},
+    util::{self, ToF32, ZERO_DB},
+};
+
+/// How the normalization gain is determined.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Mode {
+    /// Normalize each track by its own loudness.
+    #[default]
+    Track,
+
+    /// Normalize all tracks of an album by the loudness of the album.
+    ///
+    /// Applies to tracks queued from an album, and to tracks of the same album
+    /// elsewhere in the queue.
+    Album,
+
+    /// Normalize by album when consecutive tracks are from the same album, like when
+    /// playing an album in order, and by track otherwise.
+    Auto,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Track => write!(f, "track"),
+            Self::Album => write!(f, "album"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "track" => Ok(Self::Track),
+            "album" => Ok(Self::Album),
+            "auto" => Ok(Self::Auto),
+            _ => Err(Error::invalid_argument(format!(
+                "invalid normalization mode {s}"
+            ))),
+        }
+    }
+}
 
 // TODO: Remove when https://github.com/rust-lang/rust-clippy/issues/14275 is fixed
 #[expect(clippy::doc_overindented_list_items)]
@@ -137,7 +193,7 @@ where
 ///
 /// Smoothing coefficient in the range [0.0, 1.0]
 #[must_use]
-fn duration_to_coefficient(duration: Duration, sample_rate: SampleRate) -> f32 {
+pub(crate) fn duration_to_coefficient(duration: Duration, sample_rate: SampleRate) -> f32 {
     f32::exp(-1.0 / (duration.as_secs_f32() * sample_rate.to_f32_lossy()))
 }
 
@@ -248,7 +304,12 @@ pub struct NormalizeMulti<I> {
 ///
 /// Amount of gain reduction to apply in dB
 #[inline]
-fn process_sample(sample: Sample, threshold: f32, knee_width: f32, inv_knee_8: f32) -> f32 {
+pub(crate) fn process_sample(
+    sample: Sample,
+    threshold: f32,
+    knee_width: f32,
+    inv_knee_8: f32,
+) -> f32 {
     // Add slight DC offset. Some samples are silence, which is -inf dB and gets the limiter stuck.
     // Adding a small positive offset prevents this.
     let bias_db = util::ratio_to_db(sample.abs() + f32::MIN_POSITIVE) - threshold;
@@ -298,16 +359,39 @@ impl NormalizeBase {
         let limiter_db = process_sample(sample, self.threshold, self.knee_width, self.inv_knee_8);
 
         // step 5: smooth, decoupled peak detector
-        *integrator = f32::max(
-            limiter_db,
-            self.release * *integrator + (1.0 - self.release) * limiter_db,
-        );
-        *peak = self.attack * *peak + (1.0 - self.attack) * *integrator;
+        detect_peak(limiter_db, integrator, peak, self.attack, self.release);
 
         sample
     }
 }
 
+/// Smooths gain reduction through a decoupled peak detector.
+///
+/// The integrator follows increases immediately and decays with the release time; the
+/// peak follows the integrator with the attack time.
+///
+/// # Arguments
+///
+/// * `reduction_db` - Gain reduction computed for the current sample (dB)
+/// * `integrator` - Integrator state of the channel
+/// * `peak` - Peak state of the channel, the smoothed gain reduction (dB)
+/// * `attack` - Attack coefficient from [`duration_to_coefficient`]
+/// * `release` - Release coefficient from [`duration_to_coefficient`]
+#[inline]
+pub(crate) fn detect_peak(
+    reduction_db: f32,
+    integrator: &mut f32,
+    peak: &mut f32,
+    attack: f32,
+    release: f32,
+) {
+    *integrator = f32::max(
+        reduction_db,
+        release * *integrator + (1.0 - release) * reduction_db,
+    );
+    *peak = attack * *peak + (1.0 - attack) * *integrator;
+}
+
 impl<I> NormalizeMono<I>
 where
     I: Source,
@@ -520,7 +604,7 @@ where
     /// # Errors
     ///
     /// Returns error if the underlying source fails to seek
-    fn try_seek(&mut self, target: Duration) -> Result<(), SeekError> {
+    fn try_seek(&mut self, target: Duration) -> std::result::Result<(), SeekError> {
         self.inner_mut().try_seek(target)?;
 
         match self {
@@ -0,0 +1,334 @@
+//! Pluggable audio output.
+//!
+//! This module lets applications provide their own audio output through the
+//! [`AudioSink`] trait, instead of an audio device. Custom outputs receive fully
+//! processed samples, after normalization, equal-loudness compensation, volume
+//! control and dithering. Examples are network streamers, DSP hardware, or
+//! capture buffers that tests can assert on without a sound card.
+//!
+//! # Rendering
+//!
+//! The player drives a sink through an [`Output`], which:
+//! * Owns the mixer that the player's queue plays into
+//! * Runs a render thread that pulls blocks of samples from the mixer
+//! * Writes each block to the sink
+//! * Stops rendering while playback is paused
+//!
+//! Sinks may block in [`AudioSink::write`] to apply back pressure. Sinks that
+//! do not block render faster than real time.
+//!
+//! # Position Reporting
+//!
+//! Playback position is derived from the samples delivered to the sink, minus
+//! the latency that the sink reports. Underruns reported by the sink are made
+//! available through the player for diagnostics.
+//!
+//! # Example
+//!
+//! ```rust
+//! use pleezer::{
+//!     error::Result,
+//!     output::{AudioSink, Format},
+//!     player::Player,
+//! };
+//!
+//! struct Capture {
+//!     samples: Vec<f32>,
+//! }
+//!
+//! impl AudioSink for Capture {
+//!     fn format(&self) -> Format {
+//!         Format::default()
+//!     }
+//!
+//!     fn write(&mut self, samples: &[f32]) -> Result<()> {
+//!         self.samples.extend_from_slice(samples);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let player = Player::with_sink(&config, Capture { samples: Vec::new() }).await?;
+//! ```
+
+use std::{
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use rodio::{
+    ChannelCount, Sample, SampleRate, Source,
+    mixer::{self, Mixer, MixerSource},
+};
+
+use crate::{error::Result, track::DEFAULT_SAMPLE_RATE};
+
+/// Sample layout that an audio sink accepts.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Format {
+    /// Number of interleaved channels.
+    pub channels: ChannelCount,
+
+    /// Sample rate in Hz.
+    pub sample_rate: SampleRate,
+
+    /// Sample format that the sink converts to.
+    ///
+    /// Samples are always delivered as `f32`. This determines the bit depth
+    /// that the player dithers to; floating point formats are not dithered.
+    pub sample_format: cpal::SampleFormat,
+}
+
+/// Stereo at 44.1 kHz in 32-bit floating point.
+impl Default for Format {
+    fn default() -> Self {
+        Self {
+            channels: 2,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            sample_format: cpal::SampleFormat::F32,
+        }
+    }
+}
+
+/// Audio output that receives processed samples.
+///
+/// Implementations are called from the render thread. Apart from
+/// [`write`](Self::write), all methods have default implementations.
+pub trait AudioSink: Send {
+    /// Returns the sample layout that this sink accepts.
+    ///
+    /// Queried when the output is opened. Audio is resampled and channel
+    /// mapped to this format before delivery.
+    fn format(&self) -> Format;
+
+    /// Writes a block of interleaved samples.
+    ///
+    /// Blocks always contain whole frames. Implementations may block to pace
+    /// the output.
+    ///
+    /// # Errors
+    ///
+    /// Returning an error stops the render thread. The player then reopens the
+    /// output with backoff, and calls `write` again on this same sink.
+    fn write(&mut self, samples: &[Sample]) -> Result<()>;
+
+    /// Called when playback resumes after a pause.
+    ///
+    /// # Errors
+    ///
+    /// Errors are logged and do not stop rendering.
+    fn play(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when playback pauses. No samples are written until `play`.
+    ///
+    /// # Errors
+    ///
+    /// Errors are logged and do not stop rendering.
+    fn pause(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the duration of audio that was written but is not yet audible.
+    fn latency(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Returns the number of underruns since the sink was created.
+    fn underruns(&self) -> u64 {
+        0
+    }
+}
+
+/// State shared between an output and its render thread.
+#[derive(Debug)]
+struct State {
+    /// Whether the render thread should keep running.
+    running: AtomicBool,
+
+    /// Whether rendering is paused.
+    paused: AtomicBool,
+
+    /// Last reported sink latency in nanoseconds.
+    latency: AtomicU64,
+
+    /// Last reported number of sink underruns.
+    underruns: AtomicU64,
+
+    /// Whether the render thread stopped because the sink failed.
+    failed: AtomicBool,
+}
+
+/// Renders audio from a mixer into an audio sink.
+///
+/// The render thread is signalled to stop when the output is dropped. The sink
+/// itself is shared, so that it can be reopened after the output was dropped.
+pub struct Output {
+    /// Mixer that the player's sink connects to.
+    mixer: Mixer,
+
+    /// Sample layout of the audio sink.
+    format: Format,
+
+    /// State shared with the render thread.
+    state: Arc<State>,
+}
+
+impl Output {
+    /// Duration of audio that is rendered and written per block.
+    ///
+    /// Short enough to keep position reporting accurate, long enough to keep
+    /// locking and system call overhead low.
+    pub const BLOCK_DURATION: Duration = Duration::from_millis(10);
+
+    /// Opens the output and starts the render thread.
+    ///
+    /// Rendering starts paused.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the render thread cannot be spawned.
+    pub fn open(sink: Arc<Mutex<dyn AudioSink>>) -> Result<Self> {
+        let format = sink.lock().unwrap_or_else(PoisonError::into_inner).format();
+        let (mixer, source) = mixer::mixer(format.channels, format.sample_rate);
+
+        let state = Arc::new(State {
+            running: AtomicBool::new(true),
+            paused: AtomicBool::new(true),
+            latency: AtomicU64::new(0),
+            underruns: AtomicU64::new(0),
+            failed: AtomicBool::new(false),
+        });
+
+        let thread_state = Arc::clone(&state);
+        std::thread::Builder::new()
+            .name("audio output".to_string())
+            .spawn(move || render(source, &sink, &thread_state))?;
+
+        Ok(Self {
+            mixer,
+            format,
+            state,
+        })
+    }
+
+    /// Returns the mixer that sinks connect to.
+    #[must_use]
+    #[inline]
+    pub fn mixer(&self) -> &Mixer {
+        &self.mixer
+    }
+
+    /// Returns the sample layout of the audio sink.
+    #[must_use]
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Pauses or resumes rendering.
+    #[inline]
+    pub fn set_paused(&self, paused: bool) {
+        self.state.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Returns the last latency reported by the audio sink.
+    #[must_use]
+    #[inline]
+    pub fn latency(&self) -> Duration {
+        Duration::from_nanos(self.state.latency.load(Ordering::Relaxed))
+    }
+
+    /// Returns the last number of underruns reported by the audio sink.
+    #[must_use]
+    #[inline]
+    pub fn underruns(&self) -> u64 {
+        self.state.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether rendering stopped because the audio sink failed.
+    ///
+    /// A failed output must be reopened to resume rendering.
+    #[must_use]
+    #[inline]
+    pub fn is_failed(&self) -> bool {
+        self.state.failed.load(Ordering::Relaxed)
+    }
+}
+
+/// Signals the render thread to stop.
+///
+/// The thread is not joined: it may be blocked in the audio sink, and will
+/// exit after its current write.
+impl Drop for Output {
+    fn drop(&mut self) {
+        self.state.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Pulls samples from the mixer and writes them to the sink until signalled to stop.
+fn render(mut source: MixerSource, sink: &Mutex<dyn AudioSink>, state: &State) {
+    let channels = usize::from(source.channels()).max(1);
+    let frames_per_block = usize::try_from(
+        u64::from(source.sample_rate())
+            * u64::try_from(Output::BLOCK_DURATION.as_millis()).unwrap_or(u64::MAX)
+            / 1000,
+    )
+    .unwrap_or(1)
+    .max(1);
+
+    let mut block = Vec::with_capacity(frames_per_block * channels);
+    let mut paused = false;
+
+    while state.running.load(Ordering::Relaxed) {
+        let should_pause = state.paused.load(Ordering::Relaxed);
+        if should_pause != paused {
+            let mut sink = sink.lock().unwrap_or_else(PoisonError::into_inner);
+            let result = if should_pause {
+                sink.pause()
+            } else {
+                sink.play()
+            };
+            if let Err(e) = result {
+                error!("audio output failed to change playback state: {e}");
+            }
+            paused = should_pause;
+        }
+
+        block.clear();
+        if !paused {
+            block.extend(source.by_ref().take(frames_per_block * channels));
+        }
+
+        if block.is_empty() {
+            // Paused or nothing connected to the mixer: idle without writing.
+            std::thread::sleep(Output::BLOCK_DURATION);
+            continue;
+        }
+
+        // Keep channels aligned when the mixer ran dry mid-frame.
+        block.resize(block.len().next_multiple_of(channels), 0.0);
+
+        let mut sink = sink.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // The output may have been dropped and reopened while waiting for the lock.
+        if !state.running.load(Ordering::Relaxed) {
+            break;
+        }
+
+        if let Err(e) = sink.write(&block) {
+            error!("audio output failed: {e}");
+            state.failed.store(true, Ordering::Relaxed);
+            break;
+        }
+
+        let latency = u64::try_from(sink.latency().as_nanos()).unwrap_or(u64::MAX);
+        state.latency.store(latency, Ordering::Relaxed);
+        state.underruns.store(sink.underruns(), Ordering::Relaxed);
+    }
+
+    debug!("stopped audio output");
+}
@@ -0,0 +1,404 @@
+//! Raw PCM output to named pipes and files.
+//!
+//! This module provides an alternative to audio devices for setups without a
+//! sound card, or that feed multiroom servers like Snapcast. Audio is written as
+//! raw interleaved PCM in little-endian byte order, after the complete processing
+//! chain, so output is already volume-controlled and dithered.
+//!
+//! The pipe is an [`AudioSink`], rendered by the [`output`](crate::output) module.
+//!
+//! # Device Specification
+//!
+//! Selected through the device string instead of an audio host and device:
+//! ```text
+//! pipe:<path>[|<sample rate>][|<sample format>]
+//! file:<path>[|<sample rate>][|<sample format>]
+//! ```
+//!
+//! * `pipe:` writes to an existing named pipe (FIFO):
+//!   - Writes block while the reader is not consuming, so the reader sets the pace
+//!   - When the reader goes away, the pipe is reopened for the next reader
+//! * `file:` creates or truncates a regular file:
+//!   - Use `-` as path to write to standard output
+//!   - Output is paced to real time
+//!
+//! Sample rate defaults to 44.1 kHz and sample format to S16. Supported sample
+//! formats are S16, S32 and F32. Output is always stereo.
+//!
+//! # Example
+//!
+//! Snapcast server configuration matching the default output format:
+//! ```text
+//! [stream]
+//! source = pipe:///tmp/snapfifo?name=pleezer&sampleformat=44100:16:2
+//! ```
+//!
+//! With pleezer started as:
+//! ```text
+//! pleezer --device "pipe:/tmp/snapfifo"
+//! ```
+
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use cpal::FromSample;
+use rodio::{ChannelCount, Sample, SampleRate};
+
+use crate::{
+    error::{Error, Result},
+    output::{AudioSink, Format, Output},
+    track::DEFAULT_SAMPLE_RATE,
+};
+
+/// Destination of the raw PCM output.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Target {
+    /// Existing named pipe (FIFO) that is reopened when the reader goes away.
+    Pipe(PathBuf),
+    /// Regular file that is created or truncated.
+    File(PathBuf),
+    /// Standard output of the process.
+    Stdout,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pipe(path) => write!(f, "pipe {}", path.display()),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Stdout => write!(f, "standard output"),
+        }
+    }
+}
+
+/// Parsed pipe or file output specification.
+///
+/// Parsed from device strings in the format:
+/// ```text
+/// pipe:<path>[|<sample rate>][|<sample format>]
+/// file:<path>[|<sample rate>][|<sample format>]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Spec {
+    /// Where to write the output to.
+    pub target: Target,
+
+    /// Output sample rate in Hz.
+    pub sample_rate: SampleRate,
+
+    /// Output sample format.
+    pub sample_format: cpal::SampleFormat,
+}
+
+impl Spec {
+    /// Prefix for named pipe outputs.
+    const PIPE_PREFIX: &'static str = "pipe:";
+
+    /// Prefix for file outputs.
+    const FILE_PREFIX: &'static str = "file:";
+
+    /// Path that selects standard output for file outputs.
+    const STDOUT_PATH: &'static str = "-";
+
+    /// Number of output channels.
+    pub const CHANNELS: ChannelCount = 2;
+
+    /// Default output sample format.
+    ///
+    /// 16-bit signed integer is the most widely supported raw PCM format, and
+    /// the default of Snapcast.
+    pub const DEFAULT_SAMPLE_FORMAT: cpal::SampleFormat = cpal::SampleFormat::I16;
+
+    /// Returns whether a device string specifies a pipe or file output.
+    ///
+    /// The prefix is matched case-insensitively, consistent with how audio hosts
+    /// and devices are matched.
+    #[must_use]
+    pub fn matches(device: &str) -> bool {
+        [Self::PIPE_PREFIX, Self::FILE_PREFIX].iter().any(|prefix| {
+            device
+                .get(..prefix.len())
+                .is_some_and(|s| s.eq_ignore_ascii_case(prefix))
+        })
+    }
+}
+
+impl FromStr for Spec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if !Self::matches(s) {
+            return Err(Error::invalid_argument(format!(
+                "{s} is not a pipe or file output"
+            )));
+        }
+
+        // Both prefixes are of equal length.
+        let (prefix, rest) = s.split_at(Self::PIPE_PREFIX.len());
+        let mut components = rest.split('|');
+
+        let target = match components.next() {
+            Some("") | None => {
+                return Err(Error::invalid_argument(format!("{s} has no path")));
+            }
+            Some(path) => {
+                if prefix.eq_ignore_ascii_case(Self::PIPE_PREFIX) {
+                    Target::Pipe(path.into())
+                } else if path == Self::STDOUT_PATH {
+                    Target::Stdout
+                } else {
+                    Target::File(path.into())
+                }
+            }
+        };
+
+        let sample_rate = match components.next() {
+            Some("") | None => DEFAULT_SAMPLE_RATE,
+            Some(rate) => match rate.parse() {
+                Ok(rate) if rate > 0 => rate,
+                _ => {
+                    return Err(Error::invalid_argument(format!(
+                        "invalid sample rate {rate}"
+                    )));
+                }
+            },
+        };
+
+        // Accept input like `S16` for consistency with audio device specifications.
+        let sample_format = match components
+            .next()
+            .map(|fmt| fmt.to_lowercase().replace('s', "i"))
+        {
+            None => Self::DEFAULT_SAMPLE_FORMAT,
+            Some(fmt) => match fmt.as_str() {
+                "" => Self::DEFAULT_SAMPLE_FORMAT,
+                "i16" => cpal::SampleFormat::I16,
+                "i32" => cpal::SampleFormat::I32,
+                "f32" => cpal::SampleFormat::F32,
+                _ => {
+                    return Err(Error::unimplemented(format!(
+                        "sample format {fmt} is not supported for {target}"
+                    )));
+                }
+            },
+        };
+
+        if components.next().is_some() {
+            return Err(Error::invalid_argument(format!(
+                "{s} has too many components"
+            )));
+        }
+
+        Ok(Self {
+            target,
+            sample_rate,
+            sample_format,
+        })
+    }
+}
+
+/// Raw PCM output to a named pipe, file or standard output.
+///
+/// Implements [`AudioSink`], so the player renders into it like into any
+/// other custom output.
+pub struct Pipe {
+    /// Output specification this pipe was opened with.
+    spec: Spec,
+
+    /// Open handle to the target, if any.
+    output: Option<Box<dyn Write + Send>>,
+
+    /// Reusable buffer for converted samples.
+    buffer: Vec<u8>,
+
+    /// When pacing started, for paced targets.
+    /// Reset when playback resumes and after underruns.
+    started: Option<Instant>,
+
+    /// Number of frames written since pacing started.
+    frames_written: u64,
+
+    /// Number of times that writes fell behind real time.
+    underruns: u64,
+}
+
+impl Pipe {
+    /// Opens the output.
+    ///
+    /// Files and standard output are opened immediately. Named pipes are opened
+    /// on the first write, because opening a pipe blocks until a reader
+    /// connects.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// * File cannot be created
+    /// * Named pipe does not exist
+    pub fn open(spec: Spec) -> Result<Self> {
+        let mut pipe = Self {
+            spec,
+            output: None,
+            buffer: Vec::new(),
+            started: None,
+            frames_written: 0,
+            underruns: 0,
+        };
+
+        match &pipe.spec.target {
+            Target::Pipe(path) => {
+                if !path.exists() {
+                    return Err(Error::not_found(format!(
+                        "named pipe {} not found",
+                        path.display()
+                    )));
+                }
+            }
+            Target::File(_) | Target::Stdout => pipe.open_target()?,
+        }
+
+        info!("audio output: {}", pipe.spec.target);
+
+        #[expect(clippy::cast_precision_loss)]
+        let sample_rate = pipe.spec.sample_rate as f32 / 1000.0;
+        info!(
+            "audio output configuration: {sample_rate:.1} kHz in {}",
+            pipe.spec.sample_format
+        );
+
+        Ok(pipe)
+    }
+
+    /// Returns the output specification.
+    #[must_use]
+    #[inline]
+    pub fn spec(&self) -> &Spec {
+        &self.spec
+    }
+
+    /// Returns whether writes are throttled to real time.
+    ///
+    /// Named pipes are paced by their reader through back pressure. Files and
+    /// standard output would otherwise be written as fast as possible.
+    #[must_use]
+    #[inline]
+    fn is_paced(&self) -> bool {
+        !matches!(self.spec.target, Target::Pipe(_))
+    }
+
+    /// Opens the target for writing.
+    ///
+    /// For named pipes, this blocks until a reader connects.
+    fn open_target(&mut self) -> io::Result<()> {
+        let output: Box<dyn Write + Send> = match &self.spec.target {
+            Target::Pipe(path) => Box::new(OpenOptions::new().write(true).open(path)?),
+            Target::File(path) => Box::new(File::create(path)?),
+            Target::Stdout => Box::new(io::stdout()),
+        };
+
+        self.output = Some(output);
+        Ok(())
+    }
+
+    /// Sleeps until real time catches up with the frames written.
+    ///
+    /// Counts an underrun when writes fell behind by more than a block, and
+    /// restarts pacing from there instead of trying to catch up.
+    fn pace(&mut self, frames: u64) {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        self.frames_written = self.frames_written.saturating_add(frames);
+
+        let sample_rate = u64::from(self.spec.sample_rate);
+        let due = Duration::from_secs(self.frames_written / sample_rate)
+            + Duration::from_nanos(self.frames_written % sample_rate * 1_000_000_000 / sample_rate);
+
+        let elapsed = started.elapsed();
+        if let Some(ahead) = due.checked_sub(elapsed) {
+            std::thread::sleep(ahead);
+        } else if elapsed - due > Output::BLOCK_DURATION {
+            self.underruns = self.underruns.saturating_add(1);
+            self.started = None;
+            self.frames_written = 0;
+        }
+    }
+}
+
+impl AudioSink for Pipe {
+    fn format(&self) -> Format {
+        Format {
+            channels: Spec::CHANNELS,
+            sample_rate: self.spec.sample_rate,
+            sample_format: self.spec.sample_format,
+        }
+    }
+
+    /// Converts and writes interleaved samples.
+    ///
+    /// Opens the target first if it is not open. When the reader of a named
+    /// pipe goes away, the block is dropped and the pipe is reopened on the
+    /// next write.
+    fn write(&mut self, samples: &[Sample]) -> Result<()> {
+        if self.output.is_none() {
+            self.open_target()?;
+        }
+
+        self.buffer.clear();
+        for &sample in samples {
+            match self.spec.sample_format {
+                cpal::SampleFormat::I16 => self
+                    .buffer
+                    .extend_from_slice(&i16::from_sample_(sample).to_le_bytes()),
+                cpal::SampleFormat::I32 => self
+                    .buffer
+                    .extend_from_slice(&i32::from_sample_(sample).to_le_bytes()),
+                _ => self.buffer.extend_from_slice(&sample.to_le_bytes()),
+            }
+        }
+
+        if let Some(output) = self.output.as_mut() {
+            if let Err(e) = output.write_all(&self.buffer) {
+                if matches!(self.spec.target, Target::Pipe(_))
+                    && e.kind() == io::ErrorKind::BrokenPipe
+                {
+                    warn!(
+                        "reader of {} went away, waiting for reconnect",
+                        self.spec.target
+                    );
+                    self.output = None;
+                } else {
+                    return Err(e.into());
+                }
+            }
+        }
+
+        if self.is_paced() {
+            let frames = samples.len() / usize::from(Spec::CHANNELS);
+            self.pace(u64::try_from(frames).unwrap_or(u64::MAX));
+        }
+
+        Ok(())
+    }
+
+    fn play(&mut self) -> Result<()> {
+        // Do not count the pause as falling behind.
+        self.started = None;
+        self.frames_written = 0;
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<()> {
+        if let Some(output) = self.output.as_mut() {
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn underruns(&self) -> u64 {
+        self.underruns
+    }
+}
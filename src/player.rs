@@ -7,6 +7,7 @@
 //! * Volume normalization and control
 //!   - Primary: Uses Deezer-provided gain values
 //!   - Fallback: `ReplayGain` metadata from external files (e.g., podcasts)
+//!   - Livestreams: automatic gain control from their short-term loudness
 //!   - Target: -15 LUFS with headroom protection
 //!   - Dynamic range compression for loud content
 //! * Equal-loudness compensation (ISO 226:2013)
@@ -27,25 +28,41 @@
 //!    * FLAC: Raw frame handling
 //!    * AAC: ADTS stream parsing
 //!    * WAV: PCM decoding
-//! 3. Volume normalization (optional)
-//! 4. Equal-loudness compensation (ISO 226:2013)
-//! 5. Logarithmic volume control
-//! 6. Dithering and noise shaping:
+//! 3. Sample rate conversion to the output sample rate
+//! 4. Volume normalization with true-peak limiting (optional)
+//! 5. Night mode compression (optional)
+//! 6. Parametric equalizer with automatic headroom (optional)
+//! 7. FIR convolution for room correction (optional)
+//! 8. Headphone crossfeed for stereo content (optional)
+//! 9. Equal-loudness compensation (ISO 226:2013)
+//! 10. Logarithmic volume control
+//! 11. Dithering and noise shaping:
 //!    * TPDF dither with optimal noise characteristics
 //!    * Shibata noise shaping filters (when enabled)
 //!    * Automatic headroom management
-//! 7. Fade-out processing for smooth transitions
-//! 8. Audio device output
+//! 12. True-peak safety limiting (except bit-perfect)
+//! 13. Crossfade into the next track (optional)
+//! 14. Fade-out processing for smooth transitions
+//! 15. Audio device output, or raw PCM output to a pipe or file
 //!
 //! # Features
 //!
 //! * Unified audio stream handling
 //! * Optimized CBR MP3 seeking
 //! * Track preloading for gapless playback
-//! * Volume normalization with limiter
+//! * Crossfading between tracks, keeping albums gapless
+//! * Volume normalization with limiter, and automatic gain control for livestreams
+//! * Parametric equalizer, switchable at runtime
+//! * Room correction through FIR convolution
+//! * Headphone crossfeed
+//! * Night mode compression, switched by the time of day
 //! * High-quality dither and noise shaping
+//! * High-quality sample rate conversion, for mixed-rate queues
+//! * Bit-perfect playback at the native sample rate and bit depth of each track
 //! * Flexible audio device selection
 //! * Multiple audio host support
+//! * Pipe and file output for multiroom servers and headless setups
+//! * Custom outputs through the `AudioSink` trait
 //!
 //! # Example
 //!
@@ -70,24 +87,38 @@
 //! player.stop();
 //! ```
 
-use std::{collections::HashSet, f32, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    f32, fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use cpal::traits::{DeviceTrait, HostTrait};
 use md5::{Digest, Md5};
-use rodio::Source;
+use rodio::{SampleRate, Source};
 use stream_download::storage::{
     adaptive::AdaptiveStorageProvider, memory::MemoryStorageProvider, temp::TempStorageProvider,
 };
 use url::Url;
 
 use crate::{
+    agc,
+    bookmarks::{Bookmark, Bookmarks},
+    compressor,
     config::Config,
+    convolve, crossfade, crossfeed,
     decoder::Decoder,
     decrypt::{self},
-    dither,
+    dither, equalizer,
     error::{Error, ErrorKind, Result},
     events::Event,
-    http, normalize,
+    http, limiter, mixer, normalize,
+    output::{AudioSink, Output},
+    pipe::{self, Pipe},
     protocol::{
         connect::{
             Percentage,
@@ -95,9 +126,11 @@ use crate::{
         },
         gateway::{self, MediaUrl},
     },
-    track::{DEFAULT_BITS_PER_SAMPLE, DEFAULT_SAMPLE_RATE, Track, TrackId},
+    resample,
+    scan::{self, MirrorStorage},
+    track::{DEFAULT_BITS_PER_SAMPLE, Track, TrackId, TrackType},
     util::{self, ToF32, UNITY_GAIN},
-    volume::Volume,
+    volume::{self, Volume},
 };
 
 /// Audio sample type used by the decoder.
@@ -114,12 +147,13 @@ pub type SampleFormat = f32;
 /// * Queue management and ordering
 /// * Playback control
 /// * Audio parameters:
-///   - Sample rate (defaults to 44.1 kHz)
+///   - Sample rate (defaults to 44.1 kHz, converted to the output sample rate)
 ///   - Bits per sample (codec-dependent)
 ///   - Channel count (content-specific)
 /// * Volume normalization:
 ///   - Primarily uses Deezer-provided gain values
 ///   - Falls back to `ReplayGain` metadata for external content
+///   - Otherwise measures the loudness once downloaded
 ///   - Targets -15 LUFS with headroom protection
 ///   - Applies dynamic range compression when needed
 ///
@@ -128,6 +162,9 @@ pub type SampleFormat = f32;
 /// * Podcasts: MP3, AAC (ADTS), MP4, WAV (may contain `ReplayGain`)
 /// * Livestreams: AAC (ADTS) and MP3
 ///
+/// Audio output is either an audio device, a pipe or file, or a custom
+/// [`AudioSink`] provided through [`Player::with_sink`].
+///
 /// Audio device lifecycle:
 /// * Device specification is stored during construction
 /// * Device is opened automatically on first play
@@ -136,6 +173,7 @@ pub type SampleFormat = f32;
 /// * Device state affects method behavior:
 ///   - Most playback operations require an open device
 ///   - Configuration can be changed when device is closed
+#[expect(clippy::struct_excessive_bools)]
 pub struct Player {
     /// Preferred audio quality setting.
     ///
@@ -184,6 +222,14 @@ pub struct Player {
     /// Whether volume normalization is enabled.
     normalization: bool,
 
+    /// Whether to normalize by track or by album.
+    normalization_mode: normalize::Mode,
+
+    /// Whether to normalize podcast episodes with automatic gain control.
+    ///
+    /// Livestreams are always normalized this way, as they have no gain information.
+    normalize_episodes: bool,
+
     /// Whether equal-loudness compensation is enabled.
     ///
     /// When enabled, applies frequency-dependent gain based on
@@ -198,10 +244,19 @@ pub struct Player {
 
     /// Raw volume setting as a percentage (0.0 to 1.0).
     ///
-    /// This stores the user-set volume before logarithmic scaling is applied.
-    /// The actual output volume uses logarithmic scaling for better perceived control.
+    /// This stores the user-set volume before the volume curve is applied.
+    /// The actual output volume follows the curve for better perceived control.
     volume: Percentage,
 
+    /// Mapping from volume settings to output amplitudes.
+    volume_scale: volume::Scale,
+
+    /// Whether the output stays at full scale, leaving the volume to an external amplifier.
+    fixed_volume: bool,
+
+    /// Forwards volume changes to an external volume control, with a fixed output.
+    mixer: Option<mixer::Mixer>,
+
     /// Dithered volume control shared across all sources.
     ///
     /// Provides volume adjustment with dithering for improved audio quality.
@@ -213,6 +268,106 @@ pub struct Player {
     /// Noise shaping for dithering.
     noise_shaping: u8,
 
+    /// Quality preset for converting tracks to the output sample rate.
+    resample_quality: resample::Quality,
+
+    /// Whether bit-perfect mode is enabled.
+    ///
+    /// When enabled, the audio device is reopened at the native sample rate and
+    /// bit depth of each track, and processing is bypassed when it would be a no-op.
+    bit_perfect: bool,
+
+    /// Native sample rate and bit depth to open the audio device at.
+    ///
+    /// Set in bit-perfect mode from the last track that required reopening the device.
+    native_format: Option<(SampleRate, Option<u32>)>,
+
+    /// Bit depth that the open audio output carries without loss.
+    output_bits: u32,
+
+    /// Whether the current track plays without processing in bit-perfect mode.
+    ///
+    /// Volume and dither are bypassed separately, see [`Volume::is_bypassed`].
+    current_bit_perfect: bool,
+
+    /// Whether the preloaded track plays without processing in bit-perfect mode.
+    preload_bit_perfect: bool,
+
+    /// Duration of the crossfade between tracks. Zero disables crossfading.
+    crossfade: Duration,
+
+    /// Shape of the crossfade between tracks.
+    crossfade_curve: crossfade::Curve,
+
+    /// Link from the current track to the preloaded track that it crossfades into.
+    current_crossfade: Option<Arc<crossfade::Handle>>,
+
+    /// Link from the preloaded track to the track after it.
+    preload_crossfade: Option<Arc<crossfade::Handle>>,
+
+    /// Parametric equalizer settings, if configured.
+    equalizer: Option<equalizer::Settings>,
+
+    /// Whether the equalizer is switched on.
+    ///
+    /// Shared with the equalizer stage of each track, so switching applies immediately.
+    equalizer_enabled: Arc<AtomicBool>,
+
+    /// Impulse responses for room correction, if configured.
+    convolution: Option<convolve::Kernels>,
+
+    /// Delay that processing adds to the current track.
+    ///
+    /// Subtracted from the playback position, so progress matches what is heard.
+    current_latency: Duration,
+
+    /// Delay that processing adds to the preloaded track.
+    preload_latency: Duration,
+
+    /// Position where the current track started, when it was loaded at a deferred seek.
+    ///
+    /// The sink does not see seeks before a track is queued, so this is added to its
+    /// position.
+    current_offset: Duration,
+
+    /// Playback positions of podcast episodes.
+    bookmarks: Bookmarks,
+
+    /// Bookmarks of episodes that were replaced, still to be returned by
+    /// [`bookmark`](Self::bookmark).
+    outgoing_bookmarks: Vec<(TrackId, Bookmark)>,
+
+    /// Gain reduction of the limiters of the current track.
+    current_limiting: Limiting,
+
+    /// Gain reduction of the limiters of the preloaded track.
+    preload_limiting: Limiting,
+
+    /// Loudness scanner for tracks without gain information.
+    scanner: scan::Scanner,
+
+    /// Cut frequency and feed level of the headphone crossfeed.
+    crossfeed_preset: crossfeed::Preset,
+
+    /// Whether headphone crossfeed is switched on.
+    ///
+    /// Shared with the crossfeed stage of each stereo track.
+    crossfeed_enabled: Arc<AtomicBool>,
+
+    /// Whether night mode compression is switched on.
+    ///
+    /// Shared with the compressor stage of each track.
+    night_mode: Arc<AtomicBool>,
+
+    /// Time of day to switch night mode on and off automatically.
+    night_mode_hours: Option<compressor::Window>,
+
+    /// Whether it was night mode time when last checked.
+    ///
+    /// Night mode is only switched when entering or leaving the window, so that it can
+    /// be switched manually in between.
+    night_mode_time: Option<bool>,
+
     /// Channel for sending playback events.
     ///
     /// Events include:
@@ -239,6 +394,30 @@ pub struct Player {
     /// Only available when device is open (between `start()` and `stop()`).
     stream: Option<rodio::OutputStream>,
 
+    /// Custom audio output provided at construction.
+    ///
+    /// When set, used instead of the device specification. Shared with the
+    /// render thread of `output`, and kept across `stop()` and `start()`.
+    audio_sink: Option<Arc<Mutex<dyn AudioSink>>>,
+
+    /// Render thread for custom, pipe or file output.
+    ///
+    /// Used instead of `stream` when the audio goes into an `AudioSink`.
+    /// Must be kept alive to maintain playback.
+    /// Only available when output is open (between `start()` and `stop()`).
+    output: Option<Output>,
+
+    /// Whether the audio device reported a stream error.
+    ///
+    /// Set from the error callback of the output stream, for example when
+    /// the device was unplugged. Custom outputs report failure through `output`.
+    device_failed: Arc<AtomicBool>,
+
+    /// Reopening state while the audio output is being recovered.
+    ///
+    /// `None` when the output is working, or was closed on purpose.
+    recovery: Option<Recovery>,
+
     /// Queue of audio sources.
     ///
     /// Contains decoded and processed audio data ready for playback.
@@ -274,19 +453,33 @@ pub struct Player {
     max_ram: Option<u64>,
 }
 
-impl Player {
-    /// Logarithmic volume scale factor for a dynamic range of 60 dB.
-    ///
-    /// Equal to 10^(60/20) = 1000.0
-    /// Constant used in volume scaling calculations.
-    const LOG_VOLUME_SCALE_FACTOR: f32 = 1000.0;
+/// Gain reduction statistics of the limiters of a track.
+#[derive(Clone, Debug, Default)]
+struct Limiting {
+    /// Limiter after the normalization gain, if any.
+    normalization: Option<Arc<limiter::Stats>>,
 
-    /// Logarithmic volume growth rate for a dynamic range of 60 dB.
-    ///
-    /// Equal to ln(1000) ≈ 6.907755279
-    /// Constant used in volume scaling calculations.
-    const LOG_VOLUME_GROWTH_RATE: f32 = 6.907_755_4;
+    /// Safety limiter before the output, unless bit-perfect.
+    output: Option<Arc<limiter::Stats>>,
+}
+
+/// State of reopening the audio output after it failed.
+///
+/// The output is reopened with exponential backoff, and the current track is
+/// resumed at the position where the output failed.
+#[derive(Debug)]
+struct Recovery {
+    /// Whether to resume playback once the output is reopened.
+    resume: bool,
+
+    /// When to make the next attempt to reopen the output.
+    retry_at: tokio::time::Instant,
+
+    /// Time to wait after the next failed attempt.
+    backoff: Duration,
+}
 
+impl Player {
     /// Duration of the fade to prevent audio popping when clearing the queue
     /// changing volume, or seeking.
     ///
@@ -311,6 +504,7 @@ impl Player {
     /// Returns error if:
     /// * HTTP client creation fails
     /// * Decryption key is invalid
+    /// * Volume curve settings are invalid
     pub async fn new(config: &Config, device: &str) -> Result<Self> {
         let client = http::Client::without_cookies(config)?;
 
@@ -333,6 +527,11 @@ impl Player {
         let dithered_volume = Arc::new(Volume::default());
         let volume = Percentage::from_ratio(dithered_volume.volume());
 
+        let bookmarks = Bookmarks::load(config.bookmarks.clone()).unwrap_or_else(|e| {
+            error!("{e}; starting without episode bookmarks");
+            Bookmarks::new(config.bookmarks.clone())
+        });
+
         Ok(Self {
             queue: Vec::new(),
             skip_tracks: HashSet::new(),
@@ -343,12 +542,56 @@ impl Player {
             media_url: MediaUrl::default().into(),
             repeat_mode: RepeatMode::default(),
             normalization: config.normalization,
+            normalization_mode: config.normalization_mode,
+            normalize_episodes: config.normalize_episodes,
             loudness: config.loudness,
             gain_target_db,
             volume,
+            volume_scale: volume::Scale::new(
+                config.volume_curve,
+                config.volume_range,
+                config.min_volume.map_or(0.0, |volume| volume.as_ratio()),
+                config.max_volume.map_or(1.0, |volume| volume.as_ratio()),
+            )?,
+            fixed_volume: config.fixed_volume,
+            mixer: config
+                .fixed_volume
+                .then(|| {
+                    mixer::Mixer::new(config.volume_command.clone(), config.volume_mixer.clone())
+                })
+                .flatten(),
             dithered_volume,
             dither_bits: config.dither_bits,
             noise_shaping: config.noise_shaping,
+            resample_quality: config.resample_quality,
+            bit_perfect: config.bit_perfect,
+            native_format: None,
+            output_bits: 0,
+            current_bit_perfect: false,
+            preload_bit_perfect: false,
+            crossfade: config.crossfade,
+            crossfade_curve: config.crossfade_curve,
+            current_crossfade: None,
+            preload_crossfade: None,
+            equalizer: config.equalizer.clone(),
+            equalizer_enabled: Arc::new(AtomicBool::new(config.equalizer.is_some())),
+            convolution: config
+                .convolution
+                .clone()
+                .map(|impulses| convolve::Kernels::new(impulses, config.resample_quality)),
+            current_latency: Duration::ZERO,
+            preload_latency: Duration::ZERO,
+            current_offset: Duration::ZERO,
+            bookmarks,
+            outgoing_bookmarks: Vec::new(),
+            current_limiting: Limiting::default(),
+            preload_limiting: Limiting::default(),
+            scanner: scan::Scanner::new(),
+            crossfeed_preset: config.crossfeed_preset,
+            crossfeed_enabled: Arc::new(AtomicBool::new(config.crossfeed)),
+            night_mode: Arc::new(AtomicBool::new(config.night_mode)),
+            night_mode_hours: config.night_mode_hours,
+            night_mode_time: None,
             event_tx: None,
             playing_since: Duration::ZERO,
             deferred_seek: None,
@@ -358,11 +601,37 @@ impl Player {
             device: device.to_owned(),
             sink: None,
             stream: None,
+            audio_sink: None,
+            output: None,
+            device_failed: Arc::new(AtomicBool::new(false)),
+            recovery: None,
             sources: None,
             max_ram: config.max_ram,
         })
     }
 
+    /// Creates a new player instance that plays into a custom audio output.
+    ///
+    /// The sink receives fully processed samples in its requested format, and
+    /// is used instead of an audio device. See the [`output`](crate::output)
+    /// module for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Player configuration including normalization settings
+    /// * `sink` - Custom audio output
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// * HTTP client creation fails
+    /// * Decryption key is invalid
+    pub async fn with_sink(config: &Config, sink: impl AudioSink + 'static) -> Result<Self> {
+        let mut player = Self::new(config, "").await?;
+        player.audio_sink = Some(Arc::new(Mutex::new(sink)));
+        Ok(player)
+    }
+
     /// Selects and configures an audio output device.
     ///
     /// # Arguments
@@ -536,45 +805,55 @@ impl Player {
     const BUFFER_SIZE_MIN: Duration = Duration::from_millis(100);
     const BUFFER_SIZE_MAX: Duration = Duration::from_millis(500);
 
-    /// Opens and configures the audio output device for playback if not already open.
+    /// Opens an output stream on the audio device.
     ///
-    /// Called internally when needed (e.g., by `play()`) to initialize the audio device.
-    /// The device remains open until `stop` is called or the player is dropped.
+    /// Tries increasing buffer sizes from `BUFFER_SIZE_MIN` to `BUFFER_SIZE_MAX`,
+    /// and falls back to the default buffer size of the device.
     ///
-    /// Note: Manual calls to this method are not required as device initialization
-    /// is handled automatically.
+    /// # Returns
+    ///
+    /// Returns the output stream and its sample format.
+    ///
+    /// Stream errors are logged once and raise `failed`, so the player can
+    /// recover the output.
     ///
     /// # Errors
     ///
     /// Returns error if:
     /// * Audio device specification is invalid
     /// * Device is not available
-    /// * Device cannot be opened
     /// * Output stream creation fails
-    /// * Sink creation fails
-    pub fn start(&mut self) -> Result<()> {
-        if self.is_started() {
-            return Ok(());
-        }
-
-        debug!("opening output device");
+    fn open_device(
+        device: &str,
+        failed: &Arc<AtomicBool>,
+    ) -> Result<(rodio::OutputStream, cpal::SampleFormat)> {
+        let (device, device_config) = Self::get_device(device)?;
+        let error_callback = |failed: Arc<AtomicBool>| {
+            move |e: cpal::StreamError| {
+                // Stream errors tend to repeat until the stream is closed.
+                if !failed.swap(true, Ordering::Relaxed) {
+                    error!("audio output device failed: {e}");
+                }
+            }
+        };
 
-        let (device, device_config) = Self::get_device(&self.device)?;
         let stream_handle = {
+            let sample_rate = device_config.sample_rate().0;
             let mut duration = Self::BUFFER_SIZE_MIN;
             loop {
                 // Calculate buffer size in samples and ensure it's divisible by 4
                 // This ensures alignment with Alsa's period size
-                let size = (DEFAULT_SAMPLE_RATE / 1_000) * u32::try_from(duration.as_millis())?;
+                let size = (sample_rate / 1_000) * u32::try_from(duration.as_millis())?;
                 if let Ok(stream_handle) = rodio::OutputStreamBuilder::default()
                     .with_device(device.clone())
                     .with_supported_config(&device_config)
                     .with_buffer_size(cpal::BufferSize::Fixed(size))
+                    .with_error_callback(error_callback(Arc::clone(failed)))
                     .open_stream()
                 {
                     debug!(
                         "audio buffer size: {:?}",
-                        Duration::from_millis((size * 1_000 / DEFAULT_SAMPLE_RATE).into())
+                        Duration::from_millis((size * 1_000 / sample_rate).into())
                     );
                     break stream_handle;
                 }
@@ -585,16 +864,66 @@ impl Player {
                     let stream_handle = rodio::OutputStreamBuilder::default()
                         .with_device(device)
                         .with_supported_config(&device_config)
+                        .with_error_callback(error_callback(Arc::clone(failed)))
                         .open_stream()?;
                     info!("audio buffer size: default");
                     break stream_handle;
                 }
             }
         };
-        let sink = rodio::Sink::connect_new(stream_handle.mixer());
+
+        Ok((stream_handle, device_config.sample_format()))
+    }
+
+    /// Opens and configures the audio output for playback if not already open.
+    ///
+    /// Called internally when needed (e.g., by `play()`) to initialize the audio output.
+    /// The output remains open until `stop` is called or the player is dropped.
+    ///
+    /// The output is either an audio device, or a named pipe or file when the device
+    /// specification starts with `pipe:` or `file:`. See the [`pipe`](crate::pipe)
+    /// module for details.
+    ///
+    /// Note: Manual calls to this method are not required as device initialization
+    /// is handled automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// * Audio device specification is invalid
+    /// * Device is not available
+    /// * Device cannot be opened
+    /// * Output stream creation fails
+    /// * Pipe or file cannot be opened
+    /// * Sink creation fails
+    pub fn start(&mut self) -> Result<()> {
+        if self.is_started() {
+            return Ok(());
+        }
+
+        let (sink, sample_format) = if let Some(audio_sink) = self.audio_sink.clone() {
+            debug!("opening custom output");
+            self.open_output(audio_sink)?
+        } else if pipe::Spec::matches(&self.device) {
+            debug!("opening output pipe");
+            let pipe = Pipe::open(self.device.parse()?)?;
+            self.open_output(Arc::new(Mutex::new(pipe)))?
+        } else {
+            debug!("opening output device");
+
+            self.device_failed.store(false, Ordering::Relaxed);
+            let (stream_handle, sample_format) = match self.native_format {
+                Some((sample_rate, bits_per_sample)) if self.bit_perfect => {
+                    self.open_native_device(sample_rate, bits_per_sample)?
+                }
+                _ => Self::open_device(&self.device, &self.device_failed)?,
+            };
+            let sink = rodio::Sink::connect_new(stream_handle.mixer());
+            self.stream = Some(stream_handle);
+            (sink, sample_format)
+        };
 
         // Determine the dither bit depth
-        let sample_format = device_config.sample_format();
         let dither_bits = self
             .dither_bits
             .map(|dac_bits| {
@@ -610,7 +939,7 @@ impl Player {
             .or_else(|| {
                 // Set a default dithering level
                 use cpal::SampleFormat::{I8, I16, I32, I64, U8, U16, U32, U64};
-                let bits = match device_config.sample_format() {
+                let bits = match sample_format {
                     // Very low fidelity, e.g., legacy or telephony
                     I8 | U8 => 7.0,
                     // Most DACs handling 16-bit do not achieve a true 16-bit SINAD
@@ -633,8 +962,38 @@ impl Player {
 
         // Set the volume to the last known value. Do not use `self.set_volume` because
         // it will short-circuit when trying to set the volume to what `self.volume` already is.
-        let log_volume = Self::log_volume(self.volume.as_ratio());
-        self.dithered_volume = Arc::new(Volume::new(log_volume, dither_bits));
+        let amplitude = self.volume_scale.amplitude(self.volume.as_ratio());
+        let mut volume = if self.fixed_volume {
+            // The external amplifier attenuates: keep the output at full scale.
+            Volume::new(UNITY_GAIN, dither_bits).with_fixed_output(amplitude)
+        } else {
+            Volume::new(amplitude, dither_bits)
+        };
+
+        self.output_bits = match sample_format {
+            // Floats carry as many bits as their mantissa
+            cpal::SampleFormat::F32 => 24,
+            cpal::SampleFormat::F64 => 53,
+            _ => (sample_format.sample_size() * 8)
+                .try_into()
+                .unwrap_or(u32::MAX),
+        };
+        if self.bit_perfect {
+            debug!("bit-perfect: up to {} bits per sample", self.output_bits);
+            volume = volume.with_bit_perfect(self.output_bits);
+        }
+        self.dithered_volume = Arc::new(volume);
+
+        // Prepare room correction for the output sample rate, so tracks load without delay.
+        let sample_rate = self.output_sample_rate();
+        if let (Some(kernels), Some(sample_rate)) = (self.convolution.as_mut(), sample_rate) {
+            let kernel = kernels.get(sample_rate);
+            debug!(
+                "room correction: {} at {:.1} kHz",
+                kernels.path(),
+                kernel.sample_rate().to_f32_lossy() / 1000.0
+            );
+        }
 
         // The output source will output silence when the queue is empty.
         // That will cause the sink to report as "playing", so we need to pause it.
@@ -644,11 +1003,122 @@ impl Player {
 
         self.sink = Some(sink);
         self.sources = Some(sources);
-        self.stream = Some(stream_handle);
 
         Ok(())
     }
 
+    /// Starts rendering into an audio sink.
+    ///
+    /// # Returns
+    ///
+    /// Returns a sink connected to the output, and the sample format of the
+    /// audio sink.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the render thread cannot be started.
+    fn open_output(
+        &mut self,
+        audio_sink: Arc<Mutex<dyn AudioSink>>,
+    ) -> Result<(rodio::Sink, cpal::SampleFormat)> {
+        let output = Output::open(audio_sink)?;
+        let sink = rodio::Sink::connect_new(output.mixer());
+        let sample_format = output.format().sample_format;
+        self.output = Some(output);
+        Ok((sink, sample_format))
+    }
+
+    /// Opens the audio device at the native format of a track.
+    ///
+    /// Keeps the host and device of the device specification, and tries sample formats
+    /// that carry the bit depth of the track without loss, narrowest first. Falls back
+    /// to the device specification if the device supports none of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - Native sample rate of the track
+    /// * `bits_per_sample` - Native bit depth of the track, if known
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the device cannot be opened with the device specification either.
+    fn open_native_device(
+        &self,
+        sample_rate: SampleRate,
+        bits_per_sample: Option<u32>,
+    ) -> Result<(rodio::OutputStream, cpal::SampleFormat)> {
+        let mut components = self.device.split('|');
+        let host = components.next().unwrap_or_default();
+        let device = components.next().unwrap_or_default();
+        let user_format = [components.nth(1).unwrap_or_default()];
+
+        let formats: &[&str] = match bits_per_sample {
+            Some(bits) if bits <= 16 => &["i16", "i32", "f32"],
+            Some(bits) if bits <= 24 => &["i32", "f32"],
+            Some(_) => &["i32"],
+            None => &user_format,
+        };
+
+        for format in formats {
+            let spec = format!("{host}|{device}|{sample_rate}|{format}");
+            match Self::open_device(&spec, &self.device_failed) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => debug!("bit-perfect: cannot open {spec}: {e}"),
+            }
+        }
+
+        warn!(
+            "audio output device does not support {:.1} kHz at {} bits, not bit-perfect",
+            sample_rate.to_f32_lossy() / 1000.0,
+            bits_per_sample.unwrap_or(DEFAULT_BITS_PER_SAMPLE)
+        );
+        Self::open_device(&self.device, &self.device_failed)
+    }
+
+    /// Closes and reopens the audio output, keeping the playback state.
+    ///
+    /// Drops all queued audio. If the output cannot be reopened, recovery is started.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the output cannot be reopened.
+    fn reopen_output(&mut self) -> Result<()> {
+        let playing = self.sink.as_ref().is_some_and(|sink| !sink.is_paused());
+
+        self.close_output();
+        if let Err(e) = self.start() {
+            self.begin_recovery(playing);
+            return Err(e);
+        }
+
+        self.playing_since = Duration::ZERO;
+        if playing {
+            self.sink_mut()?.play();
+            if let Some(output) = &self.output {
+                output.set_paused(false);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the sample rate of the audio output in Hz.
+    ///
+    /// Tracks are converted to this sample rate before playback.
+    ///
+    /// Returns `None` if the output is not open.
+    #[must_use]
+    pub fn output_sample_rate(&self) -> Option<SampleRate> {
+        self.stream
+            .as_ref()
+            .map(|stream| stream.config().sample_rate())
+            .or_else(|| {
+                self.output
+                    .as_ref()
+                    .map(|output| output.format().sample_rate)
+            })
+    }
+
     /// Closes the audio output device and stops playback.
     ///
     /// Releases audio device resources and clears any queued audio.
@@ -657,8 +1127,19 @@ impl Player {
     /// Note: This method is automatically called when the player is dropped,
     /// ensuring proper cleanup of audio device resources.
     pub fn stop(&mut self) {
+        let started = self.is_started();
         self.ramp_volume(0.0);
+        self.close_output();
+        if started {
+            self.notify(Event::Stopped);
+        }
+
+        // Closing on purpose cancels any recovery in progress.
+        self.recovery = None;
+    }
 
+    /// Releases the audio output resources.
+    fn close_output(&mut self) {
         // Don't care if the sink is already dropped: we're already "stopped".
         if let Ok(sink) = self.sink_mut() {
             debug!("closing output device");
@@ -667,15 +1148,116 @@ impl Player {
 
         self.sources = None;
         self.stream = None;
+        self.output = None;
         self.sink = None;
     }
 
+    /// Minimum time to wait before reopening a failed audio output.
+    const RECOVERY_BACKOFF_MIN: Duration = Duration::from_millis(500);
+
+    /// Maximum time to wait between attempts to reopen a failed audio output.
+    ///
+    /// Attempts continue indefinitely, for example until a USB DAC is powered
+    /// on again.
+    const RECOVERY_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+    /// Returns whether the audio output failed while open.
+    fn is_output_failed(&self) -> bool {
+        self.device_failed.load(Ordering::Relaxed)
+            || self.output.as_ref().is_some_and(Output::is_failed)
+    }
+
+    /// Returns whether the audio output failed and is being reopened.
+    ///
+    /// While recovering, `run()` must keep being polled so that it can
+    /// reopen the output.
+    #[must_use]
+    #[inline]
+    pub fn is_recovering(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    /// Closes the failed audio output and schedules reopening it.
+    ///
+    /// Remembers the position in the current track, so that playback resumes
+    /// where the output failed. The current and next track are reloaded once the
+    /// output is reopened.
+    ///
+    /// # Arguments
+    ///
+    /// * `resume` - Whether to resume playback once the output is reopened
+    fn begin_recovery(&mut self, resume: bool) {
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.resume |= resume;
+            return;
+        }
+
+        warn!("audio output unavailable, trying to reopen");
+        self.notify(Event::PlaybackError {
+            error: "audio output unavailable".to_string(),
+        });
+
+        // Livestreams resume at the live position.
+        if self.is_loaded() && !self.track().is_some_and(Track::is_livestream) {
+            self.deferred_seek = Some(self.elapsed());
+        }
+
+        self.clear();
+        self.close_output();
+        self.device_failed.store(false, Ordering::Relaxed);
+
+        self.recovery = Some(Recovery {
+            resume,
+            retry_at: tokio::time::Instant::now() + Self::RECOVERY_BACKOFF_MIN,
+            backoff: Self::RECOVERY_BACKOFF_MIN.saturating_mul(2),
+        });
+    }
+
+    /// Attempts to reopen the audio output if the next attempt is due.
+    ///
+    /// On success, resumes playback if it was playing when the output failed.
+    /// On failure, doubles the backoff up to `RECOVERY_BACKOFF_MAX`.
+    fn recover(&mut self) {
+        let Some((resume, backoff)) = self
+            .recovery
+            .as_ref()
+            .filter(|recovery| tokio::time::Instant::now() >= recovery.retry_at)
+            .map(|recovery| (recovery.resume, recovery.backoff))
+        else {
+            return;
+        };
+
+        match self.start() {
+            Ok(()) => {
+                info!("audio output reopened");
+                self.recovery = None;
+                if resume {
+                    if let Err(e) = self.play() {
+                        error!("failed to resume playback: {e}");
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("failed to reopen audio output: {e}; retrying in {backoff:?}");
+                self.recovery = Some(Recovery {
+                    resume,
+                    retry_at: tokio::time::Instant::now() + backoff,
+                    backoff: backoff.saturating_mul(2).min(Self::RECOVERY_BACKOFF_MAX),
+                });
+            }
+        }
+    }
+
     /// The list of sample rates to enumerate.
     ///
-    /// Only includes the two most common sample rates in Hz:
+    /// Includes the most common sample rates in Hz, in order of preference:
     /// * 44100 - CD audio, most streaming services
     /// * 48000 - Professional digital audio, video production, many sound cards
-    const SAMPLE_RATES: [u32; 2] = [44_100, 48_000];
+    /// * 88200, 96000, 192000 - High-resolution DACs, some of which accept nothing else
+    ///
+    /// Tracks are resampled to the output sample rate, and noise shaping is available
+    /// at all of these rates.
+    const SAMPLE_RATES: [u32; 5] = [44_100, 48_000, 88_200, 96_000, 192_000];
 
     /// The list of sample formats to enumerate.
     ///
@@ -700,6 +1282,8 @@ impl Player {
     /// * Standard sample rates:
     ///   - 44.1 kHz (CD audio, streaming services)
     ///   - 48 kHz (professional audio, video production)
+    ///   - 88.2, 96 and 192 kHz (high-resolution DACs)
+    /// * Standard sample formats:
     ///   - I16 (16-bit integer)
     ///   - I32 (32-bit integer)
     ///   - F32 (32-bit float)
@@ -800,23 +1384,18 @@ impl Player {
         }
     }
 
-    /// The normalization attack time (5ms).
-    /// This is the time it takes for the limiter to respond to level increases.
-    /// Value matches Spotify's implementation for consistent behavior.
-    const NORMALIZE_ATTACK_TIME: Duration = Duration::from_millis(5);
-
     /// The normalization release time (100ms).
     /// This is the time it takes for the limiter to recover after level decreases.
     /// Value matches Spotify's implementation for consistent behavior.
     const NORMALIZE_RELEASE_TIME: Duration = Duration::from_millis(100);
 
-    /// Threshold level where limiting begins.
-    /// Set to -1 dB to provide headroom for inter-sample peaks.
+    /// Highest true peak after normalization, in dBTP.
+    /// Set to -1 dBTP as recommended by EBU R128.
     const NORMALIZE_THRESHOLD_DB: f32 = -1.0;
 
-    /// Width of the soft knee in dB.
-    /// A 4 dB width provides smooth transition into limiting.
-    const NORMALIZE_KNEE_WIDTH_DB: f32 = 4.0;
+    /// Highest true peak at the output, in dBTP.
+    /// Only reached with boosts after normalization, like equalization or night mode.
+    const OUTPUT_THRESHOLD_DB: f32 = -0.1;
 
     /// Time before network operations timeout.
     const NETWORK_TIMEOUT: Duration = Duration::from_secs(2);
@@ -838,7 +1417,12 @@ impl Player {
     ///    * Sample rate from codec (defaults to 44.1 kHz)
     ///    * Bits per sample if available
     ///    * Channel count from codec or content type
-    /// 4. Applies volume normalization if enabled
+    /// 4. Converts the sample rate to that of the audio output
+    /// 5. Applies volume normalization if enabled
+    /// 6. Applies night mode compression when switched on
+    /// 7. Applies the equalizer if configured
+    /// 8. Applies room correction if configured
+    /// 9. Applies headphone crossfeed to stereo content
     ///
     /// # Arguments
     ///
@@ -866,17 +1450,52 @@ impl Player {
             }
         }
 
-        let track = self
+        let mut output_sample_rate = self
+            .output_sample_rate()
+            .ok_or_else(|| Error::unavailable("audio output not available"))?;
+
+        // Tracks from the same album are never crossfaded.
+        let same_album = self
+            .track()
+            .zip(self.queue.get(position))
+            .is_some_and(|(current, next)| current.is_same_album(next));
+
+        let album_gain = if self.normalization {
+            self.album_gain(position)
+        } else {
+            None
+        };
+
+        let mut track = self
             .queue
             .get_mut(position)
             .ok_or_else(|| Error::not_found(format!("track at position {position} not found")))?;
 
-        let sources = self
-            .sources
-            .as_mut()
-            .ok_or_else(|| Error::unavailable("audio sources not available"))?;
+        if self.sources.is_none() {
+            return Err(Error::unavailable("audio sources not available"));
+        }
 
         if track.handle().is_none() {
+            // Livestreams, and optionally episodes, are normalized while they play.
+            let auto_gain = self.normalization
+                && (track.is_livestream()
+                    || (self.normalize_episodes && track.typ() == TrackType::Episode));
+
+            // Measure the loudness of tracks without gain information while they download,
+            // unless measured before.
+            let mirror = if self.normalization
+                && !auto_gain
+                && album_gain.is_none()
+                && track.gain().is_none()
+                && !track.is_livestream()
+                && self.scanner.loudness(track.id()).is_none()
+            {
+                self.scanner.mirror(track.id())
+            } else {
+                None
+            };
+            let scanning = mirror.is_some();
+
             let download = tokio::time::timeout(Self::NETWORK_TIMEOUT, async {
                 // Start downloading the track.
                 let medium = track
@@ -924,6 +1543,10 @@ impl Player {
                         .try_into()
                         .map_err(|e| Error::internal(format!("prefetch size error: {e}")))?,
                 );
+                let storage = match mirror {
+                    Some(mirror) => mirror.wrap(storage),
+                    None => MirrorStorage::passthrough(storage),
+                };
                 track.start_download(&self.client, &medium, storage).await
             })
             .await??;
@@ -936,27 +1559,100 @@ impl Player {
                 track.bits_per_sample = Some(bits_per_sample);
             }
 
+            // In bit-perfect mode, switch the audio device to the native format of the track.
+            let native_format = (decoder.sample_rate(), decoder.bits_per_sample());
+            if self.bit_perfect
+                && self.stream.is_some()
+                && (native_format.0 != output_sample_rate
+                    || native_format.1.is_some_and(|bits| bits > self.output_bits))
+            {
+                if position != self.position {
+                    // Switching formats cannot be gapless, so load the track when it
+                    // becomes current. Don't retry preloading until then.
+                    debug!(
+                        "not preloading {} {track}: output format changes",
+                        track.typ()
+                    );
+                    track.reset_download();
+                    self.preload_start = Duration::MAX;
+                    return Ok(None);
+                }
+
+                debug!(
+                    "bit-perfect: reopening audio output at {:.1} kHz",
+                    native_format.0.to_f32_lossy() / 1000.0
+                );
+                self.native_format = Some(native_format);
+                self.reopen_output()?;
+
+                output_sample_rate = self
+                    .output_sample_rate()
+                    .ok_or_else(|| Error::unavailable("audio output not available"))?;
+                track = self.queue.get_mut(position).ok_or_else(|| {
+                    Error::not_found(format!("track at position {position} not found"))
+                })?;
+            }
+
+            // Resume episodes from their bookmark, unless seeking elsewhere already.
+            if track.is_podcast() && self.deferred_seek.is_none_or(|progress| progress.is_zero()) {
+                if let Some(resume) = self.bookmarks.resume_position(track.id()) {
+                    if position != self.position {
+                        // Starting halfway cannot be gapless, so load the episode when it
+                        // becomes current. Don't retry preloading until then.
+                        debug!("not preloading {} {track}: resuming", track.typ());
+                        track.reset_download();
+                        self.preload_start = Duration::MAX;
+                        return Ok(None);
+                    }
+
+                    info!("resuming {} {track} at {}s", track.typ(), resume.as_secs());
+                    self.deferred_seek = Some(resume);
+                }
+            }
+
             // Seek to the deferred position if set.
+            let mut start = Duration::ZERO;
             if let Some(progress) = self.deferred_seek.take() {
                 // Set the track position only if `progress` is beyond the track start. We start
                 // at the beginning anyway, and this prevents decoder errors.
                 if !progress.is_zero() {
-                    if let Err(e) = decoder.try_seek(progress) {
-                        error!("failed to seek to deferred position: {e}");
+                    match decoder.try_seek(progress) {
+                        Ok(()) => {
+                            start = progress;
+                            // The sink starts counting from zero, so remember where we started.
+                            if position == self.position {
+                                self.current_offset = progress;
+                            }
+                        }
+                        Err(e) => error!("failed to seek to deferred position: {e}"),
                     }
                 }
             }
 
             // Apply volume normalization if enabled.
             let mut difference = 0.0;
-            if self.normalization {
-                match track.gain() {
+            let mut deferred_gain = None;
+            if self.normalization && !auto_gain {
+                if let Some(gain) = album_gain {
+                    debug!("album loudness: {gain:.1} dB");
+                }
+                match album_gain.or_else(|| track.gain()) {
                     Some(gain) => difference = f32::from(self.gain_target_db) - gain,
                     None => {
                         if let Some(replay_gain) = decoder.replay_gain() {
                             debug!("track replay gain: {replay_gain:.1} dB");
+                            self.scanner.cancel(track.id());
                             let track_lufs = f32::from(Self::REPLAY_GAIN_LUFS) - replay_gain;
                             difference = f32::from(self.gain_target_db) - track_lufs;
+                        } else if let Some(track_lufs) = self.scanner.loudness(track.id()) {
+                            debug!("track measured loudness: {track_lufs:.1} LUFS");
+                            difference = f32::from(self.gain_target_db) - track_lufs;
+                        } else if scanning {
+                            debug!(
+                                "{} {track} has no gain information, measuring loudness",
+                                track.typ()
+                            );
+                            deferred_gain = Some(self.scanner.gain(track.id()));
                         } else {
                             warn!(
                                 "{} {track} has no gain information, skipping normalization",
@@ -967,36 +1663,76 @@ impl Player {
                 }
             }
 
+            // Convert to the output sample rate, so all processing runs at that rate.
+            let source = resample::resample(decoder, output_sample_rate, self.resample_quality);
+            if !source.is_bypassed() {
+                debug!(
+                    "resampling {} {track} from {:.1} to {:.1} kHz ({} quality)",
+                    track.typ(),
+                    source.inner().sample_rate().to_f32_lossy() / 1000.0,
+                    output_sample_rate.to_f32_lossy() / 1000.0,
+                    self.resample_quality,
+                );
+            }
+
             let lufs_target = if self.loudness {
                 Some(self.gain_target_db.into())
             } else {
                 None
             };
 
-            let rx = if 2.0 * difference.abs() <= f32::EPSILON * difference.abs() {
-                // No normalization needed, just append the decoder.
-                sources.append_with_signal(dither::dithered_volume(
-                    decoder,
-                    self.dithered_volume.clone(),
-                    lufs_target,
-                    self.noise_shaping,
-                ))
+            // Volume and dither are bypassed at runtime, the rest of the chain must be a no-op.
+            let bit_perfect = self.bit_perfect
+                && source.is_bypassed()
+                && lufs_target.is_none()
+                && self.convolution.is_none()
+                && !self.equalizer_enabled()
+                && !self.crossfeed_enabled()
+                && !self.night_mode()
+                && deferred_gain.is_none()
+                && !auto_gain
+                && 2.0 * difference.abs() <= f32::EPSILON * difference.abs();
+            if position == self.position {
+                self.current_bit_perfect = bit_perfect;
             } else {
-                let ratio = util::db_to_ratio(difference);
-                if difference < 1.0 {
+                self.preload_bit_perfect = bit_perfect;
+            }
+
+            let mut limiting = Limiting::default();
+            let source: Box<dyn Source + Send> = if auto_gain {
+                debug!(
+                    "normalizing {} {track} with automatic gain control",
+                    track.typ()
+                );
+                let stats = limiting.normalization.insert(Arc::default()).clone();
+                Box::new(limiter::limit(
+                    agc::agc(source, f32::from(self.gain_target_db)),
+                    Self::NORMALIZE_THRESHOLD_DB,
+                    Self::NORMALIZE_RELEASE_TIME,
+                    Some(stats),
+                ))
+            } else if let Some(gain) = deferred_gain {
+                // The gain follows once the loudness is measured, so limit just in case.
+                let stats = limiting.normalization.insert(Arc::default()).clone();
+                Box::new(limiter::limit(
+                    scan::deferred(source, gain),
+                    Self::NORMALIZE_THRESHOLD_DB,
+                    Self::NORMALIZE_RELEASE_TIME,
+                    Some(stats),
+                ))
+            } else if 2.0 * difference.abs() <= f32::EPSILON * difference.abs() {
+                // No normalization needed, just process the source.
+                Box::new(source)
+            } else {
+                let ratio = util::db_to_ratio(difference);
+                if difference < 1.0 {
                     debug!(
                         "normalizing {} {track} by {difference:.1} dB ({}) by attenuation",
                         track.typ(),
                         Percentage::from_ratio(ratio)
                     );
 
-                    let attenuated = decoder.amplify(ratio);
-                    sources.append_with_signal(dither::dithered_volume(
-                        attenuated,
-                        self.dithered_volume.clone(),
-                        lufs_target,
-                        self.noise_shaping,
-                    ))
+                    Box::new(source.amplify(ratio))
                 } else {
                     debug!(
                         "normalizing {} {track} by {difference:.1} dB ({}) with dynamic limiting",
@@ -1004,23 +1740,136 @@ impl Player {
                         Percentage::from_ratio(ratio)
                     );
 
-                    let normalized = normalize::normalize(
-                        decoder,
-                        ratio,
+                    let stats = limiting.normalization.insert(Arc::default()).clone();
+                    Box::new(limiter::limit(
+                        source.amplify(ratio),
                         Self::NORMALIZE_THRESHOLD_DB,
-                        Self::NORMALIZE_KNEE_WIDTH_DB,
-                        Self::NORMALIZE_ATTACK_TIME,
                         Self::NORMALIZE_RELEASE_TIME,
-                    );
-                    sources.append_with_signal(dither::dithered_volume(
-                        normalized,
-                        self.dithered_volume.clone(),
-                        lufs_target,
-                        self.noise_shaping,
+                        Some(stats),
                     ))
                 }
             };
 
+            // Compress in night mode. When switched off, samples pass through unchanged.
+            let source: Box<dyn Source + Send> = Box::new(compressor::compress(
+                source,
+                compressor::Settings::night(),
+                self.night_mode.clone(),
+            ));
+
+            // Equalize after normalization, with headroom so boosts cannot clip.
+            // When switched off, the equalizer passes samples through unchanged.
+            let source: Box<dyn Source + Send> = match self.equalizer.as_ref() {
+                Some(settings) => Box::new(equalizer::equalize(
+                    source,
+                    settings,
+                    self.equalizer_enabled.clone(),
+                )),
+                None => source,
+            };
+
+            // Apply room correction, which delays the output.
+            let mut latency = Duration::ZERO;
+            let source: Box<dyn Source + Send> = match self.convolution.as_mut() {
+                Some(kernels) => {
+                    let kernel = kernels.get(output_sample_rate);
+                    latency = kernel.latency();
+                    Box::new(convolve::convolve(source, kernel))
+                }
+                None => source,
+            };
+            if position == self.position {
+                self.current_latency = latency;
+            } else {
+                self.preload_latency = latency;
+            }
+
+            // Crossfeed applies to stereo only. When switched off, samples pass through.
+            let source: Box<dyn Source + Send> = if source.channels() == 2 {
+                Box::new(crossfeed::crossfeed(
+                    source,
+                    self.crossfeed_preset,
+                    self.crossfeed_enabled.clone(),
+                ))
+            } else {
+                source
+            };
+
+            // Catch what boosts after normalization, like the equalizer or night mode,
+            // would clip. Limit before dithering, so that gain reduction does not rescale
+            // requantized samples. Bit-perfect tracks pass unchanged.
+            let source: Box<dyn Source + Send> = if bit_perfect {
+                source
+            } else {
+                let stats = limiting.output.insert(Arc::default()).clone();
+                Box::new(limiter::limit(
+                    source,
+                    Self::OUTPUT_THRESHOLD_DB,
+                    Self::NORMALIZE_RELEASE_TIME,
+                    Some(stats),
+                ))
+            };
+            if position == self.position {
+                self.current_limiting = limiting;
+            } else {
+                self.preload_limiting = limiting;
+            }
+
+            // Prepare to crossfade into the next track. Bit-perfect tracks are not mixed.
+            let (source, crossfade): (Box<dyn Source + Send>, _) =
+                if self.crossfade.is_zero() || bit_perfect || track.is_livestream() {
+                    (source, None)
+                } else {
+                    // The decoded duration is more exact than the metadata, and includes
+                    // the latency of room correction.
+                    let total_duration = source
+                        .total_duration()
+                        .or_else(|| track.duration().map(|duration| duration + latency));
+                    let (source, handle) = crossfade::crossfade(
+                        source,
+                        self.crossfade,
+                        total_duration,
+                        start,
+                        self.crossfade_curve,
+                    );
+                    (Box::new(source), Some(handle))
+                };
+
+            // Crossfade from the current track into a preloaded one.
+            let source: Box<dyn Source + Send> = match self.current_crossfade.as_ref() {
+                Some(previous) if position != self.position => {
+                    if same_album {
+                        debug!("playing {} {track} gapless within album", track.typ());
+                        source
+                    } else {
+                        debug!("crossfading into {} {track}", track.typ());
+                        Box::new(previous.attach(source))
+                    }
+                }
+                _ => source,
+            };
+
+            if position == self.position {
+                self.current_crossfade = crossfade;
+            } else {
+                self.preload_crossfade = crossfade;
+            }
+
+            // Apply volume and dither after mixing, so that crossfades are requantized once.
+            let source = dither::dithered_volume(
+                source,
+                self.dithered_volume.clone(),
+                lufs_target,
+                bit_perfect,
+                self.noise_shaping,
+            );
+
+            let rx = self
+                .sources
+                .as_mut()
+                .ok_or_else(|| Error::unavailable("audio sources not available"))?
+                .append_with_signal(source);
+
             let sample_rate = track.sample_rate.map_or("unknown".to_string(), |rate| {
                 (rate.to_f32_lossy() / 1000.).to_string()
             });
@@ -1047,6 +1896,8 @@ impl Player {
 
     /// Returns the current playback position from the sink.
     ///
+    /// For custom outputs, this is compensated for the latency of the output.
+    ///
     /// Returns `Duration::ZERO` if audio device is not open.
     #[must_use]
     fn get_pos(&self) -> Duration {
@@ -1054,6 +1905,23 @@ impl Player {
         self.sink
             .as_ref()
             .map_or(Duration::ZERO, rodio::Sink::get_pos)
+            .saturating_sub(self.latency())
+    }
+
+    /// Returns the latency reported by a custom output.
+    ///
+    /// Returns `Duration::ZERO` for audio devices, or if the output is not open.
+    #[must_use]
+    pub fn latency(&self) -> Duration {
+        self.output.as_ref().map_or(Duration::ZERO, Output::latency)
+    }
+
+    /// Returns the number of underruns reported by a custom output.
+    ///
+    /// Returns 0 for audio devices, or if the output is not open.
+    #[must_use]
+    pub fn underruns(&self) -> u64 {
+        self.output.as_ref().map_or(0, Output::underruns)
     }
 
     /// Main playback loop.
@@ -1067,6 +1935,11 @@ impl Player {
     /// Audio playback requires calling `start()` to open the audio device,
     /// but track loading and queue management will work without it.
     ///
+    /// When the audio output fails, for example because a USB DAC was unplugged
+    /// or an HDMI receiver went to standby, this loop keeps reopening the output
+    /// with backoff. Once reopened, the current track resumes at the position
+    /// where the output failed. Keep polling this loop while `is_recovering()`.
+    ///
     /// # Errors
     ///
     /// Returns error if:
@@ -1075,83 +1948,119 @@ impl Player {
     pub async fn run(&mut self) -> Result<()> {
         const RUN_FREQUENCY: Duration = Duration::from_millis(10);
         loop {
-            match self.current_rx.as_mut() {
-                Some(current_rx) => {
-                    if current_rx.try_recv().is_ok() {
-                        // Case 1: Current track finished; advance to the next track.
-                        // Save the point in time when the track finished playing.
-                        self.playing_since = self.get_pos();
-                        self.current_rx = self.preload_rx.take();
-                        if let Some(track) = self.track_mut() {
-                            // Finished tracks are dropped from the queue, which also removes
-                            // their associated download, so reset the state.
-                            track.reset_download();
-                        }
-                        self.go_next();
-                    } else if self.repeat_mode == RepeatMode::One {
-                        // Case 2: To repeat the current track re-using the current download,
-                        // check if we are near the end of the track.
-                        if let Some(duration) = self.track().and_then(Track::duration) {
-                            let remaining = duration.saturating_sub(self.get_pos());
-                            if remaining <= RUN_FREQUENCY * 2 {
-                                if self.set_progress(Percentage::ZERO).is_ok() {
-                                    // Count this as a new playback stream and refresh the UI.
-                                    self.notify(Event::Play);
-                                } else {
-                                    // If we failed to wind back to the beginning of the track,
-                                    // clear the player, so the run loop can download it again.
-                                    self.clear();
-                                }
+            // Reopen the audio output if it failed, e.g. because the device was unplugged.
+            if self.is_output_failed() {
+                let resume = self.is_playing();
+                self.begin_recovery(resume);
+            }
+            if self.is_recovering() {
+                self.recover();
+            }
+
+            // Loading tracks requires an open output.
+            if self.is_started() {
+                match self.current_rx.as_mut() {
+                    Some(current_rx) => {
+                        if current_rx.try_recv().is_ok() {
+                            // Case 1: Current track finished; advance to the next track.
+                            // Keep the final position of an episode before it is replaced.
+                            self.bookmark_outgoing();
+
+                            // Save the point in time when the track finished playing. With a
+                            // crossfade, the next track started playing before that.
+                            let overlap = self
+                                .current_crossfade
+                                .take()
+                                .map_or(Duration::ZERO, |handle| handle.overlap());
+                            self.playing_since = self.get_pos().saturating_sub(overlap);
+                            self.current_rx = self.preload_rx.take();
+                            self.current_crossfade = self.preload_crossfade.take();
+                            self.current_bit_perfect =
+                                std::mem::take(&mut self.preload_bit_perfect);
+                            self.current_latency = std::mem::take(&mut self.preload_latency);
+                            self.current_offset = Duration::ZERO;
+                            self.report_limiting();
+                            self.current_limiting = std::mem::take(&mut self.preload_limiting);
+                            if let Some(track) = self.track_mut() {
+                                // Finished tracks are dropped from the queue, which also removes
+                                // their associated download, so reset the state.
+                                track.reset_download();
                             }
-                        }
-                    } else if self.preload_rx.is_none()
-                        && self.track().is_some_and(Track::is_complete)
-                        && self.get_pos() >= self.preload_start
-                    {
-                        // Case 3: Preload the next track for gapless playback.
-                        let next_position = self.position.saturating_add(1);
-                        if let Some(next_track) = self.queue.get(next_position) {
-                            let next_track_id = next_track.id();
-                            let next_track_typ = next_track.typ();
-                            if !self.skip_tracks.contains(&next_track_id) {
-                                match self.load_track(next_position).await {
-                                    Ok(rx) => {
-                                        self.preload_rx = rx;
+                            self.go_next();
+                        } else if self.repeat_mode == RepeatMode::One {
+                            // Case 2: To repeat the current track re-using the current download,
+                            // check if we are near the end of the track.
+                            if let Some(duration) = self.track().and_then(Track::duration) {
+                                let remaining = duration.saturating_sub(self.get_pos());
+                                if remaining <= RUN_FREQUENCY * 2 {
+                                    if self.set_progress(Percentage::ZERO).is_ok() {
+                                        // Count this as a new playback stream and refresh the UI.
+                                        self.notify(Event::Play);
+                                    } else {
+                                        // If we failed to wind back to the beginning of the track,
+                                        // clear the player, so the run loop can download it again.
+                                        self.clear();
                                     }
-                                    Err(e) => {
-                                        error!("failed to preload next {next_track_typ}: {e}");
-                                        self.mark_unavailable(next_track_id);
+                                }
+                            }
+                        } else if self.preload_rx.is_none()
+                            && self.track().is_some_and(Track::is_complete)
+                            && self.get_pos() >= self.preload_start
+                        {
+                            // Case 3: Preload the next track for gapless playback.
+                            let next_position = self.position.saturating_add(1);
+                            if let Some(next_track) = self.queue.get(next_position) {
+                                let next_track_id = next_track.id();
+                                let next_track_typ = next_track.typ();
+                                // Placeholders are preloaded once they are resolved.
+                                if next_track.is_resolved()
+                                    && !self.skip_tracks.contains(&next_track_id)
+                                {
+                                    match self.load_track(next_position).await {
+                                        Ok(rx) => {
+                                            self.preload_rx = rx;
+                                        }
+                                        Err(e) => {
+                                            error!("failed to preload next {next_track_typ}: {e}");
+                                            self.mark_unavailable(next_track_id, &e);
+                                        }
                                     }
                                 }
                             }
                         }
                     }
-                }
 
-                None => {
-                    if let Some(track) = self.track() {
-                        let track_id = track.id();
-                        let track_typ = track.typ();
-                        let track_dur = track.duration();
-                        let track_bits = track.bits_per_sample;
-                        if self.skip_tracks.contains(&track_id) {
-                            self.go_next();
-                        } else {
-                            match self.load_track(self.position).await {
-                                Ok(rx) => {
-                                    if let Some(rx) = rx {
-                                        self.current_rx = Some(rx);
-                                        self.dithered_volume.set_track_bit_depth(track_bits);
-                                        self.preload_start = self.calc_preload_start(track_dur);
-                                        self.notify(Event::TrackChanged);
-                                        if self.is_playing() {
-                                            self.notify(Event::Play);
+                    None => {
+                        if let Some(track) = self.track() {
+                            let track_id = track.id();
+                            let track_typ = track.typ();
+                            let track_dur = track.duration();
+                            if self.skip_tracks.contains(&track_id) {
+                                self.go_next();
+                            } else if !track.is_resolved() {
+                                // Wait for the metadata of the placeholder.
+                                trace!("waiting for {track_typ} {track_id} to be resolved");
+                            } else {
+                                match self.load_track(self.position).await {
+                                    Ok(rx) => {
+                                        if let Some(rx) = rx {
+                                            self.current_rx = Some(rx);
+                                            // Known only once the track was decoded.
+                                            let track_bits = self
+                                                .track()
+                                                .and_then(|track| track.bits_per_sample);
+                                            self.dithered_volume.set_track_bit_depth(track_bits);
+                                            self.preload_start = self.calc_preload_start(track_dur);
+                                            self.notify(Event::TrackChanged);
+                                            if self.is_playing() {
+                                                self.notify(Event::Play);
+                                            }
                                         }
                                     }
-                                }
-                                Err(e) => {
-                                    error!("failed to load {track_typ}: {e}");
-                                    self.mark_unavailable(track_id);
+                                    Err(e) => {
+                                        error!("failed to load {track_typ}: {e}");
+                                        self.mark_unavailable(track_id, &e);
+                                    }
                                 }
                             }
                         }
@@ -1159,6 +2068,12 @@ impl Player {
                 }
             }
 
+            self.schedule_night_mode();
+
+            // Scan downloads of tracks without gain information.
+            self.scanner
+                .poll(&self.queue, f32::from(self.gain_target_db));
+
             // Yield to the runtime to allow other tasks to run.
             tokio::time::sleep(RUN_FREQUENCY).await;
         }
@@ -1167,21 +2082,35 @@ impl Player {
     /// Calculates the start time for preloading a track.
     ///
     /// The start time is calculated based on the current position and the track duration.
-    /// If the track duration is not available, preloads may start immediately.
+    /// If the track duration is not available, preloads may start immediately. When
+    /// crossfading, preloads start earlier by the crossfade duration.
     fn calc_preload_start(&self, track_duration: Option<Duration>) -> Duration {
         self.get_pos()
             .saturating_add(track_duration.map_or(Duration::ZERO, |duration| {
-                duration.saturating_sub(Track::PREFETCH_DURATION.saturating_mul(2))
+                duration.saturating_sub(
+                    Track::PREFETCH_DURATION
+                        .saturating_mul(2)
+                        .saturating_add(self.crossfade),
+                )
             }))
     }
 
     /// Marks a track as unavailable for playback.
     ///
     /// Tracks marked unavailable will be skipped during playback.
-    /// Logs a warning the first time a track is marked unavailable.
-    fn mark_unavailable(&mut self, track_id: TrackId) {
+    /// Logs a warning and notifies the first time a track is marked unavailable.
+    ///
+    /// # Arguments
+    ///
+    /// * `track_id` - Track to skip
+    /// * `reason` - Why the track cannot be played
+    pub fn mark_unavailable(&mut self, track_id: TrackId, reason: impl fmt::Display) {
         if self.skip_tracks.insert(track_id) {
-            warn!("marking track {track_id} as unavailable");
+            warn!("marking track {track_id} as unavailable: {reason}");
+            self.notify(Event::TrackUnavailable {
+                track_id,
+                reason: reason.to_string(),
+            });
         }
     }
 
@@ -1229,18 +2158,22 @@ impl Player {
     /// * Audio device fails to open
     /// * Device is no longer available
     pub fn play(&mut self) -> Result<()> {
-        // Ensure the audio device is open.
-        self.start()?;
+        // Ensure the audio device is open. If it cannot be opened, keep trying in
+        // the background and start playing when it becomes available.
+        if let Err(e) = self.start() {
+            self.begin_recovery(true);
+            return Err(e);
+        }
 
         if !self.is_playing() {
             debug!("starting playback");
             let original_volume = self.ramp_volume(0.0);
 
-            let pos = {
-                let sink_mut = self.sink_mut()?;
-                sink_mut.play();
-                sink_mut.get_pos()
-            };
+            self.sink_mut()?.play();
+            if let Some(output) = &self.output {
+                output.set_paused(false);
+            }
+            let pos = self.get_pos();
 
             // Gradually ramp up to prevent popping
             self.ramp_volume(original_volume);
@@ -1287,10 +2220,17 @@ impl Player {
     /// Returns error if audio device is not open.
     pub fn pause(&mut self) {
         debug!("pausing playback");
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.resume = false;
+        }
+
         let original_volume = self.ramp_volume(0.0);
 
         // Don't care if the sink is already dropped: we're already "paused".
         let _ = self.sink_mut().map(|sink| sink.pause());
+        if let Some(output) = &self.output {
+            output.set_paused(true);
+        }
         self.notify(Event::Pause);
 
         // Reset the volume to its original value.
@@ -1350,11 +2290,13 @@ impl Player {
 
     /// Replaces the entire playback queue.
     ///
+    /// * Bookmarks the current episode, if any
     /// * Clears current queue and playback state
     /// * Sets queue to the provided track order
     /// * Resets position to start
     /// * Clears skip track list
     pub fn set_queue(&mut self, tracks: Vec<Track>) {
+        self.bookmark_outgoing();
         self.clear();
         self.position = 0;
         self.queue = tracks;
@@ -1419,8 +2361,87 @@ impl Player {
 
         // Set the new queue and clear the current track and preloaded track.
         self.queue = new_queue;
+        self.drop_preload();
+    }
+
+    /// Drops the preloaded track from the output queue.
+    ///
+    /// Also stops the current track from crossfading into it.
+    fn drop_preload(&mut self) {
         self.preload_rx = None;
         self.sources.as_mut().map(|sources| sources.clear());
+        if let Some(handle) = self.current_crossfade.as_ref() {
+            handle.detach();
+        }
+        self.preload_crossfade = None;
+    }
+
+    /// Returns placeholders to resolve, nearest to the current position first.
+    ///
+    /// Looks ahead of the current position first, then behind it. Tracks marked
+    /// unavailable are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of tracks to return
+    ///
+    /// # Returns
+    ///
+    /// The type and IDs of up to `limit` tracks of the same type, or `None` if all
+    /// tracks are resolved.
+    #[must_use]
+    pub fn unresolved(&self, limit: usize) -> Option<(TrackType, Vec<TrackId>)> {
+        let position = self.position.min(self.queue.len());
+        let mut placeholders = self.queue[position..]
+            .iter()
+            .chain(self.queue[..position].iter().rev())
+            .filter(|track| !track.is_resolved() && !self.skip_tracks.contains(&track.id()))
+            .peekable();
+
+        let typ = placeholders.peek().map(|track| track.typ())?;
+        let mut ids = Vec::with_capacity(limit);
+        for track in placeholders.filter(|track| track.typ() == typ) {
+            if ids.len() >= limit {
+                break;
+            }
+            if !ids.contains(&track.id()) {
+                ids.push(track.id());
+            }
+        }
+
+        Some((typ, ids))
+    }
+
+    /// Resolves placeholders with track information from the gateway.
+    ///
+    /// All placeholders of a track are resolved, as tracks may be queued more than once.
+    /// Requested tracks that the gateway did not return are marked unavailable.
+    ///
+    /// # Arguments
+    ///
+    /// * `typ` - Type of the tracks that were requested
+    /// * `requested` - IDs of the tracks that were requested
+    /// * `items` - Track information as returned by the gateway
+    pub fn resolve_tracks(&mut self, typ: TrackType, requested: &[TrackId], items: gateway::Queue) {
+        let items: HashMap<_, _> = items
+            .into_iter()
+            .filter(|item| item.track_type() == typ)
+            .map(|item| (item.id(), item))
+            .collect();
+
+        for track in &mut self.queue {
+            if !track.is_resolved() && track.typ() == typ {
+                if let Some(item) = items.get(&track.id()) {
+                    track.resolve(item.clone());
+                }
+            }
+        }
+
+        for id in requested {
+            if !items.contains_key(id) {
+                self.mark_unavailable(*id, "not available from Deezer");
+            }
+        }
     }
 
     /// Adds tracks to the end of the queue.
@@ -1445,14 +2466,17 @@ impl Player {
         }
 
         info!("setting playlist position to {target}");
+        self.bookmark_outgoing();
 
         // If we want to skip to the next track, and the current track is completely downloaded,
         // then don't clear the queue but seek to the end of the current track. This way we don't
         // need to drop the preload. This only works if the player is playing: only then does the
-        // playback loop advance to the next track.
+        // playback loop advance to the next track. Episodes are not skipped like this, because
+        // they would be bookmarked as played.
         if target == self.position.saturating_add(1)
             && self.preload_rx.is_some()
             && self.is_playing()
+            && !self.track().is_some_and(Track::is_podcast)
         {
             match self.set_progress(Percentage::ONE_HUNDRED) {
                 Ok(()) => return,
@@ -1514,6 +2538,15 @@ impl Player {
         self.playing_since = Duration::ZERO;
         self.current_rx = None;
         self.preload_rx = None;
+        self.current_bit_perfect = false;
+        self.preload_bit_perfect = false;
+        self.current_latency = Duration::ZERO;
+        self.preload_latency = Duration::ZERO;
+        self.current_offset = Duration::ZERO;
+        self.current_limiting = Limiting::default();
+        self.preload_limiting = Limiting::default();
+        self.current_crossfade = None;
+        self.preload_crossfade = None;
     }
 
     /// Returns the current repeat mode.
@@ -1534,15 +2567,14 @@ impl Player {
 
         if repeat_mode == RepeatMode::One {
             // This only clears the preloaded track.
-            self.sources.as_mut().map(|sources| sources.clear());
-            self.preload_rx = None;
+            self.drop_preload();
         }
     }
 
     /// Returns the last volume setting as a percentage.
     ///
-    /// Returns the raw volume value that was set, before logarithmic scaling is applied.
-    /// The actual audio output uses logarithmic scaling to match human perception.
+    /// Returns the raw volume value that was set, before the volume curve is applied.
+    /// The actual audio output follows the curve to match human perception.
     ///
     /// # Returns
     ///
@@ -1556,54 +2588,21 @@ impl Player {
         self.volume
     }
 
-    /// Applies logarithmic scaling to a linear volume value.
-    ///
-    /// Converts a linear volume input (0.0 to 1.0) to a logarithmic scale that better
-    /// matches human perception of loudness. Uses a 60 dB dynamic range with smooth
-    /// transitions:
-    /// * Main range: Exponential curve for natural volume perception
-    /// * Low range (< 10%): Linear scaling for fine control near silence
-    /// * Full range: Smooth transitions between all volume levels
+    /// Sets playback volume, scaled by the configured volume curve.
     ///
-    /// # Arguments
-    ///
-    /// * `volume` - Linear volume value between 0.0 and 1.0
-    ///
-    /// # Returns
-    ///
-    /// Logarithmically scaled volume value between 0.0 and 1.0
-    ///
-    /// # Formula
-    ///
-    /// For v > 0.0 and v < 1.0:
-    /// ```text
-    /// amplitude = exp(6.908 * v) / 1000
-    /// if v < 0.1: amplitude *= v * 10
-    /// ```
-    ///
-    /// Based on research from: <https://www.dr-lex.be/info-stuff/volumecontrols.html>
-    #[must_use]
-    fn log_volume(volume: f32) -> f32 {
-        let mut amplitude = volume;
-        if amplitude > 0.0 && amplitude < UNITY_GAIN {
-            amplitude =
-                f32::exp(Self::LOG_VOLUME_GROWTH_RATE * volume) / Self::LOG_VOLUME_SCALE_FACTOR;
-            if volume < 0.1 {
-                amplitude *= volume * 10.0;
-            }
-        }
-
-        amplitude
-    }
-
-    /// Sets playback volume with logarithmic scaling.
-    ///
-    /// The volume control uses a logarithmic scale that matches human perception:
+    /// By default, the volume control uses a logarithmic scale that matches human
+    /// perception:
     /// * Logarithmic scaling across a 60 dB dynamic range
     /// * Linear fade to zero for very low volumes (< 10%)
     /// * Smooth transitions across the entire range
     /// * Gradual volume ramping to prevent audio popping
     ///
+    /// Linear and cubic curves, other ranges, and maximum and minimum volumes can be
+    /// configured. See [`volume::Scale`].
+    ///
+    /// With a fixed output, the volume is forwarded to the external volume control
+    /// instead, and the output stays at full scale.
+    ///
     /// Volume comparisons use relative epsilon comparison to handle floating-point
     /// imprecision. This prevents issues like:
     /// * Duplicate volume setting operations
@@ -1620,6 +2619,10 @@ impl Player {
     ///
     /// * `target` - Target volume percentage (0.0 to 1.0)
     pub fn set_volume(&mut self, target: Percentage) -> Percentage {
+        if self.fixed_volume {
+            return self.set_external_volume(target);
+        }
+
         // Check if the volume is already set to the target value:
         // Deezer sends the same volume on every status update, even if it hasn't changed.
         let current = self.volume;
@@ -1636,8 +2639,9 @@ impl Player {
             let old = Percentage::from_ratio(self.ramp_volume(target));
             if target > 0.0 && target < 1.0 {
                 debug!(
-                    "volume scaled logarithmically to {}",
-                    Self::log_volume(target)
+                    "volume scaled by {} curve to {}",
+                    self.volume_scale.curve(),
+                    self.volume_scale.amplitude(target)
                 );
             }
             old
@@ -1646,9 +2650,53 @@ impl Player {
         }
     }
 
+    /// Sets the volume of a fixed output.
+    ///
+    /// Forwards the volume to the external volume control, if any. In the output, only
+    /// the listening level for loudness compensation changes.
+    ///
+    /// # Returns
+    ///
+    /// Returns the previous volume.
+    fn set_external_volume(&mut self, target: Percentage) -> Percentage {
+        let volume = target.as_ratio();
+        let amplitude = self.volume_scale.amplitude(volume);
+
+        // Forward even if unchanged, so that the external volume control is set when
+        // connecting. The mixer skips repeats itself.
+        if let Some(mixer) = self.mixer.as_ref() {
+            mixer.set_volume(self.volume_scale.limit(volume), amplitude);
+        }
+
+        let current = self.volume;
+        if target != current {
+            info!("setting external volume to {target}");
+            self.volume = target;
+            self.dithered_volume.set_level(amplitude);
+        }
+
+        current
+    }
+
+    /// Returns the output gain at a volume during a volume ramp.
+    ///
+    /// With a fixed output, only fades between full scale and silence, relative to the
+    /// highest volume at either end of the ramp. Otherwise, follows the volume curve.
+    fn ramp_gain(&self, volume: f32, reference: f32) -> f32 {
+        if !self.fixed_volume {
+            return self.volume_scale.amplitude(volume);
+        }
+
+        if reference > 0.0 {
+            volume / reference
+        } else {
+            UNITY_GAIN
+        }
+    }
+
     /// Gradually changes audio volume over a short duration to prevent popping.
     ///
-    /// Applies a logarithmic volume ramp between the current and target volumes over
+    /// Applies a volume ramp along the volume curve between the current and target volumes over
     /// `FADE_DURATION` milliseconds. This prevents audio artifacts that can occur with
     /// sudden volume changes.
     ///
@@ -1673,6 +2721,7 @@ impl Player {
         {
             // Store the unscaled volume setting for playback reporting.
             self.volume = Percentage::from_ratio(target);
+            let reference = original_volume.max(target);
 
             // Only ramp if there is a current audio stream
             if self.current_rx.is_some() {
@@ -1680,8 +2729,8 @@ impl Player {
                 for i in 1..millis {
                     let progress = i.to_f32_lossy() / millis.to_f32_lossy();
                     let faded = original_volume * (1.0 - progress) + target * progress;
-                    let log_faded = Self::log_volume(faded);
-                    self.dithered_volume.set_volume(log_faded);
+                    let gain = self.ramp_gain(faded, reference);
+                    self.dithered_volume.set_volume(gain);
 
                     // This blocks the current thread for 1 ms, but is better than making the
                     // function async and waiting for the future to complete.
@@ -1689,8 +2738,8 @@ impl Player {
                 }
             }
 
-            let log_target = Self::log_volume(target);
-            self.dithered_volume.set_volume(log_target);
+            let gain = self.ramp_gain(target, reference);
+            self.dithered_volume.set_volume(gain);
 
             if let Some(dither_bits) = self.dithered_volume.effective_bit_depth() {
                 if target > 0.0 {
@@ -1721,15 +2770,74 @@ impl Player {
                     return Some(Percentage::ZERO);
                 }
 
-                // The progress is the difference between the current position of the sink, which
-                // is the total duration played, and the time the current track started playing.
                 let duration = track.duration()?;
-                let progress = self.get_pos().saturating_sub(self.playing_since);
+                let progress = self.elapsed();
                 Some(Percentage::from_ratio(progress.div_duration_f32(duration)))
             }
         })
     }
 
+    /// Returns the playback position within the current track.
+    ///
+    /// The position is the difference between the current position of the sink, which is
+    /// the total duration played, and the time the current track started playing, from
+    /// where it was loaded. Processing latency delays the audio further.
+    fn elapsed(&self) -> Duration {
+        self.get_pos()
+            .saturating_sub(self.playing_since)
+            .saturating_sub(self.current_latency)
+            .saturating_add(self.current_offset)
+    }
+
+    /// Bookmarks the playback position of the current episode.
+    ///
+    /// Stores the bookmark to file when it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `force` - Whether to bookmark small changes, like when pausing
+    ///
+    /// # Returns
+    ///
+    /// The episodes and their new bookmarks: those of episodes that ended or were
+    /// skipped since the last call, followed by that of the current episode if its
+    /// position changed enough.
+    pub fn bookmark(&mut self, force: bool) -> Vec<(TrackId, Bookmark)> {
+        let mut bookmarks = std::mem::take(&mut self.outgoing_bookmarks);
+        bookmarks.extend(self.bookmark_current(force));
+        bookmarks
+    }
+
+    /// Bookmarks the current episode before it is replaced, whatever its position.
+    ///
+    /// The episode ending or being skipped is not seen by [`bookmark`](Self::bookmark)
+    /// anymore, so the bookmark is kept until then.
+    fn bookmark_outgoing(&mut self) {
+        if let Some(bookmark) = self.bookmark_current(true) {
+            self.outgoing_bookmarks.push(bookmark);
+        }
+    }
+
+    /// Bookmarks the playback position of the current episode, if it changed enough.
+    fn bookmark_current(&mut self, force: bool) -> Option<(TrackId, Bookmark)> {
+        if !self.is_loaded() {
+            return None;
+        }
+
+        let position = self.elapsed();
+        let (id, duration) = self
+            .track()
+            .filter(|track| track.is_podcast())
+            .map(|track| (track.id(), track.duration()))?;
+
+        let bookmark = self.bookmarks.update(id, position, duration, force)?;
+        if let Err(e) = self.bookmarks.save() {
+            error!("failed to save bookmarks: {e}");
+        }
+
+        Some((id, bookmark))
+    }
+
     /// Returns duration of current track.
     ///
     /// For normal tracks, returns total duration.
@@ -1738,9 +2846,11 @@ impl Player {
     pub fn duration(&self) -> Option<Duration> {
         self.track().and_then(|track| {
             if track.is_livestream() {
-                self.sink
-                    .as_ref()
-                    .map(|sink| sink.get_pos().saturating_sub(self.playing_since))
+                self.is_started().then(|| {
+                    self.get_pos()
+                        .saturating_sub(self.playing_since)
+                        .saturating_sub(self.current_latency)
+                })
             } else {
                 track.duration()
             }
@@ -1819,6 +2929,7 @@ impl Player {
                 Ok(()) => {
                     // Reset the playing time to zero, as the sink will now reset it also.
                     self.playing_since = Duration::ZERO;
+                    self.current_offset = Duration::ZERO;
                     self.deferred_seek = None;
                 }
                 Err(e) => {
@@ -1856,6 +2967,194 @@ impl Player {
         self.normalization = normalization;
     }
 
+    /// Sets whether to normalize by track or by album.
+    ///
+    /// Applies to tracks loaded after this call.
+    #[inline]
+    pub fn set_normalization_mode(&mut self, mode: normalize::Mode) {
+        self.normalization_mode = mode;
+    }
+
+    /// Sets whether to normalize podcast episodes with automatic gain control.
+    ///
+    /// Applies to episodes loaded after this call.
+    #[inline]
+    pub fn set_normalize_episodes(&mut self, normalize_episodes: bool) {
+        self.normalize_episodes = normalize_episodes;
+    }
+
+    /// Returns the loudness of the album that the track at a queue position belongs to.
+    ///
+    /// Depending on the normalization mode, the album consists of:
+    /// * Track: nothing, so that tracks are normalized individually
+    /// * Album: all tracks in the queue from the same album
+    /// * Auto: the consecutive tracks from the same album around the position, if any
+    ///
+    /// Returns `None` when the track should be normalized individually, or when no
+    /// track of the album has gain information.
+    fn album_gain(&self, position: usize) -> Option<f32> {
+        let track = self.queue.get(position)?;
+        let album: Vec<_> = match self.normalization_mode {
+            normalize::Mode::Track => return None,
+            normalize::Mode::Album => self
+                .queue
+                .iter()
+                .filter(|other| track.is_same_album(other))
+                .collect(),
+            normalize::Mode::Auto => {
+                let start = self.queue[..position]
+                    .iter()
+                    .rposition(|other| !track.is_same_album(other))
+                    .map_or(0, |i| i + 1);
+                let end = self.queue[position..]
+                    .iter()
+                    .position(|other| !track.is_same_album(other))
+                    .map_or(self.queue.len(), |i| position + i);
+                if end - start < 2 {
+                    return None;
+                }
+                self.queue[start..end].iter().collect()
+            }
+        };
+
+        // Combine the loudness of all tracks by energy, weighted by duration, so that
+        // long tracks count for more.
+        let (energy, weight) = album
+            .iter()
+            .filter_map(|track| {
+                let gain = track.gain()?;
+                let weight = track
+                    .duration()
+                    .map_or(1.0, |duration| duration.as_secs_f64().max(1.0));
+                Some((gain, weight))
+            })
+            .fold((0.0, 0.0), |(energy, weight), (gain, duration)| {
+                (
+                    energy + duration * 10.0_f64.powf(f64::from(gain) / 10.0),
+                    weight + duration,
+                )
+            });
+
+        if weight > 0.0 {
+            #[expect(clippy::cast_possible_truncation)]
+            let loudness = (10.0 * (energy / weight).log10()) as f32;
+            Some(loudness)
+        } else {
+            None
+        }
+    }
+
+    /// Switches the equalizer on or off.
+    ///
+    /// Applies immediately to the current and preloaded tracks. Switching on reloads
+    /// tracks that were loaded bit-perfect.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FailedPrecondition` when switching on without an equalizer configured.
+    pub fn set_equalizer(&mut self, enabled: bool) -> Result<()> {
+        if self.equalizer.is_none() {
+            if enabled {
+                return Err(Error::failed_precondition("no equalizer configured"));
+            }
+            return Ok(());
+        }
+
+        info!("equalizer {}", if enabled { "on" } else { "off" });
+        self.equalizer_enabled.store(enabled, Ordering::Relaxed);
+        if enabled {
+            self.reload_bit_perfect();
+        }
+        Ok(())
+    }
+
+    /// Reloads the current and preloaded tracks if they were loaded bit-perfect.
+    ///
+    /// Bit-perfect tracks are loaded without output limiter and dither. Once audio
+    /// processing is switched on, they have to be loaded again to get them back. The
+    /// current track resumes at its position.
+    fn reload_bit_perfect(&mut self) {
+        if self.current_bit_perfect {
+            debug!("reloading bit-perfect track to process it");
+            if self.is_loaded() && !self.track().is_some_and(Track::is_livestream) {
+                self.deferred_seek = Some(self.elapsed());
+            }
+            self.clear();
+        } else if self.preload_bit_perfect {
+            self.drop_preload();
+            self.preload_bit_perfect = false;
+        }
+    }
+
+    /// Returns whether the equalizer is configured and switched on.
+    #[must_use]
+    #[inline]
+    pub fn equalizer_enabled(&self) -> bool {
+        self.equalizer.is_some() && self.equalizer_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Switches headphone crossfeed on or off.
+    ///
+    /// Applies immediately to the current and preloaded tracks. Switching on reloads
+    /// tracks that were loaded bit-perfect.
+    pub fn set_crossfeed(&mut self, enabled: bool) {
+        if enabled {
+            info!("crossfeed on ({} preset)", self.crossfeed_preset);
+        } else {
+            info!("crossfeed off");
+        }
+        self.crossfeed_enabled.store(enabled, Ordering::Relaxed);
+        if enabled {
+            self.reload_bit_perfect();
+        }
+    }
+
+    /// Returns whether headphone crossfeed is switched on.
+    #[must_use]
+    #[inline]
+    pub fn crossfeed_enabled(&self) -> bool {
+        self.crossfeed_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Switches night mode compression on or off.
+    ///
+    /// Applies immediately to the current and preloaded tracks, fading in or out.
+    /// Switching on reloads tracks that were loaded bit-perfect.
+    pub fn set_night_mode(&mut self, enabled: bool) {
+        if enabled {
+            info!("night mode on");
+        } else {
+            info!("night mode off");
+        }
+        self.night_mode.store(enabled, Ordering::Relaxed);
+        if enabled {
+            self.reload_bit_perfect();
+        }
+    }
+
+    /// Returns whether night mode compression is switched on.
+    #[must_use]
+    #[inline]
+    pub fn night_mode(&self) -> bool {
+        self.night_mode.load(Ordering::Relaxed)
+    }
+
+    /// Switches night mode on or off when entering or leaving its time window.
+    fn schedule_night_mode(&mut self) {
+        let Some(hours) = self.night_mode_hours else {
+            return;
+        };
+
+        let now = chrono::Local::now().time();
+        let night_mode_time = hours.contains(now);
+        if self.night_mode_time != Some(night_mode_time) {
+            self.night_mode_time = Some(night_mode_time);
+            if night_mode_time != self.night_mode() {
+                self.set_night_mode(night_mode_time);
+            }
+        }
+    }
+
     /// Sets target gain for volume normalization.
     ///
     /// Logs info message if normalization is enabled.
@@ -1886,6 +3185,65 @@ impl Player {
         self.normalization
     }
 
+    /// Returns whether bit-perfect mode is enabled.
+    #[must_use]
+    #[inline]
+    pub fn bit_perfect(&self) -> bool {
+        self.bit_perfect
+    }
+
+    /// Returns whether the current track plays bit-perfect.
+    ///
+    /// True when bit-perfect mode is enabled, and the samples of the current track reach
+    /// the output unchanged: at its native sample rate, without normalization,
+    /// equalization or equal-loudness compensation, and at full volume.
+    #[must_use]
+    pub fn is_bit_perfect(&self) -> bool {
+        self.current_bit_perfect
+            && self.convolution.is_none()
+            && !self.equalizer_enabled()
+            && !self.crossfeed_enabled()
+            && !self.night_mode()
+            && self.dithered_volume.is_bypassed()
+    }
+
+    /// Returns the gain reduction by the normalization limiter of the current track.
+    ///
+    /// Returns `None` when the current track is not limited after normalization, for
+    /// example because it only needed attenuation.
+    #[must_use]
+    pub fn normalization_limiting(&self) -> Option<&limiter::Stats> {
+        self.current_limiting.normalization.as_deref()
+    }
+
+    /// Returns the gain reduction by the output limiter of the current track.
+    ///
+    /// Returns `None` when the current track plays bit-perfect.
+    #[must_use]
+    pub fn output_limiting(&self) -> Option<&limiter::Stats> {
+        self.current_limiting.output.as_deref()
+    }
+
+    /// Logs the gain reduction of the current track, if it was limited.
+    fn report_limiting(&self) {
+        let Some(track) = self.track() else {
+            return;
+        };
+
+        let stages = [
+            (
+                "normalization",
+                self.current_limiting.normalization.as_deref(),
+            ),
+            ("output", self.current_limiting.output.as_deref()),
+        ];
+        for (stage, stats) in stages {
+            if let Some(stats) = stats.filter(|stats| stats.is_limited()) {
+                debug!("{stage} limiter reduced {} {track} {stats}", track.typ());
+            }
+        }
+    }
+
     /// Returns current license token.
     #[must_use]
     #[inline]
@@ -1048,6 +1048,33 @@ impl fmt::Display for RepeatMode {
     }
 }
 
+/// Parses a string into a repeat mode, ignoring case.
+///
+/// # Examples
+///
+/// ```rust
+/// assert_eq!("none".parse()?, RepeatMode::None);
+/// assert_eq!("All".parse()?, RepeatMode::All);
+/// assert_eq!("one".parse()?, RepeatMode::One);
+///
+/// // Unknown values parse to Unrecognized
+/// assert_eq!("invalid".parse()?, RepeatMode::Unrecognized);
+/// ```
+impl FromStr for RepeatMode {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let variant = match s.to_lowercase().as_str() {
+            "none" => RepeatMode::None,
+            "all" => RepeatMode::All,
+            "one" => RepeatMode::One,
+            _ => RepeatMode::Unrecognized,
+        };
+
+        Ok(variant)
+    }
+}
+
 #[expect(clippy::doc_markdown)]
 /// # Quality Levels
 ///
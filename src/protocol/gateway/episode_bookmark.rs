@@ -0,0 +1,58 @@
+//! Podcast episode bookmarks.
+//!
+//! This module handles storing the playback position of podcast episodes with
+//! Deezer, so that other clients resume episodes where pleezer left off.
+//!
+//! # Wire Format
+//!
+//! Request:
+//! ```json
+//! {
+//!     "episode_id": "123456789",
+//!     "offset": 1234
+//! }
+//! ```
+//!
+//! Response contains an acknowledgement, that carries no information beyond the
+//! absence of errors.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deezer::gateway::episode_bookmark::Request;
+//!
+//! let request = Request {
+//!     episode_id: 123456789.try_into()?,
+//!     offset: 1234,
+//! };
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+use super::Method;
+use crate::track::TrackId;
+
+/// Gateway method name for setting episode bookmarks.
+impl Method for EpisodeBookmark {
+    const METHOD: &'static str = "episode.setBookmark";
+}
+
+/// Acknowledgement of a bookmark.
+#[derive(Clone, PartialEq, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct EpisodeBookmark(pub serde_json::Value);
+
+/// Request parameters for setting an episode bookmark.
+#[serde_as]
+#[derive(Clone, Eq, PartialEq, Serialize, Debug, Hash)]
+pub struct Request {
+    /// ID of the episode.
+    #[serde_as(as = "DisplayFromStr")]
+    pub episode_id: TrackId,
+
+    /// Playback position in seconds.
+    ///
+    /// An offset at the end of the episode marks it as played.
+    pub offset: u64,
+}
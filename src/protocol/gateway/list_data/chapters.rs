@@ -0,0 +1,97 @@
+//! Audio book chapter handling for Deezer's gateway API.
+//!
+//! Provides chapter-specific wrappers and types, including:
+//! * Chapter metadata (title, audio book, author, duration)
+//! * Availability status
+//! * Audio book artwork
+//!
+//! Chapters differ from episodes in several ways:
+//! * Use encrypted downloads like songs, rather than direct streaming
+//! * Include audio book and author metadata instead of show metadata
+//! * Belong to an audio book, that plays gaplessly like an album
+//!
+//! # Wire Format
+//!
+//! Response format:
+//! ```json
+//! {
+//!     "CHAPTER_ID": "123456",
+//!     "AVAILABLE": true,
+//!     "DURATION": "1800",
+//!     "CHAPTER_TITLE": "Chapter Title",
+//!     "AUDIOBOOK_ID": "654321",
+//!     "AUDIOBOOK_TITLE": "Audio Book Title",
+//!     "AUTHOR_NAME": "Author Name",
+//!     "AUDIOBOOK_IMAGE_MD5": "cover_id",
+//!     "TRACK_TOKEN": "secret_token",
+//!     "TRACK_TOKEN_EXPIRE": "1234567890"
+//! }
+//! ```
+
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+use super::{ListData, Method};
+use crate::track::TrackId;
+
+/// Gateway method name for retrieving chapters.
+///
+/// This endpoint returns detailed chapter data including:
+/// * Chapter metadata
+/// * Audio book information
+/// * Authentication tokens
+/// * Regional availability
+impl Method for ChapterData {
+    const METHOD: &'static str = "chapter.getListData";
+}
+
+/// Wrapper for chapter data.
+///
+/// Contains the same track information as [`ListData`] but specifically
+/// for audio book chapters. The wrapper allows specialized handling while
+/// reusing the underlying data structure.
+#[derive(Clone, PartialEq, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct ChapterData(pub ListData);
+
+/// Provides access to the underlying chapter data.
+///
+/// Allows transparent access to the chapter fields while maintaining
+/// type safety for chapter-specific operations.
+impl Deref for ChapterData {
+    type Target = ListData;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Request parameters for chapter list data.
+///
+/// Used to request information for multiple chapters in a single query.
+/// Chapters must be available in the user's region to be retrieved.
+///
+/// # Example
+///
+/// ```rust
+/// use deezer::gateway::{Request, TrackId};
+///
+/// let request = Request {
+///     chapter_ids: vec![123456.into(), 789012.into()],
+/// };
+/// ```
+#[serde_as]
+#[derive(Clone, Eq, PartialEq, Serialize, Debug, Hash)]
+pub struct Request {
+    /// List of chapter IDs to fetch information for.
+    ///
+    /// Each ID must be:
+    /// * Non-zero
+    /// * Valid within Deezer's catalog
+    /// * Available in user's region
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub chapter_ids: Vec<TrackId>,
+}
@@ -4,12 +4,14 @@
 //! * Songs - Regular music tracks
 //! * Episodes - Podcast episodes
 //! * Livestreams - Radio stations
+//! * Chapters - Audio book chapters
 //!
 //! Content types share common traits but have specialized handling through
 //! type-specific wrappers in submodules:
 //! * [`songs`] - Music track handling
 //! * [`episodes`] - Podcast episode handling
 //! * [`livestream`] - Radio stream handling
+//! * [`chapters`] - Audio book chapter handling
 //!
 //! # Content Types
 //!
@@ -31,6 +33,11 @@
 //!   - Codec selection
 //!   - No duration/progress
 //!
+//! * Chapters
+//!   - Audio book/author metadata
+//!   - Encrypted content
+//!   - Availability flags
+//!
 //! # Wire Format
 //!
 //! Each content type has its own response format:
@@ -64,11 +71,23 @@
 //!     ...
 //! }
 //! ```
+//!
+//! ## Chapters
+//! ```json
+//! {
+//!     "CHAPTER_ID": "123456",
+//!     "CHAPTER_TITLE": "Chapter Title",
+//!     "AUDIOBOOK_TITLE": "Audio Book Title",
+//!     ...
+//! }
+//! ```
 
+pub mod chapters;
 pub mod episodes;
 pub mod livestream;
 pub mod songs;
 
+pub use chapters::ChapterData;
 pub use episodes::EpisodeData;
 pub use livestream::LivestreamData;
 pub use songs::SongData;
@@ -87,7 +106,7 @@ use serde_with::{
 use url::Url;
 use veil::Redact;
 
-use crate::track::TrackId;
+use crate::track::{TrackId, TrackType};
 
 use super::Method;
 
@@ -98,6 +117,7 @@ use super::Method;
 /// * User-uploaded songs
 /// * Podcast episodes
 /// * Live radio streams
+/// * Audio book chapters
 pub type Queue = Vec<ListData>;
 
 /// Detailed track information from Deezer's gateway.
@@ -114,6 +134,7 @@ pub type Queue = Vec<ListData>;
 /// * Songs - Regular music tracks
 /// * Episodes - Podcast episodes
 /// * Livestreams - Radio stations
+/// * Chapters - Audio book chapters
 ///
 /// # Fields
 ///
@@ -168,6 +189,13 @@ pub enum ListData {
         #[serde(rename = "ALB_TITLE")]
         album_title: String,
 
+        /// Album identifier.
+        ///
+        /// Used to detect consecutive songs from the same album.
+        #[serde(rename = "ALB_ID")]
+        #[serde_as(as = "Option<PickFirst<(DisplayFromStr, _)>>")]
+        album_id: Option<u64>,
+
         /// Album cover identifier.
         ///
         /// When available, this ID can be used to construct image URLs:
@@ -384,6 +412,82 @@ pub enum ListData {
         #[serde(default)]
         available: bool,
     },
+
+    /// Audio book chapter
+    #[serde(rename = "chapter")]
+    Chapter {
+        /// Unique chapter identifier.
+        #[serde(rename = "CHAPTER_ID")]
+        #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+        id: TrackId,
+
+        /// Whether the chapter is available in the user's region
+        #[serde(rename = "AVAILABLE")]
+        #[serde(default)]
+        available: bool,
+
+        /// Chapter duration.
+        ///
+        /// The actual playback length of the chapter, parsed from seconds.
+        /// Used for progress calculation and UI display.
+        /// Defaults to zero duration if not provided or invalid.
+        #[serde(default)]
+        #[serde(rename = "DURATION")]
+        #[serde_as(as = "DurationSeconds<String, Flexible>")]
+        duration: Duration,
+
+        /// Chapter title.
+        #[serde(default)]
+        #[serde(rename = "CHAPTER_TITLE")]
+        title: String,
+
+        /// Audio book identifier.
+        ///
+        /// Used to play consecutive chapters of the same audio book gaplessly.
+        #[serde(rename = "AUDIOBOOK_ID")]
+        #[serde_as(as = "Option<PickFirst<(DisplayFromStr, _)>>")]
+        audiobook_id: Option<u64>,
+
+        /// Audio book title.
+        #[serde(default)]
+        #[serde(rename = "AUDIOBOOK_TITLE")]
+        audiobook_title: String,
+
+        /// Author name.
+        ///
+        /// For audio books with multiple authors, this contains only the main author.
+        #[serde(default)]
+        #[serde(rename = "AUTHOR_NAME")]
+        author: String,
+
+        /// Audio book cover identifier.
+        ///
+        /// When available, this ID can be used to construct image URLs:
+        /// ```text
+        /// https://cdn-images.dzcdn.net/images/cover/{audiobook_cover}/{resolution}x{resolution}.{format}
+        /// ```
+        ///
+        /// Defaults to an empty string when no cover is available.
+        #[serde(default)]
+        #[serde(rename = "AUDIOBOOK_IMAGE_MD5")]
+        audiobook_cover: String,
+
+        /// Authentication token for chapter playback.
+        ///
+        /// Like for songs, this token is required to access the encrypted media
+        /// content, has a limited validity period, and should be kept secure.
+        #[serde(rename = "TRACK_TOKEN")]
+        #[redact]
+        track_token: String,
+
+        /// Token expiration timestamp.
+        ///
+        /// The time at which the `track_token` becomes invalid.
+        /// New tokens should be requested after expiration.
+        #[serde(rename = "TRACK_TOKEN_EXPIRE")]
+        #[serde_as(as = "TimestampSeconds<i64, Flexible>")]
+        expiry: SystemTime,
+    },
 }
 
 /// Converts string "1"/"0" to boolean values.
@@ -413,6 +517,7 @@ impl ListData {
     /// * "song" - Regular music track
     /// * "episode" - Podcast episode
     /// * "livestream" - Radio station
+    /// * "chapter" - Audio book chapter
     #[must_use]
     #[inline]
     pub const fn typ(&self) -> &'static str {
@@ -420,6 +525,7 @@ impl ListData {
             ListData::Song { .. } => "song",
             ListData::Episode { .. } => "episode",
             ListData::Livestream { .. } => "livestream",
+            ListData::Chapter { .. } => "chapter",
         }
     }
 
@@ -434,7 +540,20 @@ impl ListData {
         match self {
             ListData::Song { id, .. }
             | ListData::Episode { id, .. }
-            | ListData::Livestream { id, .. } => *id,
+            | ListData::Livestream { id, .. }
+            | ListData::Chapter { id, .. } => *id,
+        }
+    }
+
+    /// Returns the type of this content as a track type.
+    #[must_use]
+    #[inline]
+    pub fn track_type(&self) -> TrackType {
+        match self {
+            ListData::Song { .. } => TrackType::Song,
+            ListData::Episode { .. } => TrackType::Episode,
+            ListData::Livestream { .. } => TrackType::Livestream,
+            ListData::Chapter { .. } => TrackType::Chapter,
         }
     }
 
@@ -445,7 +564,9 @@ impl ListData {
     #[inline]
     pub fn title(&self) -> Option<&str> {
         match self {
-            ListData::Song { title, .. } | ListData::Episode { title, .. } => Some(title.as_str()),
+            ListData::Song { title, .. }
+            | ListData::Episode { title, .. }
+            | ListData::Chapter { title, .. } => Some(title.as_str()),
             ListData::Livestream { .. } => None,
         }
     }
@@ -456,6 +577,7 @@ impl ListData {
     /// * Song artist for songs
     /// * Podcast name for episodes
     /// * Station name for livestreams
+    /// * Author for chapters
     #[must_use]
     #[inline]
     pub fn artist(&self) -> &str {
@@ -463,6 +585,7 @@ impl ListData {
             ListData::Song { artist, .. } => artist.as_str(),
             ListData::Episode { podcast_title, .. } => podcast_title.as_str(),
             ListData::Livestream { title, .. } => title.as_str(),
+            ListData::Chapter { author, .. } => author.as_str(),
         }
     }
 
@@ -472,6 +595,7 @@ impl ListData {
     /// * Album cover ID for songs
     /// * Podcast artwork ID for episodes
     /// * Station logo ID for livestreams
+    /// * Audio book cover ID for chapters
     #[must_use]
     #[inline]
     pub fn cover_id(&self) -> &str {
@@ -481,6 +605,25 @@ impl ListData {
             ListData::Livestream {
                 live_stream_art, ..
             } => live_stream_art,
+            ListData::Chapter {
+                audiobook_cover, ..
+            } => audiobook_cover,
+        }
+    }
+
+    /// Returns the album identifier of this track.
+    ///
+    /// Returns:
+    /// * Album ID for songs, if available
+    /// * Audio book ID for chapters, if available
+    /// * None for podcasts and livestreams
+    #[must_use]
+    #[inline]
+    pub fn album_id(&self) -> Option<u64> {
+        match self {
+            ListData::Song { album_id, .. } => *album_id,
+            ListData::Chapter { audiobook_id, .. } => *audiobook_id,
+            ListData::Episode { .. } | ListData::Livestream { .. } => None,
         }
     }
 
@@ -489,12 +632,15 @@ impl ListData {
     /// Returns:
     /// * Track duration for songs
     /// * Episode duration for podcasts
+    /// * Chapter duration for audio books
     /// * None for livestreams
     #[must_use]
     #[inline]
     pub fn duration(&self) -> Option<Duration> {
         match self {
-            ListData::Song { duration, .. } | ListData::Episode { duration, .. } => Some(*duration),
+            ListData::Song { duration, .. }
+            | ListData::Episode { duration, .. }
+            | ListData::Chapter { duration, .. } => Some(*duration),
             ListData::Livestream { .. } => None,
         }
     }
@@ -504,14 +650,15 @@ impl ListData {
     /// Returns:
     /// * Songs - Track token for encrypted content
     /// * Episodes - Track token for Deezer CDN
+    /// * Chapters - Track token for encrypted content
     /// * Livestreams - None (uses direct URLs)
     #[must_use]
     #[inline]
     pub fn track_token(&self) -> Option<&str> {
         match self {
-            ListData::Song { track_token, .. } | ListData::Episode { track_token, .. } => {
-                Some(track_token)
-            }
+            ListData::Song { track_token, .. }
+            | ListData::Episode { track_token, .. }
+            | ListData::Chapter { track_token, .. } => Some(track_token),
             ListData::Livestream { .. } => None,
         }
     }
@@ -521,12 +668,15 @@ impl ListData {
     /// Returns:
     /// * Songs - Track token expiry
     /// * Episodes - Track token expiry
+    /// * Chapters - Track token expiry
     /// * Livestreams - None (no token needed)
     #[must_use]
     #[inline]
     pub fn expiry(&self) -> Option<SystemTime> {
         match self {
-            ListData::Song { expiry, .. } | ListData::Episode { expiry, .. } => Some(*expiry),
+            ListData::Song { expiry, .. }
+            | ListData::Episode { expiry, .. }
+            | ListData::Chapter { expiry, .. } => Some(*expiry),
             ListData::Livestream { .. } => None,
         }
     }
@@ -5,12 +5,14 @@
 //! * Authentication tokens ([`arl`])
 //! * User data and settings ([`user_data`])
 //! * Content listings ([`list_data`])
+//! * Podcast episode bookmarks ([`episode_bookmark`])
 //! * Radio stations ([`user_radio`])
 //!
 //! Supports multiple content types:
 //! * Songs - Regular music tracks
 //! * Episodes - Podcast episodes
 //! * Livestreams - Radio stations (future)
+//! * Chapters - Audio book chapters
 //!
 //! # Number Handling
 //!
@@ -39,14 +41,16 @@
 //! ```
 
 pub mod arl;
+pub mod episode_bookmark;
 pub mod list_data;
 pub mod user_data;
 pub mod user_radio;
 
 pub use arl::Arl;
+pub use episode_bookmark::EpisodeBookmark;
 pub use list_data::{
-    EpisodeData, ListData, LivestreamData, LivestreamUrl, LivestreamUrls, Queue, SongData,
-    episodes, livestream, songs,
+    ChapterData, EpisodeData, ListData, LivestreamData, LivestreamUrl, LivestreamUrls, Queue,
+    SongData, chapters, episodes, livestream, songs,
 };
 pub use user_data::{MediaUrl, UserData};
 pub use user_radio::UserRadio;
@@ -253,6 +257,31 @@ impl From<Response<LivestreamData>> for Response<ListData> {
     }
 }
 
+/// Converts chapter responses into list data responses.
+///
+/// This allows chapter data to be handled using the same infrastructure
+/// as other content types while maintaining type safety for chapter-specific
+/// operations.
+impl From<Response<ChapterData>> for Response<ListData> {
+    fn from(response: Response<ChapterData>) -> Self {
+        match response {
+            Response::Paginated { error, results } => {
+                let results = Paginated {
+                    data: results.data.into_iter().map(|data| data.0).collect(),
+                    count: results.count,
+                    total: results.total,
+                    filtered_count: results.filtered_count,
+                };
+                Response::Paginated { error, results }
+            }
+            Response::Unpaginated { error, results } => Response::Unpaginated {
+                error,
+                results: results.into_iter().map(|data| data.0).collect(),
+            },
+        }
+    }
+}
+
 /// Paginated result set from the Deezer gateway API.
 ///
 /// Contains both the actual data items and metadata about the total
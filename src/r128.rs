@@ -0,0 +1,247 @@
+//! Integrated loudness measurement according to EBU R128 (ITU-R BS.1770-4).
+//!
+//! Measures the loudness of tracks that have no gain information, so that they can be
+//! normalized like the rest of the catalogue.
+//!
+//! # Measurement
+//!
+//! 1. Each channel is K-weighted: a high shelf models the acoustic effect of the head,
+//!    and a high-pass filter the reduced sensitivity to low frequencies
+//! 2. Mean squares are collected in blocks of 400 ms, overlapping by 75%
+//! 3. Channels are summed with their weights, surround channels counting for more
+//! 4. Blocks below -70 LUFS are gated, to skip silence
+//! 5. Blocks more than 10 LU below the loudness of the remaining blocks are gated, to
+//!    skip quiet passages
+//! 6. The integrated loudness is the mean of the blocks that pass both gates
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::r128;
+//!
+//! if let Some(lufs) = r128::integrated_loudness(decoder) {
+//!     println!("track loudness: {lufs:.1} LUFS");
+//! }
+//! ```
+
+use std::f64::consts::PI;
+
+use biquad::{Biquad, Coefficients, DirectForm1};
+use rodio::{ChannelCount, SampleRate, Source};
+
+/// Offset in the loudness formula of BS.1770, in LU.
+const LOUDNESS_OFFSET: f64 = -0.691;
+
+/// Blocks quieter than this are gated, in LUFS.
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Blocks this far below the ungated loudness are gated, in LU.
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Number of steps per gating block: 400 ms blocks that start every 100 ms.
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Converts a weighted mean square to loudness in LUFS.
+#[must_use]
+#[inline]
+pub(crate) fn loudness(power: f64) -> f64 {
+    LOUDNESS_OFFSET + 10.0 * power.log10()
+}
+
+/// Returns the weight of a channel in the sum: surround channels count for more.
+///
+/// Channels are in the standard order: left, right, center, then with 5.1 and more, the
+/// LFE, which is excluded, before the surround channels.
+#[must_use]
+#[inline]
+pub(crate) fn channel_weight(channel: usize, channels: usize) -> f64 {
+    /// Index of the LFE channel, in layouts of six channels or more.
+    const LFE: usize = 3;
+
+    match channel {
+        0..=2 => 1.0,
+        LFE if channels >= 6 => 0.0,
+        _ => 1.41,
+    }
+}
+
+/// Measures the integrated loudness of a source in LUFS.
+///
+/// Consumes the source. Returns `None` when the source is silent or too short for a
+/// single gating block.
+#[must_use]
+pub fn integrated_loudness<I>(source: I) -> Option<f32>
+where
+    I: Source,
+{
+    let mut meter = Meter::new(source.channels(), source.sample_rate());
+    for sample in source {
+        meter.push(sample);
+    }
+    meter.integrated_loudness()
+}
+
+/// Incremental loudness meter.
+#[derive(Clone, Debug)]
+pub struct Meter {
+    /// K-weighting filters per channel: the high shelf, then the high-pass.
+    filters: Vec<[DirectForm1<f64>; 2]>,
+
+    /// Weight of each channel in the sum.
+    weights: Vec<f64>,
+
+    /// Sum of squares per channel in the current step.
+    squares: Vec<f64>,
+
+    /// Channel of the next sample.
+    channel: usize,
+
+    /// Frames collected in the current step.
+    frames: usize,
+
+    /// Frames per step of 100 ms.
+    frames_per_step: usize,
+
+    /// Weighted mean square of each step.
+    steps: Vec<f64>,
+}
+
+impl Meter {
+    /// Creates a meter for audio with the given channel count and sample rate.
+    #[must_use]
+    pub fn new(channels: ChannelCount, sample_rate: SampleRate) -> Self {
+        let channels = usize::from(channels.max(1));
+        let sample_rate = f64::from(sample_rate.max(1));
+
+        let (shelf, high_pass) = Self::k_weighting(sample_rate);
+        let filters = (0..channels)
+            .map(|_| [DirectForm1::new(shelf), DirectForm1::new(high_pass)])
+            .collect();
+
+        let weights = (0..channels)
+            .map(|channel| channel_weight(channel, channels))
+            .collect();
+
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames_per_step = (sample_rate / 10.0).round().max(1.0) as usize;
+
+        Self {
+            filters,
+            weights,
+            squares: vec![0.0; channels],
+            channel: 0,
+            frames: 0,
+            frames_per_step,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns the coefficients of the K-weighting filters at a sample rate.
+    ///
+    /// The filters are specified at 48 kHz; these are the analog prototypes
+    /// transformed for any sample rate.
+    pub(crate) fn k_weighting(sample_rate: f64) -> (Coefficients<f64>, Coefficients<f64>) {
+        // Stage 1: high shelf of about +4 dB above 1.5 kHz.
+        let f0 = 1_681.974_450_955_53;
+        let gain_db = 3.999_843_853_973_35;
+        let q = 0.707_175_236_955_42;
+
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10.0_f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.499_666_774_154_542);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Coefficients {
+            b0: (vh + vb * k / q + k * k) / a0,
+            b1: 2.0 * (k * k - vh) / a0,
+            b2: (vh - vb * k / q + k * k) / a0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+        };
+
+        // Stage 2: high-pass at about 38 Hz.
+        let f0 = 38.135_470_876_024_4;
+        let q = 0.500_327_037_323_877;
+
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Coefficients {
+            b0: 1.0,
+            b1: -2.0,
+            b2: 1.0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+        };
+
+        (shelf, high_pass)
+    }
+
+    /// Adds an interleaved sample.
+    #[inline]
+    pub fn push(&mut self, sample: f32) {
+        let [shelf, high_pass] = &mut self.filters[self.channel];
+        let weighted = high_pass.run(shelf.run(f64::from(sample)));
+        self.squares[self.channel] += weighted * weighted;
+
+        self.channel += 1;
+        if self.channel == self.filters.len() {
+            self.channel = 0;
+            self.frames += 1;
+            if self.frames == self.frames_per_step {
+                self.finish_step();
+            }
+        }
+    }
+
+    /// Stores the weighted mean square of the current step and starts the next.
+    fn finish_step(&mut self) {
+        #[expect(clippy::cast_precision_loss)]
+        let frames = self.frames as f64;
+        let power = self
+            .squares
+            .iter()
+            .zip(&self.weights)
+            .map(|(squares, weight)| weight * squares / frames)
+            .sum();
+
+        self.steps.push(power);
+        self.squares.fill(0.0);
+        self.frames = 0;
+    }
+
+    /// Returns the integrated loudness of the audio so far in LUFS.
+    ///
+    /// Returns `None` when the audio is silent or shorter than one gating block.
+    #[must_use]
+    pub fn integrated_loudness(&self) -> Option<f32> {
+        #[expect(clippy::cast_precision_loss)]
+        let blocks: Vec<f64> = self
+            .steps
+            .windows(STEPS_PER_BLOCK)
+            .map(|steps| steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64)
+            .collect();
+
+        let absolute = Self::mean(
+            blocks
+                .iter()
+                .copied()
+                .filter(|&power| loudness(power) > ABSOLUTE_GATE),
+        )?;
+
+        let relative_gate = loudness(absolute) + RELATIVE_GATE;
+        let gated = Self::mean(blocks.iter().copied().filter(|&power| {
+            let loudness = loudness(power);
+            loudness > ABSOLUTE_GATE && loudness > relative_gate
+        }))?;
+
+        #[expect(clippy::cast_possible_truncation)]
+        let integrated = loudness(gated) as f32;
+        Some(integrated)
+    }
+
+    /// Returns the mean of block powers, or `None` when there are none.
+    fn mean(powers: impl Iterator<Item = f64>) -> Option<f64> {
+        let (sum, count) =
+            powers.fold((0.0, 0_u32), |(sum, count), power| (sum + power, count + 1));
+        (count > 0).then_some(sum / f64::from(count))
+    }
+}
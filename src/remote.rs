@@ -32,9 +32,9 @@
 //! Emitted when the track changes
 //!
 //! Common variables for all content:
-//! - `TRACK_TYPE`: Content type ("song", "episode", "livestream")
+//! - `TRACK_TYPE`: Content type ("song", "episode", "livestream", "chapter")
 //! - `TRACK_ID`: Content identifier
-//! - `ARTIST`: Artist name/podcast title/station name
+//! - `ARTIST`: Artist name/podcast title/station name/author
 //! - `COVER_ID`: Cover art identifier
 //! - `FORMAT`: Input format and bitrate (e.g. "MP3 320K", "FLAC 1.234M")
 //! - `DECODER`: Decoded format including:
@@ -42,12 +42,12 @@
 //!   * Sample rate (e.g. "44.1 kHz")
 //!   * Channel configuration (e.g. "Stereo")
 //!
-//! Additional variables for songs and episodes:
-//! - `TITLE`: Track/episode title
+//! Additional variables for songs, episodes and chapters:
+//! - `TITLE`: Track/episode/chapter title
 //! - `DURATION`: Length in seconds
 //!
-//! Additional variables for songs:
-//! - `ALBUM_TITLE`: Album name
+//! Additional variables for songs and chapters:
+//! - `ALBUM_TITLE`: Album name/audio book title
 //!
 //! ## `connected`
 //! Emitted when a controller connects
@@ -117,6 +117,7 @@ use std::{
     fmt::Write,
     ops::ControlFlow,
     pin::Pin,
+    process::Stdio,
     time::Duration,
 };
 
@@ -124,7 +125,7 @@ use futures_util::{SinkExt, StreamExt, stream::SplitSink};
 use log::Level;
 use semver;
 use time::OffsetDateTime;
-use tokio::process::Command;
+use tokio::{io::AsyncWriteExt, process::Command};
 use tokio_tungstenite::{
     MaybeTlsStream, WebSocketStream,
     tungstenite::{
@@ -137,20 +138,20 @@ use uuid::Uuid;
 
 use crate::{
     config::{Config, Credentials},
-    error::{Error, ErrorKind, Result},
+    control,
+    error::{Error, Result},
     events::Event,
     gateway::Gateway,
     player::Player,
     protocol::connect::{
         Body, Channel, Contents, DeviceId, DeviceType, Headers, Ident, Message, Percentage,
         QueueItem, RepeatMode, Status, UserId,
-        queue::{self, MixType},
+        queue::{self, ContainerType, MixType},
         stream,
     },
     proxy,
     tokens::UserToken,
-    track::{DEFAULT_BITS_PER_SAMPLE, DEFAULT_SAMPLE_RATE, Track, TrackId},
-    util::ToF32,
+    track::{Track, TrackId, TrackType},
 };
 
 /// A client on the Deezer Connect protocol.
@@ -226,6 +227,18 @@ pub struct Client {
     /// Channel for sending player and control events
     event_tx: tokio::sync::mpsc::UnboundedSender<Event>,
 
+    /// Handle for local interfaces to control the player
+    control: control::Handle,
+
+    /// Channel for receiving requests from local interfaces
+    control_rx: tokio::sync::mpsc::Receiver<control::Request>,
+
+    /// Channel for publishing the status to local interfaces
+    status_tx: tokio::sync::watch::Sender<control::Status>,
+
+    /// Channel for sending events to local interfaces
+    events_tx: tokio::sync::broadcast::Sender<Event>,
+
     /// Volume level to set on connection and maintain until client sets below maximum.
     /// Helps work around clients that don't properly set volume levels.
     initial_volume: InitialVolume,
@@ -242,6 +255,9 @@ pub struct Client {
     /// Timer for playback progress reports
     reporting_timer: Pin<Box<tokio::time::Sleep>>,
 
+    /// Timer for resolving the next page of queued tracks
+    resolve_timer: Pin<Box<tokio::time::Sleep>>,
+
     /// Current playback queue
     ///
     /// Maintains both track list and shuffle state.
@@ -362,6 +378,9 @@ impl Client {
     /// How often to report playback progress to controller.
     const REPORTING_INTERVAL: Duration = Duration::from_secs(3);
 
+    /// Time between resolving pages of queued tracks in the background.
+    const RESOLVE_INTERVAL: Duration = Duration::from_secs(1);
+
     /// Maximum time to wait for controller heartbeat.
     const WATCHDOG_RX_TIMEOUT: Duration = Duration::from_secs(10);
 
@@ -392,6 +411,12 @@ impl Client {
     /// Cookie name to get JWT expiration from
     const JWT_COOKIE_NAME: &'static str = "refresh-token";
 
+    /// Cookie name to get ARL expiration from
+    const ARL_COOKIE_NAME: &'static str = "arl";
+
+    /// Time before ARL expiration to start warning (7 days)
+    const ARL_EXPIRY_WARNING: Duration = Duration::from_secs(7 * 24 * 3600);
+
     /// Deezer Connect websocket URL.
     const WEBSOCKET_URL: &'static str = "wss://live.deezer.com/ws/";
 
@@ -431,11 +456,13 @@ impl Client {
         // a state variant once `select!` supports `if let` statements:
         // https://github.com/tokio-rs/tokio/issues/4173
         let reporting_timer = tokio::time::sleep(Duration::ZERO);
+        let resolve_timer = tokio::time::sleep(Duration::ZERO);
         let watchdog_rx = tokio::time::sleep(Duration::ZERO);
         let watchdog_tx = tokio::time::sleep(Duration::ZERO);
 
         let (time_to_live_tx, time_to_live_rx) = tokio::sync::mpsc::channel(1);
         let (event_tx, event_rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+        let (control, control_rx, status_tx, events_tx) = control::Handle::channel();
 
         let mut player = player;
         player.register(event_tx.clone());
@@ -445,7 +472,7 @@ impl Client {
             None => InitialVolume::Disabled,
         };
 
-        Ok(Self {
+        let client = Self {
             device_id: config.device_id.into(),
             device_name: config.device_name.clone(),
             device_type: config.device_type,
@@ -469,8 +496,14 @@ impl Client {
             event_rx,
             event_tx,
 
+            control,
+            control_rx,
+            status_tx,
+            events_tx,
+
             player,
             reporting_timer: Box::pin(reporting_timer),
+            resolve_timer: Box::pin(resolve_timer),
 
             discovery_state: DiscoveryState::Available,
             discovery_sessions: HashMap::new(),
@@ -483,7 +516,18 @@ impl Client {
             deferred_position: None,
 
             eavesdrop: config.eavesdrop,
-        })
+        };
+
+        client.publish_status();
+        Ok(client)
+    }
+
+    /// Returns a handle for local interfaces to control the player.
+    ///
+    /// Requests are handled while the client runs, and wait while it reconnects.
+    #[must_use]
+    pub fn control(&self) -> control::Handle {
+        self.control.clone()
     }
 
     /// Retrieves a valid user token from the gateway.
@@ -643,6 +687,22 @@ impl Client {
             .saturating_sub(Self::TOKEN_EXPIRATION_THRESHOLD)
     }
 
+    /// Warns when the ARL is about to expire.
+    ///
+    /// Only possible when Deezer reports when the `arl` cookie expires. Emits
+    /// [`Event::ArlExpiring`] so that hook scripts can remind to log in again.
+    fn check_arl_expiry(&self) {
+        if let Some(ttl) = self.cookie_ttl(Self::ARL_COOKIE_NAME) {
+            if ttl <= Self::ARL_EXPIRY_WARNING {
+                warn!(
+                    "arl expires in {:.1} days",
+                    ttl.as_secs_f32() / (24.0 * 3600.0)
+                );
+                self.notify(Event::ArlExpiring { expires_in: ttl });
+            }
+        }
+    }
+
     /// Starts the client and handles control messages.
     ///
     /// Authentication flow:
@@ -731,6 +791,7 @@ impl Client {
         );
         let session_expiry = tokio::time::sleep(session_ttl);
         tokio::pin!(session_expiry);
+        self.check_arl_expiry();
 
         // The JWT
         let mut jwt_ttl = self.jwt_ttl();
@@ -793,6 +854,7 @@ impl Client {
                                 Ok(()) => {
                                     debug!("session renewed");
                                     session_ttl = self.session_ttl();
+                                    self.check_arl_expiry();
                                 }
                                 Err(e) => {
                                     error!("session renewal failed: {e}");
@@ -841,6 +903,15 @@ impl Client {
                     if let Err(e) = self.report_playback_progress().await {
                         error!("error reporting playback progress: {e}");
                     }
+                    self.sync_bookmark(false).await;
+                    self.publish_status();
+                }
+
+                () = &mut self.resolve_timer, if self.is_connected() && self.player.unresolved(1).is_some() => {
+                    if let Err(e) = self.resolve_queue().await {
+                        error!("error resolving queue: {e}");
+                    }
+                    self.reset_resolve_timer();
                 }
 
                 Some(message) = websocket_rx.next() => {
@@ -857,16 +928,26 @@ impl Client {
                             if let ControlFlow::Break(e) = self.handle_message(&message).await {
                                 break Err(Error::internal(format!("error handling message: {e}")));
                             }
+                            self.publish_status();
                         }
 
                         Err(e) => break Err(Error::cancelled(e.to_string())),
                     }
                 }
 
-                Err(e) = self.player.run(), if self.player.is_started() => break Err(e),
+                Err(e) = self.player.run(), if self.player.is_started() || self.player.is_recovering() => {
+                    self.notify(Event::PlaybackError { error: e.to_string() });
+                    break Err(e);
+                }
 
                 Some(event) = self.event_rx.recv() => {
                     self.handle_event(event).await;
+                    self.publish_status();
+                }
+
+                Some(request) = self.control_rx.recv() => {
+                    self.handle_control(request).await;
+                    self.publish_status();
                 }
             }
         };
@@ -875,6 +956,13 @@ impl Client {
         loop_result
     }
 
+    /// Sends an event to be handled with the others, in order.
+    fn notify(&self, event: Event) {
+        if let Err(e) = self.event_tx.send(event) {
+            error!("failed to send event: {e}");
+        }
+    }
+
     /// Processes received events.
     ///
     /// Handles:
@@ -895,7 +983,11 @@ impl Client {
     /// * `event` - Event to process
     #[allow(clippy::too_many_lines)]
     async fn handle_event(&mut self, event: Event) {
-        let mut command = self.hook.as_ref().map(Command::new);
+        let mut command = self.hook.as_ref().map(|hook| {
+            let mut command = Command::new(hook);
+            command.env("EVENT", event.name());
+            command
+        });
         let track_id = self.player.track().map(Track::id);
 
         debug!("handling event: {event:?}");
@@ -907,7 +999,7 @@ impl Client {
         }
 
         // Next, execute the rest of the event handling logic
-        match event {
+        match &event {
             Event::Play => {
                 if let Some(track_id) = track_id {
                     // Report the playback stream.
@@ -931,65 +1023,28 @@ impl Client {
                     }
 
                     if let Some(command) = command.as_mut() {
-                        command
-                            .env("EVENT", "playing")
-                            .env("TRACK_ID", track_id.to_string());
+                        command.env("TRACK_ID", track_id.to_string());
                     }
                 }
             }
 
             Event::Pause => {
-                if let Some(command) = command.as_mut() {
-                    command.env("EVENT", "paused");
-                }
+                self.sync_bookmark(true).await;
             }
 
             Event::TrackChanged => {
+                // Push the final position of an episode that ended or was skipped.
+                self.sync_bookmark(false).await;
+
                 if let Some(track) = self.player.track() {
                     if let Some(command) = command.as_mut() {
-                        let codec = track.codec().map_or("Unknown".to_string(), |codec| {
-                            codec.to_string().to_uppercase()
-                        });
-
-                        let bitrate = track.bitrate();
-                        let bitrate = match bitrate {
-                            Some(bitrate) => {
-                                if bitrate >= 1000 {
-                                    format!(" {}M", bitrate.to_f32_lossy() / 1000.)
-                                } else {
-                                    format!(" {bitrate}K")
-                                }
-                            }
-                            // If bitrate is unknown, show codec only.
-                            None => String::default(),
-                        };
-
-                        let channels =
-                            match track.channels.unwrap_or(track.typ().default_channels()) {
-                                1 => "Mono".to_string(),
-                                2 => "Stereo".to_string(),
-                                3 => "2.1 Stereo".to_string(),
-                                6 => "5.1 Surround Sound".to_string(),
-                                other => format!("{other} channels"),
-                            };
-                        let decoded = format!(
-                            "PCM {} bit {} kHz, {channels}",
-                            track.bits_per_sample.unwrap_or(DEFAULT_BITS_PER_SAMPLE),
-                            track
-                                .sample_rate
-                                .unwrap_or(DEFAULT_SAMPLE_RATE)
-                                .to_f32_lossy()
-                                / 1000.0,
-                        );
-
                         command
-                            .env("EVENT", "track_changed")
                             .env("TRACK_TYPE", track.typ().to_string())
                             .env("TRACK_ID", track.id().to_string())
                             .env("ARTIST", track.artist())
                             .env("COVER_ID", track.cover_id())
-                            .env("FORMAT", format!("{codec}{bitrate}"))
-                            .env("DECODER", decoded);
+                            .env("FORMAT", track.format())
+                            .env("DECODER", track.decoder());
 
                         if let Some(title) = track.title() {
                             command.env("TITLE", title);
@@ -1000,6 +1055,9 @@ impl Client {
                         if let Some(duration) = track.duration() {
                             command.env("DURATION", duration.as_secs().to_string());
                         }
+                        if self.player.bit_perfect() {
+                            command.env("BIT_PERFECT", self.player.is_bit_perfect().to_string());
+                        }
                     }
                 }
             }
@@ -1007,32 +1065,106 @@ impl Client {
             Event::Connected => {
                 if let Some(command) = command.as_mut() {
                     command
-                        .env("EVENT", "connected")
                         .env("USER_ID", self.user_id().to_string())
                         .env("USER_NAME", self.gateway.user_name().unwrap_or_default());
                 }
             }
 
-            Event::Disconnected => {
+            Event::Disconnected | Event::Stopped => {}
+
+            Event::VolumeChanged { volume } => {
+                if let Some(command) = command.as_mut() {
+                    command.env("VOLUME", volume.to_string());
+                }
+            }
+
+            Event::Seeked { position } => {
+                if let Some(command) = command.as_mut() {
+                    command.env("POSITION", position.as_secs().to_string());
+                }
+            }
+
+            Event::RepeatModeChanged { repeat_mode } => {
+                if let Some(command) = command.as_mut() {
+                    command.env("REPEAT_MODE", repeat_mode.to_string().to_lowercase());
+                }
+            }
+
+            Event::ShuffleChanged { shuffle } => {
+                if let Some(command) = command.as_mut() {
+                    command.env("SHUFFLE", shuffle.to_string());
+                }
+            }
+
+            Event::QueueChanged { queue_id, tracks } => {
+                if let Some(command) = command.as_mut() {
+                    command
+                        .env("QUEUE_ID", queue_id)
+                        .env("TRACKS", tracks.to_string());
+                }
+            }
+
+            Event::TrackUnavailable { track_id, reason } => {
+                if let Some(command) = command.as_mut() {
+                    command
+                        .env("TRACK_ID", track_id.to_string())
+                        .env("REASON", reason);
+                }
+            }
+
+            Event::PlaybackError { error } => {
                 if let Some(command) = command.as_mut() {
-                    command.env("EVENT", "disconnected");
+                    command.env("ERROR", error);
+                }
+            }
+
+            Event::ArlExpiring { expires_in } => {
+                if let Some(command) = command.as_mut() {
+                    command.env("EXPIRES_IN", expires_in.as_secs().to_string());
                 }
             }
         }
 
+        // Notify local interfaces before running the hook script, which may take a while.
+        self.publish_status();
+
+        // Hook scripts get the same JSON document as watchers on stdin.
+        let json = command.as_ref().and_then(|_| {
+            let status = self.status_tx.borrow();
+            let notification = control::Notification {
+                event: event.clone(),
+                status: &status,
+            };
+            serde_json::to_vec(&notification)
+                .inspect_err(|e| error!("failed to serialize {event} event: {e}"))
+                .ok()
+        });
+
+        let _ = self.events_tx.send(event);
+
         if let Some(command) = command.as_mut() {
+            command.stdin(Stdio::piped());
             match command.spawn() {
-                Ok(mut child) => match child.wait().await {
-                    Ok(status) => {
-                        if !status.success() {
-                            error!(
-                                "hook script exited with error {}",
-                                status.code().unwrap_or(-1)
-                            );
+                Ok(mut child) => {
+                    if let (Some(mut stdin), Some(json)) = (child.stdin.take(), json) {
+                        // Scripts that do not read their input may close it early.
+                        if let Err(e) = stdin.write_all(&json).await {
+                            debug!("hook script did not read event: {e}");
                         }
                     }
-                    Err(e) => error!("failed to wait for hook script: {e}"),
-                },
+
+                    match child.wait().await {
+                        Ok(status) => {
+                            if !status.success() {
+                                error!(
+                                    "hook script exited with error {}",
+                                    status.code().unwrap_or(-1)
+                                );
+                            }
+                        }
+                        Err(e) => error!("failed to wait for hook script: {e}"),
+                    }
+                }
                 Err(e) => error!("failed to spawn hook script: {e}"),
             }
         }
@@ -1093,12 +1225,26 @@ impl Client {
         }
     }
 
+    /// Resets the timer for resolving queued tracks.
+    ///
+    /// Schedules the next page according to the resolve interval.
+    #[inline]
+    fn reset_resolve_timer(&mut self) {
+        if let Some(deadline) = from_now(Self::RESOLVE_INTERVAL) {
+            self.resolve_timer.as_mut().reset(deadline);
+        }
+    }
+
     /// Stops the client and cleans up resources.
     ///
+    /// * Bookmarks the current episode
     /// * Disconnects from controller if connected
     /// * Processes remaining events
     /// * Unsubscribes from channels
     pub async fn stop(&mut self) {
+        // Keep the position of an episode that is cut off.
+        self.sync_bookmark(true).await;
+
         if self.is_connected() {
             if let Err(e) = self.disconnect().await {
                 error!("error disconnecting: {e}");
@@ -1132,6 +1278,8 @@ impl Client {
             }
             Err(e) => warn!("jwt logout timed out: {e}"),
         }
+
+        self.publish_status();
     }
 
     /// Creates a message targeted at a specific device.
@@ -1460,9 +1608,7 @@ impl Client {
                 };
 
                 info!("connected to {controller}");
-                if let Err(e) = self.event_tx.send(Event::Connected) {
-                    error!("failed to send connected event: {e}");
-                }
+                self.notify(Event::Connected);
 
                 // Refresh user token to reload configuration (normalization, audio quality)
                 // If token refresh fails, assume ARL expired and signal client restart
@@ -1530,9 +1676,7 @@ impl Client {
         if let Some(controller) = self.controller() {
             info!("disconnected from {controller}");
 
-            if let Err(e) = self.event_tx.send(Event::Disconnected) {
-                error!("failed to send disconnected event: {e}");
-            }
+            self.notify(Event::Disconnected);
         }
 
         // Ensure the player releases the output device.
@@ -1555,11 +1699,14 @@ impl Client {
     ///
     /// Updates local queue and configures player:
     /// * Stores queue metadata
-    /// * Resolves track information
-    /// * Updates player queue
+    /// * Queues placeholders for all tracks
+    /// * Resolves the tracks around the current position
     /// * Handles deferred position
     /// * Extends Flow queues
     ///
+    /// Other tracks are resolved in the background, so that queues of any size are
+    /// published within the network timeout.
+    ///
     /// # Arguments
     ///
     /// * `list` - Published queue content
@@ -1567,25 +1714,61 @@ impl Client {
     /// # Errors
     ///
     /// Returns error if:
-    /// * Queue resolution fails
+    /// * Track IDs are invalid
+    /// * Track resolution fails
     /// * Flow extension fails
+    ///
+    /// Tracks of unknown types are logged and skipped.
     async fn handle_publish_queue(&mut self, list: queue::List) -> Result<()> {
         let shuffled = if list.shuffled { "(shuffled)" } else { "" };
         info!("setting queue to {} {shuffled}", list.id);
 
-        // Await with timeout in order to prevent blocking the select loop.
-        let queue = tokio::time::timeout(Self::NETWORK_TIMEOUT, self.gateway.list_to_queue(&list))
-            .await??;
+        // Queue unsupported tracks as unavailable, so that positions match the controller.
+        let mut unsupported = Vec::new();
+        let tracks = list
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(position, item)| {
+                let id = item.id.parse()?;
+                let typ = TrackType::try_from(item).unwrap_or_else(|e| {
+                    error!("skipping track at position {position}: {e}");
+                    unsupported.push((id, e));
+                    TrackType::default()
+                });
+                let mut track = Track::placeholder(id, typ);
+
+                // Remember which album each track was queued from, so albums play gaplessly.
+                if let Some(context) = list.contexts.get(item.context as usize) {
+                    if context.container.typ.enum_value_or_default()
+                        == ContainerType::CONTAINER_TYPE_ALBUM
+                    {
+                        track.album_container = Some(context.container.context_id.clone());
+                    }
+                }
 
-        let tracks: Vec<_> = queue.into_iter().map(Track::from).collect();
+                Ok(track)
+            })
+            .collect::<Result<Vec<_>>>()?;
 
+        self.notify(Event::QueueChanged {
+            queue_id: list.id.clone(),
+            tracks: tracks.len(),
+        });
         self.queue = Some(list);
         self.player.set_queue(tracks);
+        for (id, e) in unsupported {
+            self.player.mark_unavailable(id, e);
+        }
 
         if let Some(position) = self.deferred_position.take() {
             self.set_position(position);
         }
 
+        // Resolve the tracks around the current position before playback starts.
+        self.resolve_queue().await?;
+        self.reset_resolve_timer();
+
         if self.is_flow() {
             self.extend_queue().await?;
         }
@@ -1593,6 +1776,28 @@ impl Client {
         Ok(())
     }
 
+    /// Resolves the next page of placeholders in the queue.
+    ///
+    /// Resolves up to a page of tracks nearest to the current position, reusing
+    /// track information that the gateway cached before.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the gateway request fails or times out.
+    async fn resolve_queue(&mut self) -> Result<()> {
+        if let Some((typ, ids)) = self.player.unresolved(Gateway::LIST_DATA_PAGE_SIZE) {
+            debug!("resolving {} {typ}s", ids.len());
+
+            // Await with timeout in order to prevent blocking the select loop.
+            let items =
+                tokio::time::timeout(Self::NETWORK_TIMEOUT, self.gateway.list_data(typ, &ids))
+                    .await??;
+            self.player.resolve_tracks(typ, &ids, items);
+        }
+
+        Ok(())
+    }
+
     /// Sends ping message to controller.
     ///
     /// Part of connection keepalive mechanism.
@@ -1656,7 +1861,12 @@ impl Client {
             debug!("extending queue with {} tracks", new_tracks.len());
 
             list.tracks.extend(new_list);
+            let event = Event::QueueChanged {
+                queue_id: list.id.clone(),
+                tracks: list.tracks.len(),
+            };
             self.player.extend_queue(new_tracks);
+            self.notify(event);
             self.refresh_queue().await
         } else {
             Err(Error::failed_precondition(
@@ -1860,14 +2070,9 @@ impl Client {
                 set_volume,
             );
 
-            let mut state_set = true;
-            if let Err(e) = result {
-                // When the output device fails to open or is no longer available
-                if e.kind == ErrorKind::Unavailable {
-                    return self.disconnect().await;
-                }
-                state_set = false;
-            }
+            // When the output device fails to open or is no longer available, the player keeps
+            // trying to reopen it in the background, so the session is not dropped.
+            let state_set = result.is_ok();
 
             // Refresh the queue if the shuffle mode has changed.
             if refresh_queue && self.queue.as_ref().map(|queue| queue.shuffled) == set_shuffle {
@@ -1900,6 +2105,183 @@ impl Client {
         }
     }
 
+    /// Handles a request from a local interface.
+    ///
+    /// Applies the command, if any, and replies with the resulting status. Failed
+    /// commands are replied with their error instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Command and reply channel
+    async fn handle_control(&mut self, request: control::Request) {
+        let result = match request.command {
+            Some(command) => self.handle_command(command).await,
+            None => Ok(()),
+        };
+
+        // The requester may have timed out in the meantime.
+        let _ = request.reply.send(result.map(|()| self.control_status()));
+    }
+
+    /// Applies a command from a local interface.
+    ///
+    /// Commands are applied through [`set_player_state`](Self::set_player_state), like
+    /// those from the controller. The controller, if connected, is kept in sync by
+    /// refreshing the queue when the shuffle mode changes, and reporting playback progress.
+    /// Switches of audio processing, which the controller does not know about, are
+    /// applied to the player directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Command to apply
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// * There is no track to skip or seek to
+    /// * Setting the player state fails
+    /// * Switching on audio processing that is not configured
+    async fn handle_command(&mut self, command: control::Command) -> Result<()> {
+        info!("handling local command: {command}");
+
+        let mut item = None;
+        let mut progress = None;
+        let mut should_play = None;
+        let mut set_shuffle = None;
+        let mut set_repeat_mode = None;
+        let mut set_volume = None;
+
+        match command {
+            control::Command::Play => should_play = Some(true),
+            control::Command::Pause => should_play = Some(false),
+            control::Command::Next | control::Command::Previous => {
+                item = Some(self.skip_item(command == control::Command::Next)?);
+            }
+            control::Command::Seek(position) => {
+                let duration = self
+                    .player
+                    .track()
+                    .and_then(Track::duration)
+                    .ok_or_else(|| Error::failed_precondition("no track to seek in"))?;
+                progress = Some(Percentage::from_ratio(
+                    position.div_duration_f32(duration).min(1.0),
+                ));
+            }
+            control::Command::Volume(volume) => set_volume = Some(volume),
+            control::Command::Repeat(repeat_mode) => set_repeat_mode = Some(repeat_mode),
+            control::Command::Shuffle(shuffle) => set_shuffle = Some(shuffle),
+            control::Command::Equalizer(enabled) => {
+                self.player.set_equalizer(enabled)?;
+                self.publish_status();
+                return Ok(());
+            }
+            control::Command::Crossfeed(enabled) => {
+                self.player.set_crossfeed(enabled);
+                self.publish_status();
+                return Ok(());
+            }
+            control::Command::NightMode(enabled) => {
+                self.player.set_night_mode(enabled);
+                self.publish_status();
+                return Ok(());
+            }
+        }
+
+        // Remember to refresh the queue if the shuffle mode changes.
+        let refresh_queue =
+            set_shuffle.is_some() && self.queue.as_ref().map(|queue| queue.shuffled) != set_shuffle;
+
+        let queue_id = self.queue.as_ref().map(|queue| queue.id.clone());
+        let result = self.set_player_state(
+            queue_id.as_deref(),
+            item,
+            progress,
+            should_play,
+            set_shuffle,
+            set_repeat_mode,
+            set_volume,
+        );
+
+        if self.is_connected() {
+            if refresh_queue && self.queue.as_ref().map(|queue| queue.shuffled) == set_shuffle {
+                if let Err(e) = self.refresh_queue().await {
+                    error!("error refreshing queue: {e}");
+                }
+            }
+
+            if let Err(e) = self.report_playback_progress().await {
+                error!("error reporting playback progress: {e}");
+            }
+        }
+
+        result
+    }
+
+    /// Returns the queue item to skip to.
+    ///
+    /// Wraps around the queue when repeating all tracks.
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - Whether to skip to the next track, or else the previous track
+    ///
+    /// # Errors
+    ///
+    /// Returns `FailedPrecondition` if there is no queue, or no track to skip to.
+    fn skip_item(&self, forward: bool) -> Result<QueueItem> {
+        let queue = self
+            .queue
+            .as_ref()
+            .ok_or_else(|| Error::failed_precondition("no queue to skip in"))?;
+        let len = queue.tracks.len();
+        let current = self.queue_position();
+        let repeat = self.player.repeat_mode() == RepeatMode::All;
+
+        let position = if forward {
+            match current + 1 {
+                next if next < len => Some(next),
+                _ if repeat && len > 0 => Some(0),
+                _ => None,
+            }
+        } else {
+            match current.checked_sub(1) {
+                Some(previous) => Some(previous),
+                None if repeat && len > 0 => Some(len - 1),
+                None => None,
+            }
+        }
+        .ok_or_else(|| {
+            Error::failed_precondition(if forward {
+                "no next track"
+            } else {
+                "no previous track"
+            })
+        })?;
+
+        let track_id = queue.tracks[position].id.parse()?;
+        Ok(QueueItem {
+            queue_id: queue.id.clone(),
+            track_id,
+            position,
+        })
+    }
+
+    /// Returns the position of the current track in the queue, in playing order.
+    ///
+    /// For shuffled queues, the player position is mapped through the shuffle order.
+    #[expect(clippy::cast_possible_truncation)]
+    fn queue_position(&self) -> usize {
+        let player_position = self.player.position();
+        match self.queue.as_ref() {
+            Some(queue) if queue.shuffled => queue
+                .tracks_order
+                .iter()
+                .position(|i| *i == player_position as u32)
+                .unwrap_or_default(),
+            _ => player_position,
+        }
+    }
+
     /// Sets the current playback position in the queue.
     ///
     /// Handles position conversion for shuffled queues:
@@ -1923,6 +2305,11 @@ impl Client {
         }
 
         self.player.set_position(position);
+
+        // Resolve the new position without waiting for the next interval.
+        self.resolve_timer
+            .as_mut()
+            .reset(tokio::time::Instant::now());
     }
 
     /// Updates player state based on controller commands.
@@ -1973,6 +2360,10 @@ impl Client {
     ) -> Result<()> {
         let mut result = Ok(());
 
+        let volume = self.player.volume();
+        let repeat_mode = self.player.repeat_mode();
+        let shuffle = self.queue.as_ref().map(|queue| queue.shuffled);
+
         let current = self.player.position();
         let mut target = current;
         if let Some(item) = item {
@@ -2005,6 +2396,9 @@ impl Client {
                 } else if let Err(e) = self.player.set_progress(progress) {
                     error!("error setting playback position: {e}");
                     result = Err(e);
+                } else if let Some(duration) = self.player.track().and_then(Track::duration) {
+                    let position = duration.mul_f32(progress.as_ratio().clamp(0.0, 1.0));
+                    self.notify(Event::Seeked { position });
                 }
             }
         }
@@ -2073,6 +2467,24 @@ impl Client {
             }
         }
 
+        if self.player.volume() != volume {
+            self.notify(Event::VolumeChanged {
+                volume: self.player.volume().as_percent(),
+            });
+        }
+        if self.player.repeat_mode() != repeat_mode {
+            self.notify(Event::RepeatModeChanged {
+                repeat_mode: self.player.repeat_mode(),
+            });
+        }
+        if let Some(queue) = self.queue.as_ref() {
+            if shuffle.is_some_and(|shuffle| shuffle != queue.shuffled) {
+                self.notify(Event::ShuffleChanged {
+                    shuffle: queue.shuffled,
+                });
+            }
+        }
+
         result
     }
 
@@ -2166,6 +2578,89 @@ impl Client {
         ))
     }
 
+    /// Bookmarks the playback position of the current episode with Deezer, and the final
+    /// position of episodes that ended or were skipped.
+    ///
+    /// Failures are logged only: the bookmark is stored locally anyway, and the next
+    /// change will be pushed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `force` - Whether to bookmark small changes, like when pausing
+    async fn sync_bookmark(&mut self, force: bool) {
+        for (episode_id, bookmark) in self.player.bookmark(force) {
+            match tokio::time::timeout(
+                Self::NETWORK_TIMEOUT,
+                self.gateway.set_bookmark(episode_id, bookmark.position),
+            )
+            .await
+            {
+                Ok(Ok(())) => debug!(
+                    "bookmarked episode {episode_id} at {}s",
+                    bookmark.position.as_secs()
+                ),
+                Ok(Err(e)) => warn!("failed to bookmark episode {episode_id}: {e}"),
+                Err(e) => warn!("bookmarking episode {episode_id} timed out: {e}"),
+            }
+        }
+    }
+
+    /// Returns the status of the player for local interfaces.
+    fn control_status(&self) -> control::Status {
+        let track = self.player.track();
+        let progress = self.player.progress();
+        let position = track.and_then(|track| {
+            if track.is_livestream() {
+                self.player.duration()
+            } else {
+                let duration = track.duration()?;
+                progress.map(|progress| duration.mul_f32(progress.as_ratio().clamp(0.0, 1.0)))
+            }
+        });
+
+        control::Status {
+            connected: self.is_connected(),
+            controller: self
+                .controller()
+                .filter(|_| self.is_connected())
+                .map(|controller| controller.to_string()),
+            playing: self.player.is_playing(),
+            track: track.map(control::TrackStatus::from),
+            position,
+            progress: progress
+                .filter(|_| !track.is_some_and(Track::is_livestream))
+                .map(|progress| progress.as_percent()),
+            volume: self.player.volume().as_percent(),
+            repeat: self.player.repeat_mode().to_string().to_lowercase(),
+            shuffle: self.queue.as_ref().is_some_and(|queue| queue.shuffled),
+            queue_position: track.map(|_| self.queue_position()),
+            queue_length: self.queue.as_ref().map_or(0, |queue| queue.tracks.len()),
+            equalizer: self.player.equalizer_enabled(),
+            crossfeed: self.player.crossfeed_enabled(),
+            night_mode: self.player.night_mode(),
+            normalization_limiter: self
+                .player
+                .normalization_limiting()
+                .map(control::LimiterStatus::from),
+            output_limiter: self
+                .player
+                .output_limiting()
+                .map(control::LimiterStatus::from),
+        }
+    }
+
+    /// Publishes the status to local interfaces that watch it, if it changed.
+    fn publish_status(&self) {
+        let status = self.control_status();
+        self.status_tx.send_if_modified(|current| {
+            let modified = *current != status;
+            if modified {
+                *current = status;
+            }
+            modified
+        });
+    }
+
     /// Reports current playback state to controller.
     ///
     /// Sends current:
@@ -2183,7 +2678,6 @@ impl Client {
     /// * No active queue
     /// * No current track
     /// * Message send fails
-    #[expect(clippy::cast_possible_truncation)]
     async fn report_playback_progress(&mut self) -> Result<()> {
         // Reset the timer regardless of success or failure, to prevent getting
         // stuck in a reporting state.
@@ -2197,8 +2691,6 @@ impl Client {
                     .as_ref()
                     .ok_or_else(|| Error::internal("no active queue"))?;
 
-                let player_position = self.player.position();
-                let mut position = player_position;
                 let progress = self.player.progress();
 
                 // If current progress is 100% and there is a track upcoming, then skip this
@@ -2211,19 +2703,10 @@ impl Client {
                     return Ok(());
                 }
 
-                // If in shuffle mode, find the position of the current track in the shuffled order.
-                if queue.shuffled {
-                    position = queue
-                        .tracks_order
-                        .iter()
-                        .position(|i| *i == player_position as u32)
-                        .unwrap_or_default();
-                }
-
                 let item = QueueItem {
                     queue_id: queue.id.to_string(),
                     track_id: track.id(),
-                    position,
+                    position: self.queue_position(),
                 };
 
                 let progress = Body::PlaybackProgress {
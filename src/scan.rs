@@ -0,0 +1,516 @@
+//! Background loudness scanning of tracks without gain information.
+//!
+//! Most tracks come with gain information from the Deezer API or ReplayGain tags. For the
+//! rest, such as some user uploads and podcasts, this module measures the integrated
+//! loudness once their download completes, so that they can be normalized too.
+//!
+//! # Operation
+//!
+//! 1. While downloading, a copy of the track is mirrored to a temporary file
+//! 2. When the download completes, the copy is decoded and measured on a blocking thread
+//! 3. The loudness is cached per track ID for the rest of the session
+//! 4. When the measurement arrives shortly after the track started playing, its gain is
+//!    ramped in; otherwise it applies from the next playback
+//!
+//! The playback download is not touched: it may be held in memory and cannot be read
+//! twice, which is why the copy is kept separately.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Seek, SeekFrom, Write},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        mpsc,
+    },
+    time::Duration,
+};
+
+use rodio::{ChannelCount, SampleRate, Source, source::SeekError};
+use stream_download::storage::StorageProvider;
+use tempfile::NamedTempFile;
+
+use crate::{
+    audio_file::AudioFile,
+    decoder::Decoder,
+    r128,
+    track::{Track, TrackId},
+    util::{self, ToF32, UNITY_GAIN},
+};
+
+/// Time into playback during which a measured gain is still adopted.
+///
+/// Later measurements are kept for the next playback instead, so that the loudness
+/// does not shift noticeably in the middle of a track.
+const ADOPTION_WINDOW: Duration = Duration::from_secs(10);
+
+/// Time constant of the ramp towards a measured gain.
+const RAMP_TIME: Duration = Duration::from_millis(250);
+
+/// Loudness scanner with a per-session cache.
+#[derive(Debug)]
+pub struct Scanner {
+    /// Integrated loudness per track in LUFS.
+    cache: HashMap<TrackId, f32>,
+
+    /// Downloads being mirrored, waiting to complete.
+    pending: HashMap<TrackId, Pending>,
+
+    /// Gains of tracks that play while their loudness is measured.
+    gains: HashMap<TrackId, Arc<Gain>>,
+
+    /// Sender for measurements, cloned into each scan.
+    tx: mpsc::Sender<(TrackId, Option<f32>)>,
+
+    /// Receiver for measurements.
+    rx: mpsc::Receiver<(TrackId, Option<f32>)>,
+}
+
+/// Mirror of a download in progress.
+#[derive(Debug)]
+struct Pending {
+    /// Temporary file with the copy, deleted when dropped.
+    file: NamedTempFile,
+
+    /// Whether mirroring failed, leaving the copy incomplete.
+    failed: Arc<AtomicBool>,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner {
+    /// Creates a scanner with an empty cache.
+    #[must_use]
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            cache: HashMap::new(),
+            pending: HashMap::new(),
+            gains: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Returns the measured loudness of a track in LUFS, if available.
+    #[must_use]
+    pub fn loudness(&self, id: TrackId) -> Option<f32> {
+        self.cache.get(&id).copied()
+    }
+
+    /// Starts mirroring the download of a track, to scan it when complete.
+    ///
+    /// Returns the file to mirror to, or `None` when no temporary file could be created.
+    pub fn mirror(&mut self, id: TrackId) -> Option<Mirror> {
+        let created = NamedTempFile::new().and_then(|file| {
+            let writer = file.reopen()?;
+            Ok((file, writer))
+        });
+
+        match created {
+            Ok((file, writer)) => {
+                let failed = Arc::new(AtomicBool::new(false));
+                self.pending.insert(
+                    id,
+                    Pending {
+                        file,
+                        failed: failed.clone(),
+                    },
+                );
+                Some(Mirror {
+                    file: writer,
+                    failed,
+                })
+            }
+            Err(e) => {
+                warn!("not scanning loudness of track {id}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Stops scanning a track, for example when it turned out to have gain information.
+    pub fn cancel(&mut self, id: TrackId) {
+        self.pending.remove(&id);
+        self.gains.remove(&id);
+    }
+
+    /// Returns the gain to apply to a track that plays while it is scanned.
+    ///
+    /// The gain starts at unity and is set once the measurement arrives.
+    pub fn gain(&mut self, id: TrackId) -> Arc<Gain> {
+        self.gains.entry(id).or_default().clone()
+    }
+
+    /// Starts scans of completed downloads and receives finished measurements.
+    ///
+    /// Call regularly from the player loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - Tracks in the queue, to check the state of their downloads
+    /// * `gain_target_db` - Target loudness in LUFS
+    pub fn poll(&mut self, queue: &[Track], gain_target_db: f32) {
+        while let Ok((id, loudness)) = self.rx.try_recv() {
+            let gain = self.gains.remove(&id);
+            let Some(loudness) = loudness else {
+                warn!("failed to measure loudness of track {id}");
+                continue;
+            };
+
+            debug!("track {id} measured at {loudness:.1} LUFS");
+            self.cache.insert(id, loudness);
+            if let Some(gain) = gain {
+                gain.set(util::db_to_ratio(gain_target_db - loudness));
+            }
+        }
+
+        // Drop gains of sources that finished playing.
+        self.gains.retain(|_, gain| Arc::strong_count(gain) > 1);
+
+        // Drop mirrors of downloads that were reset, or of tracks no longer queued.
+        self.pending.retain(|id, pending| {
+            !pending.failed.load(Ordering::Relaxed)
+                && queue
+                    .iter()
+                    .any(|track| track.id() == *id && track.handle().is_some())
+        });
+
+        let complete: Vec<TrackId> = self
+            .pending
+            .keys()
+            .copied()
+            .filter(|id| {
+                queue
+                    .iter()
+                    .any(|track| track.id() == *id && track.is_complete())
+            })
+            .collect();
+
+        for id in complete {
+            let Some(pending) = self.pending.remove(&id) else {
+                continue;
+            };
+            let Some(track) = queue.iter().find(|track| track.id() == id) else {
+                continue;
+            };
+
+            let decoder = pending
+                .file
+                .reopen()
+                .map_err(Into::into)
+                .and_then(|file| AudioFile::try_from_file(track, file))
+                .and_then(|file| Decoder::new(track, file));
+
+            match decoder {
+                Ok(decoder) => {
+                    debug!("scanning loudness of {} {track}", track.typ());
+                    let tx = self.tx.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let loudness = r128::integrated_loudness(decoder);
+                        // Keep the copy until the measurement is done.
+                        drop(pending);
+                        let _ = tx.send((id, loudness));
+                    });
+                }
+                Err(e) => {
+                    warn!("failed to scan loudness of {} {track}: {e}", track.typ());
+                }
+            }
+        }
+    }
+}
+
+/// Storage that mirrors a download to a file, while passing it on unchanged.
+#[derive(Debug)]
+pub struct Mirror {
+    /// File to write the copy to.
+    file: File,
+
+    /// Set when writing the copy fails.
+    failed: Arc<AtomicBool>,
+}
+
+impl Mirror {
+    /// Wraps a storage provider, mirroring all that is written to it.
+    #[must_use]
+    pub fn wrap<P>(self, inner: P) -> MirrorStorage<P> {
+        MirrorStorage {
+            inner,
+            mirror: Some(self),
+        }
+    }
+}
+
+/// Storage provider that optionally mirrors downloads.
+#[derive(Debug)]
+pub struct MirrorStorage<P> {
+    /// Storage of the download itself.
+    inner: P,
+
+    /// Where to mirror to, if anywhere.
+    mirror: Option<Mirror>,
+}
+
+impl<P> MirrorStorage<P> {
+    /// Wraps a storage provider without mirroring.
+    #[must_use]
+    pub fn passthrough(inner: P) -> Self {
+        Self {
+            inner,
+            mirror: None,
+        }
+    }
+}
+
+impl<P> StorageProvider for MirrorStorage<P>
+where
+    P: StorageProvider,
+{
+    type Reader = P::Reader;
+    type Writer = MirrorWriter<P::Writer>;
+
+    fn into_reader_writer(
+        self,
+        content_length: Option<u64>,
+    ) -> io::Result<(Self::Reader, Self::Writer)> {
+        let (reader, inner) = self.inner.into_reader_writer(content_length)?;
+        let writer = MirrorWriter {
+            inner,
+            mirror: self.mirror,
+        };
+        Ok((reader, writer))
+    }
+
+    fn max_capacity(&self) -> Option<usize> {
+        self.inner.max_capacity()
+    }
+}
+
+/// Writer that copies all writes and seeks to a mirror.
+///
+/// Errors of the mirror never fail the download: the mirror is abandoned instead.
+#[derive(Debug)]
+pub struct MirrorWriter<W> {
+    /// Writer of the download itself.
+    inner: W,
+
+    /// Where to mirror to, until it fails.
+    mirror: Option<Mirror>,
+}
+
+impl<W> MirrorWriter<W> {
+    /// Applies an operation to the mirror, abandoning it on errors.
+    fn mirror(&mut self, op: impl FnOnce(&mut File) -> io::Result<()>) {
+        if let Some(mirror) = self.mirror.as_mut() {
+            if let Err(e) = op(&mut mirror.file) {
+                warn!("abandoning loudness scan: {e}");
+                mirror.failed.store(true, Ordering::Relaxed);
+                self.mirror = None;
+            }
+        }
+    }
+}
+
+impl<W> Write for MirrorWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.mirror(|file| file.write_all(&buf[..written]));
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.mirror(File::flush);
+        Ok(())
+    }
+}
+
+impl<W> Seek for MirrorWriter<W>
+where
+    W: Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.mirror(|file| file.seek(SeekFrom::Start(position)).map(drop));
+        Ok(position)
+    }
+}
+
+/// Gain ratio that is set once a measurement arrives.
+#[derive(Debug)]
+pub struct Gain(AtomicU32);
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self(AtomicU32::new(UNITY_GAIN.to_bits()))
+    }
+}
+
+impl Gain {
+    /// Returns the gain ratio.
+    #[must_use]
+    #[inline]
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Sets the gain ratio.
+    #[inline]
+    pub fn set(&self, ratio: f32) {
+        self.0.store(ratio.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Applies a gain to a source that may change while it plays.
+///
+/// The source starts at unity gain. A gain set within the first seconds of playback is
+/// ramped in smoothly; later changes are ignored.
+pub fn deferred<I>(input: I, gain: Arc<Gain>) -> Deferred<I>
+where
+    I: Source,
+{
+    let frames_per_second = input.sample_rate().to_f32_lossy();
+    let coefficient = 1.0 - f32::exp(-1.0 / (RAMP_TIME.as_secs_f32() * frames_per_second));
+
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let window = (ADOPTION_WINDOW.as_secs_f32() * frames_per_second) as u64;
+
+    Deferred {
+        input,
+        gain,
+        current: UNITY_GAIN,
+        target: UNITY_GAIN,
+        coefficient,
+        channel: 0,
+        frames: 0,
+        window,
+    }
+}
+
+/// Audio source with a gain that may be set while it plays.
+#[derive(Debug)]
+pub struct Deferred<I> {
+    /// The underlying audio source
+    input: I,
+
+    /// Gain shared with the scanner
+    gain: Arc<Gain>,
+
+    /// Gain applied to the current frame
+    current: f32,
+
+    /// Gain ramped towards
+    target: f32,
+
+    /// Smoothing coefficient of the ramp
+    coefficient: f32,
+
+    /// Channel of the next sample
+    channel: ChannelCount,
+
+    /// Frames played so far
+    frames: u64,
+
+    /// Frames after which the target is no longer updated
+    window: u64,
+}
+
+impl<I> Deferred<I>
+where
+    I: Source,
+{
+    /// Returns a reference to the underlying audio source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying audio source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Consumes self and returns the underlying audio source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Deferred<I>
+where
+    I: Source,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+
+        // Update the gain on frame boundaries only, to keep channels in step.
+        if self.channel == 0 {
+            if self.frames < self.window {
+                self.frames += 1;
+                self.target = self.gain.get();
+            }
+            self.current += (self.target - self.current) * self.coefficient;
+        }
+
+        self.channel += 1;
+        if self.channel >= self.input.channels() {
+            self.channel = 0;
+        }
+
+        Some(sample * self.current)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Deferred<I>
+where
+    I: Source,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Attempts to seek to the specified position.
+    ///
+    /// Seeking does not reopen the adoption window, and keeps the current gain.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.channel = 0;
+        Ok(())
+    }
+}
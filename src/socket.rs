@@ -0,0 +1,436 @@
+//! Line-delimited JSON control protocol on a unix domain socket (Unix only).
+//!
+//! For local control without opening a TCP port. Access is controlled by the
+//! filesystem permissions of the socket. Commands are applied through
+//! [`control`](crate::control), like commands from a Deezer client, which stays in
+//! sync.
+//!
+//! # Protocol
+//!
+//! Clients send one JSON [`Request`] per line, and receive one JSON line in reply:
+//! the [`Status`](crate::control::Status) of the player after handling the request, or an error.
+//! ```text
+//! → {"command": "volume", "volume": 40}
+//! ← {"connected": true, "playing": true, "volume": 40.0, ...}
+//! → {"command": "next"}
+//! ← {"error": "no next track"}
+//! ```
+//!
+//! After a `watch` request, the connection streams a line for every
+//! [`Event`](crate::events::Event), with the status of the player after it, until the
+//! client disconnects:
+//! ```text
+//! → {"command": "watch"}
+//! ← {"event": "track_changed", "status": {...}}
+//! ← {"event": "playing", "status": {...}}
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pleezer::socket::{Client, Request, Server};
+//!
+//! let server = Server::bind("/run/pleezer.sock", 0o660, client.control())?;
+//! // Serves until dropped.
+//!
+//! let mut client = Client::connect("/run/pleezer.sock").await?;
+//! let status = client.request(&Request::Pause).await?;
+//! ```
+
+use std::{
+    fs,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::broadcast::error::RecvError,
+    task::JoinHandle,
+};
+
+use crate::{
+    control::{Command, Handle, Notification},
+    error::{Error, Result},
+    protocol::connect::{Percentage, RepeatMode},
+};
+
+/// Maximum length of a request line in bytes.
+const LINE_MAX: usize = 1024;
+
+/// Time to wait before accepting connections again, after accepting failed.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Request on the socket.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Get the player status
+    Status,
+
+    /// Start or resume playback
+    Play,
+
+    /// Pause playback
+    Pause,
+
+    /// Skip to the next track
+    Next,
+
+    /// Skip to the previous track
+    Previous,
+
+    /// Seek to a position in seconds
+    Seek {
+        /// Position in seconds
+        position: f64,
+    },
+
+    /// Set the volume in percent
+    Volume {
+        /// Volume from 0 to 100
+        volume: f32,
+    },
+
+    /// Set the repeat mode: none, all or one
+    Repeat {
+        /// Repeat mode
+        mode: String,
+    },
+
+    /// Enable or disable shuffle
+    Shuffle {
+        /// Whether to shuffle
+        enabled: bool,
+    },
+
+    /// Switch the equalizer on or off
+    Equalizer {
+        /// Whether to equalize
+        enabled: bool,
+    },
+
+    /// Switch headphone crossfeed on or off
+    Crossfeed {
+        /// Whether to crossfeed
+        enabled: bool,
+    },
+
+    /// Switch night mode on or off
+    NightMode {
+        /// Whether to compress
+        enabled: bool,
+    },
+
+    /// Stream events until disconnected
+    Watch,
+}
+
+impl Request {
+    /// Returns the command to send to the player, or `None` to only get the status.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if an argument is out of range, or if the request
+    /// is to watch events.
+    pub fn command(&self) -> Result<Option<Command>> {
+        let command = match self {
+            Self::Status => return Ok(None),
+            Self::Play => Command::Play,
+            Self::Pause => Command::Pause,
+            Self::Next => Command::Next,
+            Self::Previous => Command::Previous,
+            Self::Seek { position } => {
+                let position = Duration::try_from_secs_f64(*position).map_err(|e| {
+                    Error::invalid_argument(format!("invalid position {position}: {e}"))
+                })?;
+                Command::Seek(position)
+            }
+            Self::Volume { volume } => {
+                if !(0.0..=100.0).contains(volume) {
+                    return Err(Error::invalid_argument(format!(
+                        "invalid volume {volume}: expected 0 to 100"
+                    )));
+                }
+                Command::Volume(Percentage::from_percent(*volume))
+            }
+            Self::Repeat { mode } => match mode.parse() {
+                Ok(RepeatMode::Unrecognized) | Err(_) => {
+                    return Err(Error::invalid_argument(format!(
+                        "invalid repeat mode {mode}: expected none, all or one"
+                    )));
+                }
+                Ok(repeat_mode) => Command::Repeat(repeat_mode),
+            },
+            Self::Shuffle { enabled } => Command::Shuffle(*enabled),
+            Self::Equalizer { enabled } => Command::Equalizer(*enabled),
+            Self::Crossfeed { enabled } => Command::Crossfeed(*enabled),
+            Self::NightMode { enabled } => Command::NightMode(*enabled),
+            Self::Watch => {
+                return Err(Error::invalid_argument("watch is not a command"));
+            }
+        };
+
+        Ok(Some(command))
+    }
+}
+
+/// Server of the control protocol.
+///
+/// Stops accepting connections and removes the socket when dropped.
+#[derive(Debug)]
+pub struct Server {
+    /// Task accepting connections
+    task: JoinHandle<()>,
+
+    /// Path of the socket
+    path: PathBuf,
+}
+
+impl Server {
+    /// Binds to a socket path and starts serving in the background.
+    ///
+    /// Replaces a stale socket left behind by a previous run. Must be called from
+    /// within a Tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the socket to create
+    /// * `mode` - Permissions of the socket, like `0o660`
+    /// * `control` - Handle to control the player with
+    ///
+    /// # Errors
+    ///
+    /// Returns `FailedPrecondition` if another process is listening on the path or
+    /// it is not a socket, or an error if the socket cannot be created.
+    pub fn bind(path: impl AsRef<Path>, mode: u32, control: Handle) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            if !metadata.file_type().is_socket() {
+                return Err(Error::failed_precondition(format!(
+                    "{} exists and is not a socket",
+                    path.display()
+                )));
+            }
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                return Err(Error::failed_precondition(format!(
+                    "{} is in use by another process",
+                    path.display()
+                )));
+            }
+            fs::remove_file(&path)?;
+        }
+
+        // Bind synchronously, so that errors surface at startup.
+        let listener = std::os::unix::net::UnixListener::bind(&path)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+        listener.set_nonblocking(true)?;
+        let listener = UnixListener::from_std(listener)?;
+        info!("serving control socket on {}", path.display());
+
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        error!("failed to accept socket connection: {e}");
+                        tokio::time::sleep(ACCEPT_BACKOFF).await;
+                        continue;
+                    }
+                };
+
+                let control = control.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, &control).await {
+                        debug!("socket connection failed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { task, path })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.task.abort();
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("failed to remove {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Serves requests on a connection until the client disconnects.
+async fn serve(stream: UnixStream, control: &Handle) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    while let Some(line) = read_line(&mut reader).await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let e = Error::invalid_argument(format!("invalid request: {e}"));
+                write_line(&mut writer, &error_json(&e)).await?;
+                continue;
+            }
+        };
+        trace!("socket request: {request:?}");
+
+        if request == Request::Watch {
+            return watch(&mut writer, control).await;
+        }
+
+        let result = match request.command() {
+            Ok(Some(command)) => control.send(command).await,
+            Ok(None) => control.status().await,
+            Err(e) => Err(e),
+        };
+
+        let reply = match result.and_then(|status| serde_json::to_value(status).map_err(Into::into))
+        {
+            Ok(status) => status,
+            Err(e) => {
+                debug!("socket request failed: {e}");
+                error_json(&e)
+            }
+        };
+        write_line(&mut writer, &reply).await?;
+    }
+
+    Ok(())
+}
+
+/// Streams events to a client until it disconnects.
+async fn watch(writer: &mut (impl AsyncWrite + Unpin), control: &Handle) -> Result<()> {
+    let mut events = control.events();
+    let status = control.watch();
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("socket client missed {missed} events");
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+
+        let notification = {
+            let status = status.borrow();
+            serde_json::to_value(Notification {
+                event,
+                status: &status,
+            })?
+        };
+        write_line(writer, &notification).await?;
+    }
+}
+
+/// Reads a line, or returns `None` at the end of the stream.
+///
+/// # Errors
+///
+/// Returns `InvalidArgument` if the line is longer than [`LINE_MAX`].
+async fn read_line(reader: &mut (impl AsyncBufRead + Unpin)) -> Result<Option<String>> {
+    let mut line = String::new();
+    let limit = u64::try_from(LINE_MAX)?.saturating_add(1);
+    if reader.take(limit).read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    if line.len() > LINE_MAX {
+        return Err(Error::invalid_argument(format!(
+            "line longer than {LINE_MAX} bytes"
+        )));
+    }
+
+    Ok(Some(line))
+}
+
+/// Writes a JSON value as a line.
+async fn write_line(
+    writer: &mut (impl AsyncWrite + Unpin),
+    value: &serde_json::Value,
+) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+/// Returns the JSON reply for an error.
+fn error_json(error: &Error) -> serde_json::Value {
+    serde_json::json!({ "error": error.error.to_string() })
+}
+
+/// Client of the control protocol, as used by `pleezer ctl`.
+#[derive(Debug)]
+pub struct Client {
+    /// Connection to the server
+    stream: BufReader<UnixStream>,
+}
+
+impl Client {
+    /// Connects to the socket of a running pleezer.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the socket cannot be connected to.
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let stream = UnixStream::connect(path).await.map_err(|e| {
+            Error::unavailable(format!("cannot connect to {}: {e}", path.display()))
+        })?;
+
+        Ok(Self {
+            stream: BufReader::new(stream),
+        })
+    }
+
+    /// Sends a request and returns the reply.
+    ///
+    /// After a `watch` request, call [`next`](Self::next) for every line instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the connection fails, or the server replies with an error.
+    pub async fn request(&mut self, request: &Request) -> Result<serde_json::Value> {
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        self.stream.get_mut().write_all(&line).await?;
+
+        if *request == Request::Watch {
+            return Ok(serde_json::Value::Null);
+        }
+
+        self.next()
+            .await?
+            .ok_or_else(|| Error::unavailable("connection closed"))
+    }
+
+    /// Returns the next line from the server, or `None` if it closed the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the connection fails, or the line is an error.
+    pub async fn next(&mut self) -> Result<Option<serde_json::Value>> {
+        let mut line = String::new();
+        if self.stream.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+
+        let value: serde_json::Value = serde_json::from_str(&line)?;
+        if let Some(error) = value.get("error").and_then(serde_json::Value::as_str) {
+            return Err(Error::unknown(error.to_string()));
+        }
+
+        Ok(Some(value))
+    }
+}
@@ -16,6 +16,7 @@
 //!   - Songs: Stereo (2 channels)
 //!   - Episodes: Mono (1 channel)
 //!   - Livestreams: Stereo (2 channels)
+//!   - Chapters: Mono (1 channel)
 //!
 //! # Audio Format Support
 //!
@@ -30,12 +31,15 @@
 //! * Livestreams:
 //!   - AAC (in ADTS container)
 //!   - MP3
+//! * Chapters (Audio books):
+//!   - MP3 (CBR, encrypted like songs)
 //!
 //! # Track Lifecycle
 //!
 //! 1. Creation
 //!    * From gateway API response
 //!    * Contains metadata and tokens
+//!    * Or as a placeholder in large queues, resolved when it comes near
 //!
 //! 2. Media Source Resolution
 //!    * Retrieves download URLs
@@ -110,7 +114,7 @@ use crate::{
     http,
     protocol::{
         self, Codec,
-        connect::AudioQuality,
+        connect::{AudioQuality, queue},
         gateway::{self, LivestreamUrls},
         media::{self, Cipher, CipherFormat, Data, Format, Medium},
     },
@@ -141,6 +145,8 @@ pub enum TrackType {
     Episode,
     /// Live radio station with multiple streams
     Livestream,
+    /// Audio book chapter with encrypted download
+    Chapter,
 }
 
 impl TrackType {
@@ -152,7 +158,7 @@ impl TrackType {
     /// Default number of audio channels for this track type.
     ///
     /// * Songs and livestreams use stereo (2 channels)
-    /// * Episodes (podcasts) use mono (1 channel)
+    /// * Episodes (podcasts) and chapters (audio books) use mono (1 channel)
     ///
     /// These defaults match typical encoding settings for each content type.
     /// Actual channel count may differ based on source material.
@@ -160,7 +166,7 @@ impl TrackType {
     pub fn default_channels(&self) -> u16 {
         match self {
             Self::Song | Self::Livestream => Self::STEREO,
-            Self::Episode => Self::MONO,
+            Self::Episode | Self::Chapter => Self::MONO,
         }
     }
 }
@@ -189,6 +195,29 @@ impl fmt::Display for TrackType {
             Self::Song => write!(f, "song"),
             Self::Episode => write!(f, "episode"),
             Self::Livestream => write!(f, "livestream"),
+            Self::Chapter => write!(f, "chapter"),
+        }
+    }
+}
+
+impl TryFrom<&queue::Track> for TrackType {
+    type Error = Error;
+
+    /// Returns the type of a queued track.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unimplemented` for unknown types, naming the track.
+    fn try_from(track: &queue::Track) -> Result<Self> {
+        match track.typ.enum_value() {
+            Ok(queue::TrackType::TRACK_TYPE_SONG) => Ok(Self::Song),
+            Ok(queue::TrackType::TRACK_TYPE_EPISODE) => Ok(Self::Episode),
+            Ok(queue::TrackType::TRACK_TYPE_LIVE) => Ok(Self::Livestream),
+            Ok(queue::TrackType::TRACK_TYPE_CHAPTER) => Ok(Self::Chapter),
+            Err(typ) => Err(Error::unimplemented(format!(
+                "cannot queue track {} of unknown type {typ}",
+                track.id
+            ))),
         }
     }
 }
@@ -201,6 +230,7 @@ impl FromStr for TrackType {
             "song" => Ok(Self::Song),
             "episode" => Ok(Self::Episode),
             "livestream" => Ok(Self::Livestream),
+            "chapter" => Ok(Self::Chapter),
             _ => Err(Error::invalid_argument(format!("unknown track type: {s}"))),
         }
     }
@@ -234,7 +264,7 @@ impl FromStr for TrackType {
 /// ```
 #[derive(Debug)]
 pub struct Track {
-    /// Type of content (song, episode, or livestream)
+    /// Type of content (song, episode, livestream, or chapter)
     typ: TrackType,
 
     /// Unique identifier for the track
@@ -259,15 +289,20 @@ pub struct Track {
     /// * Artist name for songs
     /// * Show name for episodes
     /// * Station name for livestreams
+    /// * Author for chapters
     artist: String,
 
-    /// Album title. Only available for songs.
+    /// Album title. Only available for songs, or the audio book title for chapters.
     album_title: Option<String>,
 
+    /// Album identifier. Only available for songs, or the audio book for chapters.
+    album_id: Option<u64>,
+
     /// Identifier for cover artwork:
     /// * Album art for songs
     /// * Show art for episodes
     /// * Station logo for livestreams
+    /// * Audio book cover for chapters
     cover_id: String,
 
     /// Replay gain value in decibels.
@@ -306,7 +341,7 @@ pub struct Track {
     handle: Option<StreamHandle>,
 
     /// Whether the track is available for download.
-    /// Only available for podcasts and episodes.
+    /// Only available for podcasts, livestreams and chapters.
     /// Songs have this always set to `true`.
     /// Note that the expiry time should be checked separately.
     available: bool,
@@ -335,6 +370,15 @@ pub struct Track {
     /// Set by player after decoder initialization.
     pub channels: Option<u16>,
 
+    /// Context ID of the album that the track was queued from.
+    /// Set by the remote client from the queue contexts.
+    /// None when queued from other contexts, like playlists or Flow.
+    pub album_container: Option<String>,
+
+    /// Whether the metadata of the track was resolved.
+    /// Placeholders only know their ID and type, and cannot be played yet.
+    resolved: bool,
+
     /// Fallback track to use when primary track is unavailable.
     /// * Contains complete track metadata
     /// * Used for alternative versions of same song
@@ -424,6 +468,61 @@ impl Track {
     /// Value of 60KB matches official client behavior.
     const PREFETCH_DEFAULT: usize = 60 * 1024;
 
+    /// Creates a placeholder for a track whose metadata is not resolved yet.
+    ///
+    /// Placeholders keep the place of a track in the queue, until they are resolved
+    /// with metadata from the gateway. They cannot be played before that.
+    #[must_use]
+    pub fn placeholder(id: TrackId, typ: TrackType) -> Self {
+        Self {
+            typ,
+            id,
+            track_token: None,
+            title: None,
+            artist: String::new(),
+            album_title: None,
+            album_id: None,
+            cover_id: String::new(),
+            duration: None,
+            gain: None,
+            expiry: None,
+            quality: AudioQuality::Unknown,
+            buffered: Arc::new(Mutex::new(None)),
+            file_size: None,
+            cipher: Cipher::BF_CBC_STRIPE,
+            handle: None,
+            available: false,
+            external: false,
+            external_url: None,
+            bitrate: None,
+            codec: None,
+            sample_rate: None,
+            bits_per_sample: None,
+            channels: None,
+            album_container: None,
+            resolved: false,
+            fallback: None,
+        }
+    }
+
+    /// Returns whether the metadata of the track was resolved.
+    ///
+    /// False for placeholders.
+    #[must_use]
+    #[inline]
+    pub fn is_resolved(&self) -> bool {
+        self.resolved
+    }
+
+    /// Resolves the track with metadata from the gateway.
+    ///
+    /// Keeps the album container that the track was queued from.
+    pub fn resolve(&mut self, item: gateway::ListData) {
+        let album_container = self.album_container.take();
+        *self = Self::from(item);
+        self.album_container = album_container;
+    }
+
     /// Returns the track's unique identifier.
     #[must_use]
     #[inline]
@@ -491,12 +590,31 @@ impl Track {
         self.album_title.as_deref()
     }
 
+    /// Returns the album identifier for this track.
+    #[must_use]
+    #[inline]
+    pub fn album_id(&self) -> Option<u64> {
+        self.album_id
+    }
+
+    /// Returns whether this track is from the same album as another.
+    ///
+    /// Tracks are from the same album when they were queued from the same album, or
+    /// have the same album identifier. Chapters of the same audio book count as the
+    /// same album.
+    #[must_use]
+    pub fn is_same_album(&self, other: &Self) -> bool {
+        (self.album_container.is_some() && self.album_container == other.album_container)
+            || (self.album_id.is_some() && self.typ == other.typ && self.album_id == other.album_id)
+    }
+
     /// Returns the cover art identifier for this track.
     ///
     /// Returns:
     /// * Album cover ID for songs (use with "<https://cdn-images.dzcdn.net/images/cover>")
     /// * Podcast artwork ID for episodes (use with "<https://cdn-images.dzcdn.net/images/talk>")
     /// * Station logo ID for livestreams (use with "<https://cdn-images.dzcdn.net/images/cover>")
+    /// * Audio book cover ID for chapters (use with "<https://cdn-images.dzcdn.net/images/cover>")
     ///
     /// Append "/{id}/{resolution}x{resolution}.{format}" where:
     /// * `resolution` is the desired size in pixels (up to 1920)
@@ -509,6 +627,26 @@ impl Track {
         &self.cover_id
     }
 
+    /// Returns the URL of the cover art for this track, as a JPEG.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolution` - Size in pixels (up to 1920)
+    ///
+    /// Returns None if the track has no cover art.
+    #[must_use]
+    pub fn cover_url(&self, resolution: u16) -> Option<String> {
+        if self.cover_id.is_empty() {
+            return None;
+        }
+
+        let kind = if self.is_podcast() { "talk" } else { "cover" };
+        Some(format!(
+            "https://cdn-images.dzcdn.net/images/{kind}/{}/{resolution}x{resolution}.jpg",
+            self.cover_id
+        ))
+    }
+
     /// Returns the track's expiration time.
     ///
     /// After this time, the track becomes unavailable for download
@@ -712,7 +850,8 @@ impl Track {
     /// Retrieves a media source for the track.
     ///
     /// Attempts to get download URLs for the requested quality level,
-    /// falling back to lower qualities if necessary.
+    /// falling back to lower qualities if necessary. Songs and chapters are
+    /// requested with their track token; external content uses its own URLs.
     ///
     /// # Arguments
     ///
@@ -862,7 +1001,7 @@ impl Track {
     #[must_use]
     #[inline]
     pub fn is_deezer(&self) -> bool {
-        self.typ == TrackType::Song && !self.is_user_uploaded()
+        matches!(self.typ, TrackType::Song | TrackType::Chapter) && !self.is_user_uploaded()
     }
 
     #[must_use]
@@ -874,7 +1013,7 @@ impl Track {
     /// Opens a stream for downloading or streaming content.
     ///
     /// Behavior varies by content type:
-    /// * Songs - Downloads encrypted content
+    /// * Songs and chapters - Downloads encrypted content
     /// * Episodes - Opens direct stream
     /// * Livestreams - Opens selected quality stream
     ///
@@ -1273,6 +1412,47 @@ impl Track {
         self.codec
     }
 
+    /// Returns a description of the encoded audio format, like "FLAC 1.411M" or "MP3 320K".
+    ///
+    /// Shows the codec only if the bitrate is unknown, and "Unknown" if the codec is.
+    #[must_use]
+    pub fn format(&self) -> String {
+        let codec = self.codec.map_or("Unknown".to_string(), |codec| {
+            codec.to_string().to_uppercase()
+        });
+
+        match self.bitrate {
+            Some(bitrate) if bitrate >= 1000 => {
+                format!("{codec} {}M", bitrate.to_f32_lossy() / 1000.)
+            }
+            Some(bitrate) => format!("{codec} {bitrate}K"),
+            None => codec,
+        }
+    }
+
+    /// Returns a description of the decoded audio, like "PCM 16 bit 44.1 kHz, Stereo".
+    ///
+    /// Falls back to the defaults for the content type, until the stream is probed.
+    #[must_use]
+    pub fn decoder(&self) -> String {
+        let channels = match self.channels.unwrap_or(self.typ.default_channels()) {
+            1 => "Mono".to_string(),
+            2 => "Stereo".to_string(),
+            3 => "2.1 Stereo".to_string(),
+            6 => "5.1 Surround Sound".to_string(),
+            other => format!("{other} channels"),
+        };
+
+        format!(
+            "PCM {} bit {} kHz, {channels}",
+            self.bits_per_sample.unwrap_or(DEFAULT_BITS_PER_SAMPLE),
+            self.sample_rate
+                .unwrap_or(DEFAULT_SAMPLE_RATE)
+                .to_f32_lossy()
+                / 1000.0,
+        )
+    }
+
     /// Returns the size of audio data to prefetch before playback.
     ///
     /// The prefetch size is calculated based on:
@@ -1327,17 +1507,19 @@ impl Track {
 /// * Livestreams - Uses station metadata and quality streams
 impl From<gateway::ListData> for Track {
     fn from(item: gateway::ListData) -> Self {
-        let (gain, album_title) = if let gateway::ListData::Song {
-            gain, album_title, ..
-        } = &item
-        {
-            (gain.as_ref(), Some(album_title))
-        } else {
-            (None, None)
+        let (gain, album_title) = match &item {
+            gateway::ListData::Song {
+                gain, album_title, ..
+            } => (gain.as_ref(), Some(album_title)),
+            gateway::ListData::Chapter {
+                audiobook_title, ..
+            } => (None, Some(audiobook_title)),
+            _ => (None, None),
         };
 
         let (available, external, external_url, fallback) = match &item {
             gateway::ListData::Song { fallback, .. } => (true, false, None, fallback.clone()),
+            gateway::ListData::Chapter { available, .. } => (*available, false, None, None),
             gateway::ListData::Episode {
                 available,
                 external,
@@ -1370,6 +1552,7 @@ impl From<gateway::ListData> for Track {
             title: item.title().map(ToOwned::to_owned),
             artist: item.artist().to_owned(),
             album_title: album_title.map(ToString::to_string),
+            album_id: item.album_id(),
             cover_id: item.cover_id().to_owned(),
             duration: item.duration(),
             gain: gain.map(|gain| gain.to_f32_lossy()),
@@ -1387,6 +1570,8 @@ impl From<gateway::ListData> for Track {
             sample_rate: None,
             bits_per_sample: None,
             channels: None,
+            album_container: None,
+            resolved: true,
             fallback: fallback.map(|boxed| Box::new((*boxed).into())),
         }
     }
@@ -15,6 +15,19 @@
 //! * Default volume is 1.0 (100%)
 //! * Changes are immediately reflected across all threads
 //!
+//! # Volume Curves
+//!
+//! Volume settings are mapped to amplitudes by a [`Scale`]:
+//! * Linear, cubic or logarithmic curve
+//! * Configurable dynamic range for the logarithmic curve, 60 dB by default
+//! * Optional maximum and minimum settings, to protect amplifiers and speakers
+//!
+//! # Fixed Output
+//!
+//! When an external amplifier controls the volume, the output stays at full scale and
+//! dithering only requantizes the track bit depth. The volume then only fades the output
+//! in and out, while the listening level for loudness compensation is set separately.
+//!
 //! # Dithering
 //!
 //! When configured with DAC bit depth information, provides:
@@ -23,6 +36,12 @@
 //! * Volume-aware dither scaling
 //! * Source/destination bit depth tracking
 //!
+//! # Bit-Perfect Bypass
+//!
+//! When enabled, processing is bypassed at full volume for lossless tracks whose bit
+//! depth the output can carry. Samples then reach the output unchanged, instead of
+//! being dithered to the DAC resolution.
+//!
 //! # Example
 //!
 //! ```rust
@@ -43,10 +62,15 @@
 //! }
 //! ```
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
 
 use crate::{
     dither::DC_COMPENSATION,
+    error::{Error, Result},
     track::DEFAULT_BITS_PER_SAMPLE,
     util::{ToF32, UNITY_GAIN},
 };
@@ -58,12 +82,29 @@ use crate::{
 /// * Source/destination bit depth management
 /// * Dynamic quantization step calculation
 /// * Volume-aware dither scaling
+/// * Optional bit-perfect bypass at full volume
 #[derive(Debug)]
 pub struct Volume {
     /// Current volume level stored as bits of an f32.
     /// Uses atomic storage for thread-safe access.
     volume: AtomicU32,
 
+    /// Whether the volume was set to full scale.
+    /// Tracked separately because the stored volume leaves headroom for dithering.
+    unity: AtomicBool,
+
+    /// Current track/source material bit depth.
+    /// Zero if unknown, for example for lossy codecs.
+    track_bit_depth: AtomicU32,
+
+    /// Bit depth that the output carries without loss.
+    /// None if bit-perfect bypass is disabled.
+    bit_perfect_depth: Option<u32>,
+
+    /// Listening level stored as bits of an f32, when the volume is controlled externally.
+    /// None if the output is attenuated in software.
+    fixed_level: Option<AtomicU32>,
+
     /// Optional dithering configuration.
     /// None if dithering is disabled (no DAC bit depth provided).
     dither: Option<Dither>,
@@ -81,10 +122,6 @@ struct Dither {
     /// Fixed value determined at initialization.
     dac_bit_depth: f32,
 
-    /// Current track/source material bit depth.
-    /// Stored atomically for thread-safe updates.
-    track_bit_depth: AtomicU32,
-
     /// Current quantization step size for dithering.
     /// Stored as bits of an f32 for atomic updates.
     quantization_step: AtomicU32,
@@ -97,6 +134,10 @@ impl Default for Volume {
     fn default() -> Self {
         Self {
             volume: AtomicU32::new(DEFAULT_VOLUME.to_bits()),
+            unity: AtomicBool::new(true),
+            track_bit_depth: AtomicU32::new(0),
+            bit_perfect_depth: None,
+            fixed_level: None,
             dither: None,
         }
     }
@@ -126,9 +167,12 @@ impl Volume {
         let track_bits = DEFAULT_BITS_PER_SAMPLE;
         Self {
             volume: AtomicU32::new(volume.to_bits()),
+            unity: AtomicBool::new(volume >= UNITY_GAIN),
+            track_bit_depth: AtomicU32::new(0),
+            bit_perfect_depth: None,
+            fixed_level: None,
             dither: dac_bits.map(|dac_bits| Dither {
                 dac_bit_depth: dac_bits,
-                track_bit_depth: AtomicU32::new(track_bits),
                 quantization_step: AtomicU32::new(
                     calculate_quantization_step(dac_bits, track_bits, volume).to_bits(),
                 ),
@@ -136,6 +180,73 @@ impl Volume {
         }
     }
 
+    /// Enables bit-perfect bypass.
+    ///
+    /// At full volume, lossless tracks of up to `output_bits` bits per sample then pass
+    /// through without volume scaling or dithering.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_bits` - Bit depth that the output sample format carries without loss
+    #[must_use]
+    pub fn with_bit_perfect(mut self, output_bits: u32) -> Self {
+        self.bit_perfect_depth = Some(output_bits);
+        self
+    }
+
+    /// Enables a fixed output, for volume control by an external amplifier.
+    ///
+    /// The volume then only fades the output in and out, while the listening level
+    /// is set separately with [`set_level`](Self::set_level).
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Initial listening level as an amplitude (0.0 to 1.0)
+    #[must_use]
+    pub fn with_fixed_output(mut self, level: f32) -> Self {
+        self.fixed_level = Some(AtomicU32::new(level.to_bits()));
+        self
+    }
+
+    /// Returns the listening level as an amplitude (0.0 to 1.0).
+    ///
+    /// This is the volume, unless the output is fixed and the volume is controlled
+    /// externally. Loudness compensation follows the listening level.
+    #[must_use]
+    pub fn level(&self) -> f32 {
+        self.fixed_level.as_ref().map_or_else(
+            || self.volume(),
+            |level| f32::from_bits(level.load(Ordering::Relaxed)),
+        )
+    }
+
+    /// Sets the listening level of a fixed output.
+    ///
+    /// Has no effect unless the output is fixed: the listening level then follows
+    /// the volume.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Listening level as an amplitude (0.0 to 1.0)
+    pub fn set_level(&self, level: f32) {
+        if let Some(fixed_level) = self.fixed_level.as_ref() {
+            fixed_level.store(level.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Returns whether volume scaling and dithering may currently be skipped.
+    ///
+    /// True when bit-perfect bypass is enabled, the volume is at full scale, and the
+    /// bit depth of the current track is known and fits the output. Only the volume
+    /// stage is considered: samples that were processed before it still need dither.
+    #[must_use]
+    pub fn is_bypassed(&self) -> bool {
+        self.bit_perfect_depth.is_some_and(|output_bits| {
+            let track_bits = self.track_bit_depth.load(Ordering::Relaxed);
+            self.unity.load(Ordering::Relaxed) && track_bits > 0 && track_bits <= output_bits
+        })
+    }
+
     /// Returns the current quantization step size used for dithering.
     ///
     /// The quantization step determines the magnitude of dither noise added
@@ -178,6 +289,8 @@ impl Volume {
     /// * Quantization step size
     /// * Dithering parameters
     pub fn set_volume(&self, volume: f32) -> f32 {
+        self.unity.store(volume >= UNITY_GAIN, Ordering::Relaxed);
+
         let mut new = volume;
         if let Some(dither) = self.dither.as_ref() {
             let quantization_step =
@@ -200,14 +313,13 @@ impl Volume {
     /// Returns the current track bit depth setting.
     ///
     /// This represents the bit depth of the source audio material.
-    /// If no dithering is configured, returns the default bit depth.
+    /// If the bit depth is unknown, returns the default bit depth.
     #[must_use]
     pub fn track_bit_depth(&self) -> u32 {
-        self.dither
-            .as_ref()
-            .map_or(DEFAULT_BITS_PER_SAMPLE, |dither| {
-                dither.track_bit_depth.load(Ordering::Relaxed)
-            })
+        match self.track_bit_depth.load(Ordering::Relaxed) {
+            0 => DEFAULT_BITS_PER_SAMPLE,
+            track_bits => track_bits,
+        }
     }
 
     /// Sets the track bit depth and updates the quantization parameters.
@@ -219,13 +331,16 @@ impl Volume {
     ///
     /// This updates both the track bit depth and recalculates the quantization step
     /// based on the DAC bit depth, track bit depth, and current volume settings.
-    /// Has no effect if dithering is disabled.
     pub fn set_track_bit_depth(&self, track_bits: Option<u32>) {
+        self.track_bit_depth
+            .store(track_bits.unwrap_or(0), Ordering::Relaxed);
+
         if let Some(dither) = self.dither.as_ref() {
-            let track_bits = track_bits.unwrap_or(DEFAULT_BITS_PER_SAMPLE);
-            let quant_level =
-                calculate_quantization_step(dither.dac_bit_depth, track_bits, self.volume());
-            dither.track_bit_depth.store(track_bits, Ordering::Relaxed);
+            let quant_level = calculate_quantization_step(
+                dither.dac_bit_depth,
+                self.track_bit_depth(),
+                self.volume(),
+            );
             dither
                 .quantization_step
                 .store(quant_level.to_bits(), Ordering::Relaxed);
@@ -301,3 +416,180 @@ fn calculate_quantization_step(dac_bits: f32, track_bits: u32, volume: f32) -> f
         calculate_effective_bit_depth(dac_bits, track_bits, volume) - 1.0,
     )
 }
+
+/// Dynamic range of the logarithmic volume curve by default, in dB.
+pub const DEFAULT_VOLUME_RANGE: f32 = 60.0;
+
+/// Shape of the mapping from volume setting to amplitude.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Curve {
+    /// Amplitude equals the setting.
+    ///
+    /// For amplifiers and mixers that apply their own curve.
+    Linear,
+
+    /// Amplitude is the cube of the setting.
+    ///
+    /// Close to logarithmic over a range of about 60 dB, but falls to silence more
+    /// gradually.
+    Cubic,
+
+    /// Attenuation in dB is proportional to the setting, over a configurable range.
+    ///
+    /// Matches the perception of loudness, so that equal steps sound equal.
+    #[default]
+    Logarithmic,
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => write!(f, "linear"),
+            Self::Cubic => write!(f, "cubic"),
+            Self::Logarithmic => write!(f, "log"),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "cubic" => Ok(Self::Cubic),
+            "log" | "logarithmic" => Ok(Self::Logarithmic),
+            _ => Err(Error::invalid_argument(format!("invalid volume curve {s}"))),
+        }
+    }
+}
+
+/// Scales volume settings to amplitudes.
+///
+/// Applies the volume limits first, then the curve. A setting of zero always mutes,
+/// regardless of the minimum.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct Scale {
+    /// Shape of the mapping
+    curve: Curve,
+
+    /// Dynamic range of the logarithmic curve in dB
+    range: f32,
+
+    /// Lowest setting that is not muted (0.0 to 1.0)
+    min: f32,
+
+    /// Highest setting (0.0 to 1.0)
+    max: f32,
+}
+
+impl Default for Scale {
+    /// Creates the logarithmic scale over 60 dB, without limits.
+    fn default() -> Self {
+        Self {
+            curve: Curve::default(),
+            range: DEFAULT_VOLUME_RANGE,
+            min: 0.0,
+            max: UNITY_GAIN,
+        }
+    }
+}
+
+impl Scale {
+    /// Creates a volume scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - Shape of the mapping
+    /// * `range` - Dynamic range of the logarithmic curve in dB
+    /// * `min` - Lowest setting that is not muted (0.0 to 1.0)
+    /// * `max` - Highest setting (0.0 to 1.0)
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if the range is not a positive number, or the limits are out of
+    /// range or reversed.
+    pub fn new(curve: Curve, range: f32, min: f32, max: f32) -> Result<Self> {
+        if !range.is_finite() || range <= 0.0 {
+            return Err(Error::invalid_argument(format!(
+                "volume range must be positive, got {range} dB"
+            )));
+        }
+
+        if !(0.0..=UNITY_GAIN).contains(&min) || !(0.0..=UNITY_GAIN).contains(&max) || min > max {
+            return Err(Error::invalid_argument(format!(
+                "invalid volume limits: minimum {min} and maximum {max}"
+            )));
+        }
+
+        Ok(Self {
+            curve,
+            range,
+            min,
+            max,
+        })
+    }
+
+    /// Returns the shape of the mapping.
+    #[must_use]
+    pub fn curve(&self) -> Curve {
+        self.curve
+    }
+
+    /// Applies the volume limits to a volume setting.
+    ///
+    /// A setting of zero stays zero, so that muting still works.
+    #[must_use]
+    pub fn limit(&self, volume: f32) -> f32 {
+        if volume <= 0.0 {
+            return 0.0;
+        }
+
+        volume.clamp(self.min, self.max)
+    }
+
+    /// Converts a volume setting to an amplitude.
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - Volume setting between 0.0 and 1.0
+    ///
+    /// # Returns
+    ///
+    /// Amplitude between 0.0 and 1.0
+    ///
+    /// # Formula
+    ///
+    /// For the logarithmic curve with a range of R dB and 0.0 < v < 1.0:
+    /// ```text
+    /// amplitude = 10^(R * (v - 1) / 20)
+    /// if v < 0.1: amplitude *= v * 10
+    /// ```
+    ///
+    /// The linear fade below 10% reaches silence at zero, instead of stopping at -R dB.
+    ///
+    /// Based on research from: <https://www.dr-lex.be/info-stuff/volumecontrols.html>
+    #[must_use]
+    pub fn amplitude(&self, volume: f32) -> f32 {
+        let volume = self.limit(volume);
+        if volume <= 0.0 {
+            return 0.0;
+        }
+
+        match self.curve {
+            Curve::Linear => volume,
+            Curve::Cubic => volume.powi(3),
+            Curve::Logarithmic => {
+                if volume >= UNITY_GAIN {
+                    return UNITY_GAIN;
+                }
+
+                let mut amplitude = f32::powf(10.0, self.range * (volume - 1.0) / 20.0);
+                if volume < 0.1 {
+                    amplitude *= volume * 10.0;
+                }
+                amplitude
+            }
+        }
+    }
+}
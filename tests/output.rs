@@ -0,0 +1,156 @@
+//! Rendering into a custom audio sink.
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use pleezer::{
+    error::{Error, Result},
+    output::{AudioSink, Format, Output},
+};
+use rodio::{Sample, buffer::SamplesBuffer};
+
+/// Latency that the capture sink reports.
+const LATENCY: Duration = Duration::from_millis(30);
+
+/// Underruns that the capture sink reports.
+const UNDERRUNS: u64 = 2;
+
+/// Audio sink that keeps everything written to it.
+#[derive(Default)]
+struct Capture {
+    samples: Vec<Sample>,
+    writes: usize,
+    playing: bool,
+    fail: bool,
+}
+
+impl AudioSink for Capture {
+    fn format(&self) -> Format {
+        Format::default()
+    }
+
+    fn write(&mut self, samples: &[Sample]) -> Result<()> {
+        if self.fail {
+            return Err(Error::unavailable("capture failed"));
+        }
+
+        self.samples.extend_from_slice(samples);
+        self.writes += 1;
+        Ok(())
+    }
+
+    fn play(&mut self) -> Result<()> {
+        self.playing = true;
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<()> {
+        self.playing = false;
+        Ok(())
+    }
+
+    fn latency(&self) -> Duration {
+        LATENCY
+    }
+
+    fn underruns(&self) -> u64 {
+        UNDERRUNS
+    }
+}
+
+/// Waits until a condition holds, or panics after a while.
+fn wait_for(mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out waiting for output");
+        thread::sleep(Output::BLOCK_DURATION);
+    }
+}
+
+/// Returns how many samples are at a level.
+fn count(samples: &[Sample], level: Sample) -> usize {
+    samples.iter().filter(|&&sample| sample == level).count()
+}
+
+/// Opens an output that renders into a new capture sink.
+fn open(capture: Capture) -> (Arc<Mutex<Capture>>, Output) {
+    let sink = Arc::new(Mutex::new(capture));
+    let output =
+        Output::open(Arc::clone(&sink) as Arc<Mutex<dyn AudioSink>>).expect("output should open");
+    (sink, output)
+}
+
+#[test]
+fn renders_samples_to_sink() {
+    let (sink, output) = open(Capture::default());
+    let format = output.format();
+
+    // One second of a constant level, easy to tell apart from silence.
+    let level = 0.25;
+    let len = usize::try_from(format.sample_rate).unwrap() * usize::from(format.channels);
+    output.mixer().add(SamplesBuffer::new(
+        format.channels,
+        format.sample_rate,
+        vec![level; len],
+    ));
+
+    // Rendering starts paused.
+    thread::sleep(Output::BLOCK_DURATION * 5);
+    assert!(sink.lock().unwrap().samples.is_empty());
+
+    output.set_paused(false);
+    wait_for(|| count(&sink.lock().unwrap().samples, level) >= len);
+
+    let sink = sink.lock().unwrap();
+    assert!(sink.playing);
+    assert_eq!(sink.samples.len() % usize::from(format.channels), 0);
+    assert!(
+        sink.samples
+            .iter()
+            .all(|&sample| sample == level || sample == 0.0),
+        "samples should pass through unchanged"
+    );
+    assert_eq!(count(&sink.samples, level), len);
+}
+
+#[test]
+fn reports_sink_latency_and_underruns() {
+    let (sink, output) = open(Capture::default());
+    let format = output.format();
+    assert_eq!(output.latency(), Duration::ZERO);
+    assert_eq!(output.underruns(), 0);
+
+    output.mixer().add(SamplesBuffer::new(
+        format.channels,
+        format.sample_rate,
+        vec![0.5; 1024],
+    ));
+    output.set_paused(false);
+    wait_for(|| sink.lock().unwrap().writes > 0);
+    wait_for(|| output.latency() == LATENCY);
+
+    assert_eq!(output.underruns(), UNDERRUNS);
+    assert!(!output.is_failed());
+}
+
+#[test]
+fn stops_when_sink_fails() {
+    let (sink, output) = open(Capture {
+        fail: true,
+        ..Capture::default()
+    });
+    let format = output.format();
+
+    output.mixer().add(SamplesBuffer::new(
+        format.channels,
+        format.sample_rate,
+        vec![0.5; 1024],
+    ));
+    output.set_paused(false);
+    wait_for(|| output.is_failed());
+
+    assert!(sink.lock().unwrap().samples.is_empty());
+}